@@ -3,36 +3,56 @@ use std::io::{Error, ErrorKind};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use algorithm::{JsonQuery, LruCache};
+use algorithm::{CacheStats, Column, ColumnKind, LruCache, QueryPlan};
 use itertools::izip;
 use reqwest_middleware::ClientWithMiddleware;
 use schemas::{CandleStick, reload::Reload};
-use serde_json::Value;
 use tracing::{debug, info};
 
 const INDEXES: [&str; 3] = ["VNINDEX", "HNXINDEX", "VN30"];
 const SECONDS_IN_WEEK: i64 = 7 * 24 * 60 * 60;
 
-trait JsonValueExt {
-    fn as_f64_lossy(&self) -> f64;
-    fn as_i32_lossy(&self) -> i32;
-}
-
-impl JsonValueExt for Value {
-    fn as_f64_lossy(&self) -> f64 {
-        match self {
-            Value::Number(n) => n.as_f64().unwrap_or(0.0),
-            Value::String(s) => s.parse::<f64>().unwrap_or(0.0),
-            _ => 0.0,
-        }
-    }
-    fn as_i32_lossy(&self) -> i32 {
-        match self {
-            Value::Number(n) => n.as_i64().unwrap_or(0) as i32,
-            Value::String(s) => s.parse::<i32>().unwrap_or(0),
-            _ => 0,
-        }
-    }
+/// Cột thời gian là `I64`, các cột giá/khối lượng là `F64`.
+const COLUMN_KINDS: [ColumnKind; 6] = [
+    ColumnKind::I64,
+    ColumnKind::F64,
+    ColumnKind::F64,
+    ColumnKind::F64,
+    ColumnKind::F64,
+    ColumnKind::F64,
+];
+
+/// Ghép 6 cột `t, o, h, l, c, v` của `QueryPlan` thành nến, tối đa `limit` cây
+/// (`0` = không giới hạn).
+fn to_candles(columns: &[Column], limit: usize) -> Vec<CandleStick> {
+    let (Some(t), Some(o), Some(h), Some(l), Some(c), Some(v)) = (
+        columns[0].as_i64(),
+        columns[1].as_f64(),
+        columns[2].as_f64(),
+        columns[3].as_f64(),
+        columns[4].as_f64(),
+        columns[5].as_f64(),
+    ) else {
+        return vec![];
+    };
+
+    let count = if limit > 0 {
+        limit.min(t.len())
+    } else {
+        t.len()
+    };
+
+    izip!(t, o, h, l, c, v)
+        .take(count)
+        .map(|(&t, &o, &h, &l, &c, &v)| CandleStick {
+            t: t as i32,
+            o,
+            h,
+            l,
+            c,
+            v,
+        })
+        .collect()
 }
 
 // Define the layers from the inside out
@@ -62,7 +82,7 @@ pub struct QueryCandleSticks {
 }
 
 struct CompiledProfile {
-    queries: [JsonQuery; 6],
+    plan: QueryPlan,
     url_template: String,
 }
 
@@ -118,7 +138,15 @@ impl QueryCandleSticks {
             (
                 "binance",
                 "https://api.binance.com/api/v3/klines?startTime={from}&endTime={to}&symbol={stock}&interval={res}&limit={limit}",
-                ["[].0", "[].1", "[].2", "[].3", "[].4", "[].5"],
+                // Binance trả timestamp theo millisecond
+                [
+                    "[].0 | divide(1000)",
+                    "[].1",
+                    "[].2",
+                    "[].3",
+                    "[].4",
+                    "[].5",
+                ],
             ),
             (
                 "msn",
@@ -165,19 +193,13 @@ impl QueryCandleSticks {
         let mapping = RwLock::new(serde_json::from_str(&mapping_str).unwrap_or_default());
 
         for (name, url, paths) in raw_configs {
-            let queries = [
-                JsonQuery::parse(paths[0])?,
-                JsonQuery::parse(paths[1])?,
-                JsonQuery::parse(paths[2])?,
-                JsonQuery::parse(paths[3])?,
-                JsonQuery::parse(paths[4])?,
-                JsonQuery::parse(paths[5])?,
-            ];
+            let paths: Vec<_> = paths.into_iter().zip(COLUMN_KINDS).collect();
+            let plan = QueryPlan::parse(&paths)?;
             profiles.insert(
                 name.to_string(),
                 CompiledProfile {
                     url_template: url.into(),
-                    queries,
+                    plan,
                 },
             );
         }
@@ -229,6 +251,24 @@ impl QueryCandleSticks {
         Ok(fetched_candles)
     }
 
+    /// Thống kê cache nến, gộp các stack của mọi mã theo `resolution` để số
+    /// series metrics không tăng theo số mã.
+    pub fn cache_stats(&self) -> Vec<(String, CacheStats)> {
+        let caches = self.caches.read().unwrap();
+        let mut stats = HashMap::<String, CacheStats>::new();
+
+        for stacks in caches.values() {
+            for (resolution, cache) in stacks {
+                let entry = stats.entry(resolution.clone()).or_default();
+                *entry = *entry + cache.stats();
+            }
+        }
+
+        let mut stats = stats.into_iter().collect::<Vec<_>>();
+        stats.sort_by(|a, b| a.0.cmp(&b.0));
+        stats
+    }
+
     fn fetch_from_cache(
         &self,
         stock: &str,
@@ -435,46 +475,15 @@ impl QueryCandleSticks {
             .await
             .map_err(|e| Error::other(format!("Failed to fetch data from {}: {}", provider, e)))?;
 
-        let raw_json: Value = resp.json().await.map_err(|e| {
-            Error::new(
-                ErrorKind::InvalidData,
-                format!("Failed to parse JSON: {}", e),
-            )
-        })?;
-
-        let t_ref = profile.queries[0].pick(&raw_json);
-        if t_ref.is_empty() {
-            return Ok(vec![]);
-        }
-
-        let o_ref = profile.queries[1].pick(&raw_json);
-        let h_ref = profile.queries[2].pick(&raw_json);
-        let l_ref = profile.queries[3].pick(&raw_json);
-        let c_ref = profile.queries[4].pick(&raw_json);
-        let v_ref = profile.queries[5].pick(&raw_json);
-
-        let count = if limit > 0 {
-            limit.min(t_ref.len())
-        } else {
-            t_ref.len()
-        };
-        let mut candles = Vec::with_capacity(count);
-
-        for (t, o, h, l, c, v) in izip!(t_ref, o_ref, h_ref, l_ref, c_ref, v_ref).take(count) {
-            candles.push(CandleStick {
-                t: if provider == "binance" {
-                    (t.as_i64().unwrap_or(0) / 1000) as i32
-                } else {
-                    t.as_i32_lossy()
-                },
-                o: o.as_f64_lossy(),
-                h: h.as_f64_lossy(),
-                l: l.as_f64_lossy(),
-                c: c.as_f64_lossy(),
-                v: v.as_f64_lossy(),
-            });
-        }
+        let body = resp
+            .bytes()
+            .await
+            .map_err(|e| Error::other(format!("Failed to read body from {}: {}", provider, e)))?;
 
+        // @NOTE: plan đọc thẳng 6 cột trong một lượt, không dựng `Value`; các
+        // function trong profile (vd: `divide(1000)`) vẫn được áp dụng
+        let columns = profile.plan.run_slice(&body)?;
+        let candles = to_candles(&columns, limit);
         Ok(candles)
     }
 }
@@ -485,7 +494,7 @@ mod tests {
     use reqwest::Client as HttpClient;
     use reqwest_middleware::ClientBuilder;
     use reqwest_tracing::TracingMiddleware;
-    use serde_json::json;
+    use serde_json::{Value, json};
     use std::time::Instant;
 
     async fn run_provider_test(
@@ -581,10 +590,13 @@ mod tests {
         let data = mock_ssi_data(10);
 
         let profile = service.profiles.get("ssi").unwrap();
-        let t_ref = profile.queries[0].pick(&data);
+        assert!(profile.plan.is_streaming());
+
+        let columns = profile.plan.run_str(&data.to_string()).unwrap();
+        let t_ref = columns[0].as_i64().unwrap();
 
         assert_eq!(t_ref.len(), 10);
-        assert_eq!(t_ref[0].as_i32_lossy(), 1700000000);
+        assert_eq!(t_ref[0], 1700000000);
     }
 
     #[test]
@@ -598,18 +610,18 @@ mod tests {
 
         // Binance format: [[t, o, h, l, c, v], ...]
         let data = json!([
-            [170000000i32, "100.5", "101.0", "99.0", "100.8", "5000"],
-            [170000000i32, "100.8", "102.0", "100.5", "101.5", "6000"]
+            [1700000000000i64, "100.5", "101.0", "99.0", "100.8", "5000"],
+            [1700000060000i64, "100.8", "102.0", "100.5", "101.5", "6000"]
         ]);
 
         let profile = service.profiles.get("binance").unwrap();
-        let t_ref = profile.queries[0].pick(&data);
-        let o_ref = profile.queries[1].pick(&data);
+        let columns = profile.plan.run_str(&data.to_string()).unwrap();
+        let candles = to_candles(&columns, 0);
 
-        assert_eq!(t_ref.len(), 2);
-        assert_eq!(o_ref[0].as_f64_lossy(), 100.5);
-        // Binance timestamp is ms, our lossy converter handles it
-        assert_eq!(t_ref[0].as_i32_lossy(), 170000000i32);
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].o, 100.5);
+        // Binance timestamp is ms, profile đổi sang giây bằng `divide(1000)`
+        assert_eq!(candles[0].t, 1700000000i32);
     }
 
     #[test]
@@ -621,29 +633,13 @@ mod tests {
         );
         let service = QueryCandleSticks::new(client, 70).unwrap();
         let size = 5000;
-        let data = mock_ssi_data(size);
+        let body = mock_ssi_data(size).to_string().into_bytes();
         let profile = service.profiles.get("ssi").unwrap();
 
         let start = Instant::now();
 
-        let t_ref = profile.queries[0].pick(&data);
-        let o_ref = profile.queries[1].pick(&data);
-        let h_ref = profile.queries[2].pick(&data);
-        let l_ref = profile.queries[3].pick(&data);
-        let c_ref = profile.queries[4].pick(&data);
-        let v_ref = profile.queries[5].pick(&data);
-
-        let mut candles = Vec::with_capacity(size);
-        for (t, o, h, l, c, v) in izip!(t_ref, o_ref, h_ref, l_ref, c_ref, v_ref).take(size) {
-            candles.push(CandleStick {
-                t: t.as_i32_lossy(),
-                o: o.as_f64_lossy(),
-                h: h.as_f64_lossy(),
-                l: l.as_f64_lossy(),
-                c: c.as_f64_lossy(),
-                v: v.as_f64_lossy(),
-            });
-        }
+        let columns = profile.plan.run_slice(&body).unwrap();
+        let candles = to_candles(&columns, size);
 
         let duration = start.elapsed();
         println!("\n⚡ Benchmark Results:");
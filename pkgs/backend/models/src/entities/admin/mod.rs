@@ -31,11 +31,11 @@ use std::time::Duration;
 use sea_orm::sea_query::{Alias, Condition, Expr, OnConflict, Query};
 use sea_orm::{
     ColumnTrait, ConnectOptions, ConnectionTrait, Database, DatabaseConnection,
-    DatabaseTransaction, DbErr, EntityTrait, ExprTrait, JoinType, QueryFilter, QuerySelect,
-    RuntimeErr, Set, TransactionTrait, Value as OrmValue,
+    DatabaseTransaction, DbErr, EntityTrait, ExprTrait, JoinType, QueryFilter, QueryOrder,
+    QuerySelect, RuntimeErr, Set, TransactionTrait, Value as OrmValue,
 };
 
-use algorithm::{LruCache, Operator, decrypt, encrypt};
+use algorithm::{CacheStats, Keyring, LruCache, Operator};
 use chrono::{DateTime, Utc};
 use integration::Api as ApiEngine;
 use regex::Regex;
@@ -48,14 +48,21 @@ use crate::resolver::Resolver;
 
 static API_PLACEHOLDE_REGEX: OnceLock<Regex> = OnceLock::new();
 
+// @NOTE: token và API schema hết hạn sau TTL để tự nạp lại bản mới từ database
+const CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+/// Số token đọc mỗi lượt khi quét `sys_token_map` để rotate/verify
+const TOKEN_PAGE_SIZE: u64 = 500;
+
 pub struct Admin {
     // @NOTE: controller
     resolver: Arc<Resolver>,
     api: Arc<ApiEngine>,
 
     // @NOTE: caching
-    cache_unencrypted_tokens_by_services: Arc<LruCache<(i64, String), Option<String>, 32>>,
-    cache_unencrypted_tokens_by_ids: Arc<LruCache<i64, Option<String>, 32>>,
+    /// Chỉ cache token tìm thấy, token chưa có không bị nhớ lại để lần ghi sau
+    /// có hiệu lực ngay
+    cache_unencrypted_tokens_by_services: Arc<LruCache<(i64, String), String, 32>>,
+    cache_unencrypted_tokens_by_ids: Arc<LruCache<i64, String, 32>>,
     cache_api_info_by_name: Arc<LruCache<String, Option<Api>, 32>>,
     cache_api_info_by_id: Arc<LruCache<i64, Option<Api>, 32>>,
     cache_connections: Arc<LruCache<i64, DatabaseConnection, 32>>,
@@ -319,6 +326,34 @@ pub struct AuthConfig {
     oidc_expected_alg: Option<String>,
 }
 
+/// Kết quả của `Admin::rotate_tokens` và `Admin::verify_tokens`.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct TokenReport {
+    pub total: usize,
+    /// Đã nằm dưới primary key
+    pub current: usize,
+    /// Giải mã được nhưng chưa nằm dưới primary key
+    pub outdated: usize,
+    /// Đã ghi lại bằng primary key trong các database đã commit (luôn `0` khi
+    /// dry-run hoặc có token không giải mã được)
+    pub rotated: usize,
+    pub failures: Vec<TokenFailure>,
+    /// Index các database (theo `Resolver::databases`) đã commit. Mỗi database
+    /// commit riêng nên commit lỗi giữa chừng để lại một phần đã rotate; chạy
+    /// lại là an toàn vì token đã nằm dưới primary key được bỏ qua.
+    pub committed: Vec<usize>,
+    /// Lỗi commit làm dừng rotate, các database chưa commit bị rollback
+    pub commit_error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TokenFailure {
+    pub id: i64,
+    pub tenant_id: i64,
+    pub service: String,
+    pub error: String,
+}
+
 impl Admin {
     pub fn new(resolver: &Arc<Resolver>) -> Self {
         // @TODO: có cách nào lấy dữ liêụ từ resolver về capacity của cache_unencrypted_tokens_by_services và api
@@ -326,23 +361,55 @@ impl Admin {
         Self {
             resolver: resolver.clone(),
             api: Arc::new(ApiEngine::new(10 * 32)),
-            cache_unencrypted_tokens_by_services: Arc::new(LruCache::new(10 * 32)),
-            cache_unencrypted_tokens_by_ids: Arc::new(LruCache::new(10 * 32)),
-            cache_api_info_by_name: Arc::new(LruCache::new(10 * 32)),
-            cache_api_info_by_id: Arc::new(LruCache::new(10 * 32)),
+            cache_unencrypted_tokens_by_services: Arc::new(
+                LruCache::new(10 * 32).with_default_ttl(CACHE_TTL),
+            ),
+            cache_unencrypted_tokens_by_ids: Arc::new(
+                LruCache::new(10 * 32).with_default_ttl(CACHE_TTL),
+            ),
+            cache_api_info_by_name: Arc::new(LruCache::new(10 * 32).with_default_ttl(CACHE_TTL)),
+            cache_api_info_by_id: Arc::new(LruCache::new(10 * 32).with_default_ttl(CACHE_TTL)),
             cache_connections: Arc::new(LruCache::new(10 * 32)),
         }
     }
 
+    /// Thống kê các LruCache của `Admin`, đặt tên theo field.
+    pub fn cache_stats(&self) -> Vec<(&'static str, CacheStats)> {
+        vec![
+            (
+                "admin_tokens_by_services",
+                self.cache_unencrypted_tokens_by_services.stats(),
+            ),
+            (
+                "admin_tokens_by_ids",
+                self.cache_unencrypted_tokens_by_ids.stats(),
+            ),
+            (
+                "admin_api_info_by_name",
+                self.cache_api_info_by_name.stats(),
+            ),
+            ("admin_api_info_by_id", self.cache_api_info_by_id.stats()),
+            ("admin_connections", self.cache_connections.stats()),
+        ]
+    }
+
     fn dbt(&self, tenant_id: i64) -> &DatabaseConnection {
         self.resolver.database(tenant_id)
     }
 
-    async fn get_master_key(&self) -> Result<Vec<u8>, DbErr> {
+    async fn get_keyring(&self) -> Result<Keyring, DbErr> {
         // TODO: Sau này thay thế đoạn này bằng gọi KMS SDK
-        env::var("MASTER_KEY")
-            .map(|s| s.into_bytes())
-            .map_err(|_| DbErr::Custom("Missing MASTER_KEY".into()))
+        let master_key =
+            env::var("MASTER_KEY").map_err(|_| DbErr::Custom("Missing MASTER_KEY".into()))?;
+        let key_id = env::var("MASTER_KEY_ID").unwrap_or_else(|_| "default".to_string());
+
+        // @NOTE: key cũ dạng `id:key,id:key`, chỉ dùng để đọc token chưa re-encrypt
+        let keyring = Keyring::new(&key_id, master_key.as_bytes());
+        match env::var("MASTER_KEYS_RETIRED") {
+            Ok(retired) => keyring.and_then(|keyring| keyring.with_keys(&retired)),
+            Err(_) => keyring,
+        }
+        .map_err(|error| DbErr::Custom(format!("Invalid master keys: {error}")))
     }
 
     // @TODO: refresh cache
@@ -599,20 +666,8 @@ impl Admin {
         tenant_id: i64,
         service_name: &String,
     ) -> Result<String, DbErr> {
-        let cache_key = (tenant_id, service_name.clone());
-
-        match self.cache_unencrypted_tokens_by_services.get(&cache_key) {
-            Some(Some(token)) => Ok(token),
-            Some(None) => Err(DbErr::Query(RuntimeErr::Internal(format!(
-                "Not found service {}, tenant {}",
-                service_name, tenant_id,
-            )))),
-            None => {
-                let cache_key_after_done = cache_key.clone();
-
-                self.cache_unencrypted_tokens_by_services
-                    .put(cache_key, None);
-
+        self.cache_unencrypted_tokens_by_services
+            .get_or_load((tenant_id, service_name.clone()), || async {
                 let encrypted_bytes = TokenMap::find()
                     .select_only()
                     .filter(token_map::Column::TenantId.eq(tenant_id))
@@ -625,33 +680,19 @@ impl Admin {
                         DbErr::Query(RuntimeErr::Internal(format!(
                             "Failed when querying to fetch token id: {error}",
                         )))
-                    })?
-                    .ok_or_else(|| {
-                        DbErr::Query(RuntimeErr::Internal(format!(
-                            "Not found service {}, tenant {}",
-                            service_name, tenant_id,
-                        )))
                     })?;
 
-                let token = decrypt(
-                    self.get_master_key()
-                        .await
-                        .map_err(|error| {
-                            DbErr::Query(RuntimeErr::Internal(format!("Decrypt failed: {error}")))
-                        })?
-                        .as_slice(),
-                    encrypted_bytes.as_slice(),
-                )
-                .map_err(|error| {
-                    DbErr::Query(RuntimeErr::Internal(format!("Decrypt failed: {error}")))
+                let encrypted_bytes = encrypted_bytes.ok_or_else(|| {
+                    DbErr::Query(RuntimeErr::Internal(format!(
+                        "Not found service {}, tenant {}",
+                        service_name, tenant_id,
+                    )))
                 })?;
-
-                self.cache_unencrypted_tokens_by_services
-                    .put(cache_key_after_done, Some(token.clone()));
-
-                Ok(token)
-            }
-        }
+                self.decrypt_token(tenant_id, service_name, &encrypted_bytes)
+                    .await
+            })
+            .await
+            .map_err(unshare_db_err)
     }
 
     async fn get_unencrypted_token_by_id(
@@ -659,52 +700,48 @@ impl Admin {
         tenant_id: i64,
         token_id: i64,
     ) -> Result<String, DbErr> {
-        let cache_key = token_id;
-
-        match self.cache_unencrypted_tokens_by_ids.get(&cache_key) {
-            Some(Some(token)) => Ok(token),
-            Some(None) => Err(DbErr::Query(RuntimeErr::Internal(format!(
-                "Not found token {token_id}, tenant {tenant_id}",
-            )))),
-            None => {
+        self.cache_unencrypted_tokens_by_ids
+            .get_or_load(token_id, || async {
                 let encrypted_bytes = TokenMap::find()
                     .select_only()
                     .filter(token_map::Column::TenantId.eq(tenant_id))
                     .filter(token_map::Column::Id.eq(token_id))
+                    .column(token_map::Column::Service)
                     .column(token_map::Column::Token)
-                    .into_tuple::<Vec<u8>>()
+                    .into_tuple::<(String, Vec<u8>)>()
                     .one(self.dbt(tenant_id))
                     .await
                     .map_err(|error| {
                         DbErr::Query(RuntimeErr::Internal(format!(
                             "Failed when querying to fetch token data: {error}",
                         )))
-                    })?
-                    .ok_or_else(|| {
-                        DbErr::Query(RuntimeErr::Internal(format!(
-                            "Not found token_id {} for tenant {}",
-                            token_id, tenant_id,
-                        )))
                     })?;
 
-                let token = decrypt(
-                    self.get_master_key()
-                        .await
-                        .map_err(|error| {
-                            DbErr::Query(RuntimeErr::Internal(format!("Decrypt failed: {error}")))
-                        })?
-                        .as_slice(),
-                    encrypted_bytes.as_slice(),
-                )
-                .map_err(|error| {
-                    DbErr::Query(RuntimeErr::Internal(format!("Decrypt failed: {error}")))
+                let (service_name, encrypted_bytes) = encrypted_bytes.ok_or_else(|| {
+                    DbErr::Query(RuntimeErr::Internal(format!(
+                        "Not found token_id {} for tenant {}",
+                        token_id, tenant_id,
+                    )))
                 })?;
+                self.decrypt_token(tenant_id, &service_name, &encrypted_bytes)
+                    .await
+            })
+            .await
+            .map_err(unshare_db_err)
+    }
 
-                self.cache_unencrypted_tokens_by_ids
-                    .put(cache_key, Some(token.clone()));
-                Ok(token)
-            }
-        }
+    async fn decrypt_token(
+        &self,
+        tenant_id: i64,
+        service_name: &str,
+        encrypted_bytes: &[u8],
+    ) -> Result<String, DbErr> {
+        decrypt_token(
+            &self.get_keyring().await?,
+            tenant_id,
+            service_name,
+            encrypted_bytes,
+        )
     }
 
     pub async fn put_unencrypted_token(
@@ -713,19 +750,21 @@ impl Admin {
         service_name: &String,
         token_plain: &String,
     ) -> Result<(), DbErr> {
+        let keyring = self.get_keyring().await?;
         let txn = self.dbt(tenant_id).begin().await?;
-        self.put_unencrypted_token_txn(&txn, tenant_id, service_name, token_plain)
+        self.put_unencrypted_token_txn(&txn, &keyring, tenant_id, service_name, token_plain)
             .await?;
         txn.commit().await?;
 
         self.cache_unencrypted_tokens_by_services
-            .put((tenant_id, service_name.clone()), Some(token_plain.clone()));
+            .put((tenant_id, service_name.clone()), token_plain.clone());
         Ok(())
     }
 
     async fn put_unencrypted_token_txn(
         &self,
         txn: &DatabaseTransaction,
+        keyring: &Keyring,
         tenant_id: i64,
         service_name: &String,
         token_plain: &String,
@@ -733,18 +772,11 @@ impl Admin {
         token_map::Entity::insert(token_map::ActiveModel {
             tenant_id: Set(tenant_id),
             service: Set(service_name.to_owned()),
-            token: Set(encrypt(
-                self.get_master_key()
-                    .await
-                    .map_err(|error| {
-                        DbErr::Query(RuntimeErr::Internal(format!("Encrypt failed: {error}")))
-                    })?
-                    .as_slice(),
-                token_plain,
-            )
-            .map_err(|error| {
-                DbErr::Query(RuntimeErr::Internal(format!("Encrypt failed: {error}")))
-            })?),
+            token: Set(keyring
+                .encrypt(token_plain, &token_aad(tenant_id, service_name))
+                .map_err(|error| {
+                    DbErr::Query(RuntimeErr::Internal(format!("Encrypt failed: {error}")))
+                })?),
             ..Default::default()
         })
         .on_conflict(
@@ -761,6 +793,130 @@ impl Admin {
         Ok(())
     }
 
+    /// Mã hoá lại mọi token chưa nằm dưới primary key của `keyring` (kể cả blob
+    /// định dạng cũ), đọc/ghi qua cùng logic với `get_unencrypted_token` và
+    /// `put_unencrypted_token`.
+    ///
+    /// Mỗi database chạy trong một transaction. Chỉ commit khi mọi token đều giải
+    /// mã được và không phải `dry_run` — tránh trường hợp bỏ key cũ đi rồi mới
+    /// phát hiện còn token chưa chuyển.
+    ///
+    /// @NOTE: các transaction được commit lần lượt, không atomic giữa các
+    /// database. Commit lỗi thì dừng lại, rollback phần còn lại và trả report
+    /// với `committed`/`commit_error` thay vì lỗi; rotate là idempotent theo
+    /// từng token nên chỉ cần chạy lại.
+    pub async fn rotate_tokens(
+        &self,
+        keyring: &Keyring,
+        dry_run: bool,
+    ) -> Result<TokenReport, DbErr> {
+        let mut report = TokenReport::default();
+        let mut txns = Vec::with_capacity(self.resolver.databases().len());
+
+        for db in self.resolver.databases() {
+            let txn = db.begin().await?;
+            let mut rotated = 0;
+            let mut after = 0;
+
+            loop {
+                let tokens = list_encrypted_tokens(&txn, after, TOKEN_PAGE_SIZE).await?;
+                let Some(&(last, ..)) = tokens.last() else {
+                    break;
+                };
+                after = last;
+
+                for (id, tenant_id, service_name, encrypted_bytes) in tokens {
+                    report.total += 1;
+                    if !keyring.needs_rotation(&encrypted_bytes) {
+                        report.current += 1;
+                        continue;
+                    }
+
+                    match decrypt_token(keyring, tenant_id, &service_name, &encrypted_bytes) {
+                        Ok(token_plain) => {
+                            report.outdated += 1;
+                            if !dry_run {
+                                self.put_unencrypted_token_txn(
+                                    &txn,
+                                    keyring,
+                                    tenant_id,
+                                    &service_name,
+                                    &token_plain,
+                                )
+                                .await?;
+                                rotated += 1;
+                            }
+                        }
+                        Err(error) => report.failures.push(TokenFailure {
+                            id,
+                            tenant_id,
+                            service: service_name,
+                            error: error.to_string(),
+                        }),
+                    }
+                }
+            }
+
+            txns.push((txn, rotated));
+        }
+
+        if dry_run || !report.failures.is_empty() {
+            for (txn, _) in txns {
+                txn.rollback().await?;
+            }
+            return Ok(report);
+        }
+
+        let mut txns = txns.into_iter().enumerate();
+        for (index, (txn, rotated)) in txns.by_ref() {
+            if let Err(error) = txn.commit().await {
+                report.commit_error = Some(format!("database {index}: {error}"));
+                break;
+            }
+            report.committed.push(index);
+            report.rotated += rotated;
+        }
+        for (_, (txn, _)) in txns {
+            // Report đã ghi lỗi commit, rollback lỗi thì transaction cũng bị huỷ khi drop
+            let _ = txn.rollback().await;
+        }
+        Ok(report)
+    }
+
+    /// Kiểm tra mọi token đang lưu đều giải mã được bằng keyring hiện tại.
+    pub async fn verify_tokens(&self) -> Result<TokenReport, DbErr> {
+        let keyring = self.get_keyring().await?;
+        let mut report = TokenReport::default();
+
+        for db in self.resolver.databases() {
+            let mut after = 0;
+
+            loop {
+                let tokens = list_encrypted_tokens(db, after, TOKEN_PAGE_SIZE).await?;
+                let Some(&(last, ..)) = tokens.last() else {
+                    break;
+                };
+                after = last;
+
+                for (id, tenant_id, service_name, encrypted_bytes) in tokens {
+                    report.total += 1;
+
+                    match decrypt_token(&keyring, tenant_id, &service_name, &encrypted_bytes) {
+                        Ok(_) if keyring.needs_rotation(&encrypted_bytes) => report.outdated += 1,
+                        Ok(_) => report.current += 1,
+                        Err(error) => report.failures.push(TokenFailure {
+                            id,
+                            tenant_id,
+                            service: service_name,
+                            error: error.to_string(),
+                        }),
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
     pub async fn list_supported_services(&self, tenant_id: i64) -> Result<Vec<String>, DbErr> {
         TokenMap::find()
             .filter(token_map::Column::TenantId.eq(tenant_id))
@@ -1152,9 +1308,10 @@ impl Admin {
         token: String,
         dsn: String,
     ) -> Result<(), DbErr> {
+        let keyring = self.get_keyring().await?;
         let txn = self.dbt(tenant_id).begin().await?;
 
-        self.put_unencrypted_token_txn(&txn, tenant_id, &dsn, &token)
+        self.put_unencrypted_token_txn(&txn, &keyring, tenant_id, &dsn, &token)
             .await?;
 
         DatabaseMap::insert(database_map::ActiveModel {
@@ -1167,7 +1324,7 @@ impl Admin {
 
         txn.commit().await?;
         self.cache_unencrypted_tokens_by_services
-            .put((tenant_id, token.clone()), Some(dsn.clone()));
+            .put((tenant_id, token.clone()), dsn.clone());
         Ok(())
     }
 
@@ -1549,6 +1706,50 @@ impl Admin {
     }
 }
 
+/// Một trang token có id > `after`, theo thứ tự id tăng dần
+async fn list_encrypted_tokens<C: ConnectionTrait>(
+    db: &C,
+    after: i64,
+    limit: u64,
+) -> Result<Vec<(i64, i64, String, Vec<u8>)>, DbErr> {
+    TokenMap::find()
+        .filter(token_map::Column::Id.gt(after))
+        .order_by_asc(token_map::Column::Id)
+        .limit(limit)
+        .select_only()
+        .column(token_map::Column::Id)
+        .column(token_map::Column::TenantId)
+        .column(token_map::Column::Service)
+        .column(token_map::Column::Token)
+        .into_tuple::<(i64, i64, String, Vec<u8>)>()
+        .all(db)
+        .await
+}
+
+/// Lấy lại `DbErr` gốc từ lỗi dùng chung của `LruCache::get_or_load`. Chỉ
+/// caller cuối cùng còn giữ lỗi mới lấy được bản gốc (`DbErr` không `Clone`),
+/// các caller chờ cùng lượt load nhận bản sao dạng chuỗi.
+fn unshare_db_err(error: Arc<DbErr>) -> DbErr {
+    Arc::try_unwrap(error)
+        .unwrap_or_else(|error| DbErr::Query(RuntimeErr::Internal(error.to_string())))
+}
+
+fn decrypt_token(
+    keyring: &Keyring,
+    tenant_id: i64,
+    service_name: &str,
+    encrypted_bytes: &[u8],
+) -> Result<String, DbErr> {
+    keyring
+        .decrypt(encrypted_bytes, &token_aad(tenant_id, service_name))
+        .map_err(|error| DbErr::Query(RuntimeErr::Internal(format!("Decrypt failed: {error}"))))
+}
+
+/// Gắn token với tenant và service: blob chép sang dòng khác sẽ không giải mã được.
+pub fn token_aad(tenant_id: i64, service_name: &str) -> Vec<u8> {
+    format!("{tenant_id}/{service_name}").into_bytes()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
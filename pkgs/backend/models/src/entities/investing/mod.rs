@@ -454,19 +454,10 @@ impl Investing {
         from: i64,
     ) -> Result<(), DbErr> {
         if user_id.is_none() {
-            match self.cache_broker_candlesticks_limit.get(broker) {
-                Some(Some(limit)) => {
-                    if from < Utc::now().timestamp() - limit as i64 * 24 * 60 * 60 {
-                        Err(DbErr::Query(RuntimeErr::Internal(format!(
-                            "Broker {broker} is limited to access data",
-                        ))))
-                    } else {
-                        Ok(())
-                    }
-                }
-                Some(None) => Ok(()),
-                None => {
-                    let limit = BrokerLimitation::find()
+            let limit = self
+                .cache_broker_candlesticks_limit
+                .get_or_load(broker.to_string(), || {
+                    BrokerLimitation::find()
                         .select_only()
                         .column(broker_limitation::Column::GuestMaxHistoryDays)
                         .join_rev(
@@ -479,23 +470,17 @@ impl Investing {
                         .filter(brokers::Column::Name.eq(broker))
                         .into_tuple::<i32>()
                         .one(self.dbt(tenant_id))
-                        .await?;
-
-                    self.cache_broker_candlesticks_limit
-                        .put(broker.to_string(), limit);
-
-                    if let Some(limit) = limit {
-                        if from < Utc::now().timestamp() - limit as i64 * 24 * 60 * 60 {
-                            Err(DbErr::Query(RuntimeErr::Internal(format!(
-                                "Broker {broker} is limited to access data",
-                            ))))
-                        } else {
-                            Ok(())
-                        }
-                    } else {
-                        Ok(())
-                    }
+                })
+                .await
+                .map_err(|error| DbErr::Query(RuntimeErr::Internal(error.to_string())))?;
+
+            match limit {
+                Some(limit) if from < Utc::now().timestamp() - limit as i64 * 24 * 60 * 60 => {
+                    Err(DbErr::Query(RuntimeErr::Internal(format!(
+                        "Broker {broker} is limited to access data",
+                    ))))
                 }
+                _ => Ok(()),
             }
         } else {
             Ok(())
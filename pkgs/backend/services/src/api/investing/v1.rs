@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io::ErrorKind;
 use std::time::Duration;
 
@@ -14,7 +14,7 @@ use axum::routing::get;
 use tokio::sync::broadcast::error::RecvError;
 use utoipa::{IntoParams, OpenApi, ToSchema};
 
-use analysis::{VolumeProfile, calculate_rrg};
+use analysis::{IndicatorSpec, Pivot, PivotKind, ProfileConfig, VolumeProfile, calculate_rrg};
 use models::cache::Cache;
 use models::entities::admin::ApiType;
 use models::entities::investing::Price;
@@ -28,10 +28,12 @@ use super::{AppState, InvestingHeaders, fast_cache_response};
         get_ohcl_from_broker,
         get_last_price_from_broker,
         get_heatmap_from_broker,
+        get_pivots_from_broker,
         get_list_of_resolutions,
         get_list_of_brokers,
         get_list_of_symbols,
         get_rrg_from_broker,
+        get_indicators_from_broker,
         upsert_symbol,
         get_symbol_price,
         get_list_of_symbols_by_product,
@@ -40,8 +42,14 @@ use super::{AppState, InvestingHeaders, fast_cache_response};
     components(schemas(
         OhclResponse,
         HeatmapResponse,
+        NakedPocResponse,
         GetOhclRequest,
         HeatmapRequest,
+        PivotRequest,
+        PivotsResponse,
+        PivotPointResponse,
+        IndicatorRequest,
+        IndicatorsResponse,
         QueryPagingInput,
         ListBrokersRequest,
         CandleStick,
@@ -60,7 +68,15 @@ pub fn routes() -> Router<AppState> {
             "/ohcl/heatmap/{broker}/{symbol}",
             get(get_heatmap_from_broker),
         )
+        .route(
+            "/ohcl/pivots/{broker}/{symbol}",
+            get(get_pivots_from_broker),
+        )
         .route("/ohcl/rrg/{broker}/{symbol}", get(get_rrg_from_broker))
+        .route(
+            "/ohcl/indicators/{broker}/{symbol}",
+            get(get_indicators_from_broker),
+        )
         .route("/ohcl/resolution", get(get_list_of_resolutions))
         .route("/ohcl/brokers", get(get_list_of_brokers))
         .route("/ohcl/brokers/{broker}/all", get(get_list_of_symbols))
@@ -107,6 +123,20 @@ struct HeatmapResponse {
     levels: Vec<f64>,
     ranges: Vec<(usize, usize, usize)>,
     timelines: Vec<Vec<(usize, usize)>>,
+    /// Các trường dưới đây thẳng hàng với cột của `heatmap` (mỗi window một phần tử),
+    /// `null` khi window không có volume
+    pocs: Vec<Option<f64>>,
+    value_areas: Vec<Option<(f64, f64)>>,
+    high_volume_nodes: Vec<Vec<f64>>,
+    low_volume_nodes: Vec<Vec<f64>>,
+    naked_pocs: Vec<NakedPocResponse>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, ToSchema)]
+struct NakedPocResponse {
+    t: i32,
+    price: f64,
+    volume: f64,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, ToSchema)]
@@ -135,6 +165,9 @@ pub struct OhclResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     heatmap: Option<HeatmapResponse>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pivots: Option<PivotsResponse>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     ohcl: Option<Vec<CandleStick>>,
 
@@ -153,6 +186,9 @@ pub struct OhclResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     rrgs: Option<Vec<RrgPoint>>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    indicators: Option<IndicatorsResponse>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     symbols: Option<Vec<String>>,
 
@@ -400,6 +436,8 @@ pub struct HeatmapRequest {
     overlap: usize,
     number_of_levels: usize,
     interval_in_hour: i32,
+    /// Phần trăm volume nằm trong value area, mặc định 70
+    value_area_percent: Option<f64>,
 }
 
 #[utoipa::path(
@@ -494,7 +532,12 @@ async fn get_heatmap_from_broker(
                 .await
             {
                 Ok(candles) => {
-                    match VolumeProfile::new_from_candles(
+                    let mut vp = VolumeProfile::new().with_config(ProfileConfig {
+                        value_area: args.value_area_percent.unwrap_or(70.0) / 100.0,
+                        ..Default::default()
+                    });
+
+                    match vp.calculate(
                         candles
                             .iter()
                             .filter_map(|candle| {
@@ -510,7 +553,7 @@ async fn get_heatmap_from_broker(
                         args.overlap,
                         args.interval_in_hour,
                     ) {
-                        Ok(vp) => {
+                        Ok(()) => {
                             let cols = vp.heatmap().len();
                             let rows = args.number_of_levels;
                             let mut heatmap: Vec<Vec<f64>> = vec![vec![0.0; cols]; rows];
@@ -523,6 +566,11 @@ async fn get_heatmap_from_broker(
                                 }
                             }
 
+                            let prices = |levels: &[usize]| -> Vec<f64> {
+                                levels.iter().map(|&level| vp.price_of(level)).collect()
+                            };
+                            let summaries = vp.summaries();
+
                             Ok((
                                 StatusCode::OK,
                                 Json(OhclResponse {
@@ -531,6 +579,45 @@ async fn get_heatmap_from_broker(
                                         levels: vp.levels().clone(),
                                         ranges: vp.ranges().clone(),
                                         timelines: vp.timelines().clone(),
+                                        pocs: summaries
+                                            .iter()
+                                            .map(|summary| {
+                                                summary.as_ref().map(|s| vp.price_of(s.poc))
+                                            })
+                                            .collect(),
+                                        value_areas: summaries
+                                            .iter()
+                                            .map(|summary| {
+                                                summary.as_ref().map(|s| vp.value_area_prices(s))
+                                            })
+                                            .collect(),
+                                        high_volume_nodes: summaries
+                                            .iter()
+                                            .map(|summary| {
+                                                summary
+                                                    .as_ref()
+                                                    .map(|s| prices(&s.high_volume_nodes))
+                                                    .unwrap_or_default()
+                                            })
+                                            .collect(),
+                                        low_volume_nodes: summaries
+                                            .iter()
+                                            .map(|summary| {
+                                                summary
+                                                    .as_ref()
+                                                    .map(|s| prices(&s.low_volume_nodes))
+                                                    .unwrap_or_default()
+                                            })
+                                            .collect(),
+                                        naked_pocs: vp
+                                            .naked_pocs()
+                                            .iter()
+                                            .map(|naked| NakedPocResponse {
+                                                t: naked.t,
+                                                price: naked.price,
+                                                volume: naked.volume,
+                                            })
+                                            .collect(),
                                     }),
                                     ..Default::default()
                                 }),
@@ -567,6 +654,177 @@ async fn get_heatmap_from_broker(
     }
 }
 
+/// Window lớn nhất cho phép, mỗi window là một Fenwick tree nên chi phí tăng tuyến tính
+const MAX_PIVOT_WINDOW: usize = 200;
+
+/// Số pivot mỗi loại trả về khi không truyền `limit`
+const DEFAULT_PIVOT_LIMIT: usize = 10;
+
+#[derive(Deserialize, Debug, ToSchema, IntoParams)]
+pub struct PivotRequest {
+    resolution: String,
+    from: i64,
+    to: i64,
+    /// Window nhỏ nhất (số nến mỗi phía), mặc định 2
+    min_window: Option<usize>,
+    /// Window lớn nhất, mặc định `n / 3` nhưng không quá 200
+    max_window: Option<usize>,
+    /// Số pivot high/low mạnh nhất trả về, mặc định 10
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, ToSchema)]
+struct PivotPointResponse {
+    t: i32,
+    price: f64,
+    window: usize,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, ToSchema)]
+struct PivotsResponse {
+    /// Các mảng điểm số thẳng hàng với `timestamps`
+    timestamps: Vec<i32>,
+    highs: Vec<f64>,
+    lows: Vec<f64>,
+    degrees: Vec<f64>,
+    spreads: Vec<f64>,
+    pivot_highs: Vec<PivotPointResponse>,
+    pivot_lows: Vec<PivotPointResponse>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/ohcl/pivots/{broker}/{symbol}",
+    params(
+        ("broker" = String, Path, description = "Broker name"),
+        ("symbol" = String, Path, description = "Symbol ticker"),
+        PivotRequest
+    ),
+    responses(
+        (status = 200, description = "Success", body = OhclResponse),
+        (status = 400, description = "Invalid window sizes", body = OhclResponse),
+        (status = 404, description = "Broker not found or data access limited", body = OhclResponse),
+        (status = 500, description = "Internal Server Error", body = OhclResponse)
+    )
+)]
+async fn get_pivots_from_broker(
+    State(app_state): State<AppState>,
+    Path((broker, symbol)): Path<(String, String)>,
+    Query(args): Query<PivotRequest>,
+    InvestingHeaders { tenant_id, user_id }: InvestingHeaders,
+) -> Result<impl IntoResponse, (StatusCode, impl IntoResponse)> {
+    let tenant_id = tenant_id.into();
+    let broker = app_state
+        .investing_entity
+        .convert_to_real_broker(tenant_id, &broker.to_lowercase())
+        .await
+        .map_err(|error| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(OhclResponse {
+                    error: Some(format!("Failed to calculate pivots: {error}")),
+                    ..Default::default()
+                }),
+            )
+        })?;
+    let symbol = symbol.to_uppercase();
+
+    app_state
+        .investing_entity
+        .validate_broker_candlesticks_limit(tenant_id, &broker, &user_id.0, args.from)
+        .await
+        .map_err(|error| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(OhclResponse {
+                    error: Some(format!("Limit data access: {error}")),
+                    ..Default::default()
+                }),
+            )
+        })?;
+
+    let resolution = app_state
+        .investing_entity
+        .convert_to_broker_resolution(tenant_id, &broker, &args.resolution)
+        .await
+        .map_err(|error| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(OhclResponse {
+                    error: Some(format!("Failed to convert resolution: {}", error)),
+                    ..Default::default()
+                }),
+            )
+        })?;
+
+    let candles = app_state
+        .query_candlesticks
+        .get_candlesticks(&broker, &symbol, &resolution, args.from, args.to, 0)
+        .await
+        .map_err(|error| {
+            let status = match error.kind() {
+                ErrorKind::NotFound => StatusCode::NOT_FOUND,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+
+            (
+                status,
+                Json(OhclResponse {
+                    error: Some(format!("Failed to fetch OHLC: {}", error)),
+                    ..Default::default()
+                }),
+            )
+        })?
+        .into_iter()
+        .filter(|candle| candle.t as i64 >= args.from && candle.t as i64 <= args.to)
+        .collect::<Vec<_>>();
+
+    let min_window = args.min_window.unwrap_or(2);
+    let max_window = args
+        .max_window
+        .unwrap_or(candles.len() / 3)
+        .min(MAX_PIVOT_WINDOW);
+    let pivot = Pivot::with_windows(&candles, min_window, max_window).map_err(|error| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(OhclResponse {
+                error: Some(format!("Failed to calculate pivots: {error}")),
+                ..Default::default()
+            }),
+        )
+    })?;
+
+    let limit = args.limit.unwrap_or(DEFAULT_PIVOT_LIMIT);
+    let strongest = |kind: PivotKind| -> Vec<PivotPointResponse> {
+        pivot
+            .strongest(kind, limit)
+            .into_iter()
+            .map(|point| PivotPointResponse {
+                t: point.t,
+                price: point.price,
+                window: point.window,
+            })
+            .collect()
+    };
+    let scores = pivot.scores();
+
+    Ok((
+        StatusCode::OK,
+        Json(OhclResponse {
+            pivots: Some(PivotsResponse {
+                timestamps: candles.iter().map(|candle| candle.t).collect(),
+                highs: scores.iter().map(|score| score.high).collect(),
+                lows: scores.iter().map(|score| score.low).collect(),
+                degrees: scores.iter().map(|score| score.degree).collect(),
+                spreads: scores.iter().map(|score| score.spread).collect(),
+                pivot_highs: strongest(PivotKind::High),
+                pivot_lows: strongest(PivotKind::Low),
+            }),
+            ..Default::default()
+        }),
+    ))
+}
+
 #[derive(Deserialize, Debug, ToSchema, IntoParams)]
 pub struct RrgRequest {
     resolution: String,
@@ -714,6 +972,224 @@ async fn get_rrg_from_broker(
     }
 }
 
+/// Số chỉ báo tối đa trong một request
+const MAX_INDICATORS_PER_REQUEST: usize = 16;
+
+/// Warm-up lớn nhất cho phép, để look-back không kéo về quá xa
+const MAX_INDICATOR_WARM_UP: usize = 1000;
+
+/// Số lần nhân đôi look-back khi chưa đủ nến warm-up (phiên nghỉ, cuối tuần...)
+const MAX_LOOK_BACK_ATTEMPTS: usize = 4;
+
+#[derive(Deserialize, Debug, ToSchema, IntoParams)]
+pub struct IndicatorRequest {
+    resolution: String,
+    from: i64,
+    to: i64,
+    /// Danh sách chỉ báo cách nhau bởi dấu phẩy, vd `rsi:14,macd:12:26:9,bb:20:2`
+    indicators: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, ToSchema)]
+struct IndicatorSeries {
+    spec: String,
+    lines: BTreeMap<String, Vec<Option<f64>>>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, ToSchema)]
+struct IndicatorsResponse {
+    timestamps: Vec<i32>,
+    series: Vec<IndicatorSeries>,
+}
+
+fn resolution_in_seconds(resolution: &str) -> Option<i64> {
+    match resolution {
+        "1m" => Some(60),
+        "5m" => Some(5 * 60),
+        "15m" => Some(15 * 60),
+        "30m" => Some(30 * 60),
+        "1H" => Some(60 * 60),
+        "4H" => Some(4 * 60 * 60),
+        "1D" => Some(24 * 60 * 60),
+        "1W" => Some(7 * 24 * 60 * 60),
+        "1M" => Some(31 * 24 * 60 * 60),
+        _ => None,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/ohcl/indicators/{broker}/{symbol}",
+    params(
+        ("broker" = String, Path, description = "Broker name"),
+        ("symbol" = String, Path, description = "Symbol ticker"),
+        IndicatorRequest
+    ),
+    responses(
+        (status = 200, description = "Success", body = OhclResponse),
+        (status = 400, description = "Invalid indicator or resolution", body = OhclResponse),
+        (status = 404, description = "Broker not found or data access limited", body = OhclResponse),
+        (status = 500, description = "Internal Server Error", body = OhclResponse)
+    )
+)]
+async fn get_indicators_from_broker(
+    State(app_state): State<AppState>,
+    Path((broker, symbol)): Path<(String, String)>,
+    Query(args): Query<IndicatorRequest>,
+    InvestingHeaders { tenant_id, user_id }: InvestingHeaders,
+) -> Result<impl IntoResponse, (StatusCode, impl IntoResponse)> {
+    let tenant_id = tenant_id.into();
+    let bad_request = |error: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(OhclResponse {
+                error: Some(error),
+                ..Default::default()
+            }),
+        )
+    };
+
+    let specs = IndicatorSpec::parse_list(&args.indicators)
+        .map_err(|error| bad_request(error.to_string()))?;
+    if specs.is_empty() || specs.len() > MAX_INDICATORS_PER_REQUEST {
+        return Err(bad_request(format!(
+            "Expect from 1 to {MAX_INDICATORS_PER_REQUEST} indicators, got {}",
+            specs.len()
+        )));
+    }
+
+    let warm_up = specs.iter().map(IndicatorSpec::warm_up).max().unwrap_or(0);
+    if warm_up > MAX_INDICATOR_WARM_UP {
+        return Err(bad_request(format!(
+            "Indicators need {warm_up} candles to warm up, limit is {MAX_INDICATOR_WARM_UP}"
+        )));
+    }
+
+    let seconds = resolution_in_seconds(&args.resolution)
+        .ok_or_else(|| bad_request(format!("Not support resolution `{}`", args.resolution)))?;
+
+    let broker = app_state
+        .investing_entity
+        .convert_to_real_broker(tenant_id, &broker.to_lowercase())
+        .await
+        .map_err(|error| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(OhclResponse {
+                    error: Some(format!("Failed to calculate indicators: {error}")),
+                    ..Default::default()
+                }),
+            )
+        })?;
+    let symbol = symbol.to_uppercase();
+
+    let resolution = app_state
+        .investing_entity
+        .convert_to_broker_resolution(tenant_id, &broker, &args.resolution)
+        .await
+        .map_err(|error| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(OhclResponse {
+                    error: Some(format!("Failed to convert resolution: {}", error)),
+                    ..Default::default()
+                }),
+            )
+        })?;
+
+    // @NOTE: `warm_up * seconds` chỉ đủ với thị trường chạy 24/7, với chứng khoán
+    // thì phiên nghỉ/cuối tuần làm thiếu nến nên nhân đôi look-back tới khi đủ.
+    // Lần fetch đầu phải qua `validate_broker_candlesticks_limit` như heatmap/RRG,
+    // các lần nới sau mà vượt giới hạn thì dừng và trả phần warm-up đang có.
+    let mut look_back = warm_up as i64 * seconds;
+    let mut candles: Vec<CandleStick> = Vec::new();
+
+    for attempt in 0..MAX_LOOK_BACK_ATTEMPTS {
+        let from = args.from - look_back;
+
+        if let Err(error) = app_state
+            .investing_entity
+            .validate_broker_candlesticks_limit(tenant_id, &broker, &user_id.0, from)
+            .await
+        {
+            if attempt == 0 {
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    Json(OhclResponse {
+                        error: Some(format!("Limit data access: {error}")),
+                        ..Default::default()
+                    }),
+                ));
+            }
+            break;
+        }
+
+        let fetched = app_state
+            .query_candlesticks
+            .get_candlesticks(&broker, &symbol, &resolution, from, args.to, 0)
+            .await
+            .map_err(|error| {
+                let status = match error.kind() {
+                    ErrorKind::NotFound => StatusCode::NOT_FOUND,
+                    _ => StatusCode::INTERNAL_SERVER_ERROR,
+                };
+
+                (
+                    status,
+                    Json(OhclResponse {
+                        error: Some(format!("Failed to fetch OHLC: {}", error)),
+                        ..Default::default()
+                    }),
+                )
+            })?;
+
+        let warmed = fetched
+            .iter()
+            .take_while(|candle| (candle.t as i64) < args.from)
+            .count();
+        // Không có thêm nến nào nghĩa là đã chạm đầu lịch sử của mã
+        let exhausted = attempt > 0 && fetched.len() <= candles.len();
+
+        candles = fetched;
+        if warmed >= warm_up || exhausted {
+            break;
+        }
+        look_back *= 2;
+    }
+
+    let candles = candles
+        .into_iter()
+        .filter(|candle| candle.t as i64 <= args.to)
+        .collect::<Vec<_>>();
+    let start = candles
+        .iter()
+        .position(|candle| candle.t as i64 >= args.from)
+        .unwrap_or(candles.len());
+
+    let series = specs
+        .iter()
+        .map(|spec| IndicatorSeries {
+            spec: spec.to_string(),
+            lines: spec
+                .compute(&candles)
+                .into_iter()
+                .map(|(name, values)| (name.to_string(), values[start..].to_vec()))
+                .collect(),
+        })
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(OhclResponse {
+            indicators: Some(IndicatorsResponse {
+                timestamps: candles[start..].iter().map(|candle| candle.t).collect(),
+                series,
+            }),
+            ..Default::default()
+        }),
+    ))
+}
+
 #[utoipa::path(
     get,
     path = "/ohcl/products/{broker}",
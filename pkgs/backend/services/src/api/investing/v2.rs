@@ -1541,8 +1541,7 @@ async fn build_store_index(
             if name.is_empty() {
                 continue;
             }
-            let key = name.to_lowercase().into_bytes();
-            let _ = index.insert(&key, id, name).await;
+            let _ = index.insert_text(name, id, name).await;
         }
     }
 
@@ -1556,16 +1555,16 @@ async fn search_index(
     index: &StoreSearchIndex,
     query: &str,
     limit: usize,
+    fuzzy: usize,
 ) -> Vec<StoreSearchSuggestion> {
     if query.is_empty() {
         return vec![];
     }
 
-    let key = query.to_lowercase().into_bytes();
-    match index.inner.search_like(&key, limit).await {
+    match index.inner.search_ranked(query, limit, fuzzy).await {
         Ok(results) => results
             .into_iter()
-            .map(|(id, name)| StoreSearchSuggestion { id, name })
+            .map(|(id, name, _)| StoreSearchSuggestion { id, name })
             .collect(),
         Err(_) => vec![],
     }
@@ -1575,7 +1574,8 @@ async fn search_index(
     get,
     path = "/stores/search",
     params(
-        ("q" = String, Query, description = "LIKE search query (substring match)"),
+        ("q" = String, Query, description = "Search query, diacritic-insensitive; every word must match (substring)"),
+        ("fuzzy" = Option<usize>, Query, description = "Max edit distance per word for typo tolerance (0-2, default 0)"),
     ),
     responses(
         (status = 200, body = OhclResponse),
@@ -1605,6 +1605,12 @@ async fn search_stores(
         .unwrap_or(20)
         .min(100);
 
+    let fuzzy: usize = params
+        .get("fuzzy")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0)
+        .min(2);
+
     let tid: i64 = tenant_id.into();
     let tenant_key = tid.to_string();
 
@@ -1615,7 +1621,7 @@ async fn search_stores(
             && index.built_at.elapsed() <= std::time::Duration::from_secs(300)
         {
             return Ok(response_search_results(
-                search_index(index, query, limit).await,
+                search_index(index, query, limit, fuzzy).await,
             ));
         }
     }
@@ -1632,7 +1638,7 @@ async fn search_stores(
             )
         })?;
 
-    let results = search_index(&index, query, limit).await;
+    let results = search_index(&index, query, limit, fuzzy).await;
 
     {
         let mut cache = app_state.store_search_indices.write().await;
@@ -14,7 +14,7 @@ use headers::Header;
 use http::header;
 use http::{HeaderName, HeaderValue};
 
-use algorithm::SearchIndex;
+use algorithm::{CacheStats, SearchIndex};
 use aws_sdk_s3::Client as S3Client;
 use pprof::protos::Message;
 use reqwest::Client as HttpClient;
@@ -159,6 +159,24 @@ impl AppState {
         })
     }
 
+    /// Thống kê các LruCache có tên, dùng cho endpoint `/metrics`.
+    pub fn cache_stats(&self) -> Vec<(String, CacheStats)> {
+        let mut stats = self
+            .admin_entity
+            .cache_stats()
+            .into_iter()
+            .map(|(name, stats)| (name.to_string(), stats))
+            .collect::<Vec<_>>();
+
+        stats.extend(
+            self.query_candlesticks
+                .cache_stats()
+                .into_iter()
+                .map(|(resolution, stats)| (format!("candlesticks_{resolution}"), stats)),
+        );
+        stats
+    }
+
     pub async fn stop(&self) -> Result<(), Error> {
         self.runtime.read().await.stop()
     }
@@ -178,6 +196,8 @@ impl Reload for AppState {
             "S3_BUCKET",
             "BROKER",
             "MASTER_KEY",
+            "MASTER_KEY_ID",
+            "MASTER_KEYS_RETIRED",
             "MARKET_PIPELINE_OUTPUT",
         ]
     }
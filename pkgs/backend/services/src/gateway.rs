@@ -1,7 +1,7 @@
 use axum::{
     Router,
     body::Body,
-    extract::connect_info,
+    extract::{State, connect_info},
     http::Request,
     routing::{get, post},
     serve::IncomingStream,
@@ -16,9 +16,11 @@ use tokio::signal;
 use tower::ServiceBuilder;
 use tower_http::trace::TraceLayer;
 
+use algorithm::CacheStats;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
+use std::fmt::Write;
 use std::fs;
 use std::io::Error;
 use std::os::unix::fs::PermissionsExt;
@@ -159,6 +161,62 @@ async fn admin_openapi() -> Json<utoipa::openapi::OpenApi> {
     Json(AdminApiDoc::openapi())
 }
 
+/// Nối thống kê LruCache vào output Prometheus (text format) của `/metrics`.
+fn render_cache_metrics(body: &mut String, caches: &[(String, CacheStats)]) {
+    let families: [(&str, &str, &str, fn(&CacheStats) -> u64); 7] = [
+        (
+            "lru_cache_hits_total",
+            "counter",
+            "Cache lookups that found a live entry",
+            |s| s.hits,
+        ),
+        (
+            "lru_cache_misses_total",
+            "counter",
+            "Cache lookups that found nothing",
+            |s| s.misses,
+        ),
+        (
+            "lru_cache_inserts_total",
+            "counter",
+            "New keys written to the cache",
+            |s| s.inserts,
+        ),
+        (
+            "lru_cache_evictions_total",
+            "counter",
+            "Entries evicted because a shard was full",
+            |s| s.evictions,
+        ),
+        (
+            "lru_cache_expirations_total",
+            "counter",
+            "Entries removed after their TTL",
+            |s| s.expirations,
+        ),
+        (
+            "lru_cache_promotion_skips_total",
+            "counter",
+            "LRU promotions skipped because the shard lock was busy",
+            |s| s.promotion_skips,
+        ),
+        (
+            "lru_cache_entries",
+            "gauge",
+            "Entries currently in the cache",
+            |s| s.len,
+        ),
+    ];
+
+    for (name, kind, help, value) in families {
+        let _ = writeln!(body, "# HELP {name} {help}");
+        let _ = writeln!(body, "# TYPE {name} {kind}");
+        for (cache, stats) in caches {
+            let _ = writeln!(body, "{name}{{cache=\"{cache}\"}} {}", value(stats));
+        }
+    }
+}
+
 pub async fn routes(app_state: AppState, enable_sentry: bool) -> Result<Router, Error> {
     let (prometheus_layer, metric_handle) = PrometheusMetricLayer::pair();
     let environment = std::env::var("ENVIRONMENT").unwrap_or_else(|_| "dev".to_string());
@@ -168,7 +226,14 @@ pub async fn routes(app_state: AppState, enable_sentry: bool) -> Result<Router,
         .route("/health", get(health_check))
         .route("/reload", post(reload))
         .route("/debug/pprof/profile", get(pprof))
-        .route("/metrics", get(|| async move { metric_handle.render() }))
+        .route(
+            "/metrics",
+            get(|State(app_state): State<AppState>| async move {
+                let mut body = metric_handle.render();
+                render_cache_metrics(&mut body, &app_state.cache_stats());
+                body
+            }),
+        )
         .route("/docs/investing/openapi.json", get(investing_openapi))
         .route("/docs/admin/openapi.json", get(admin_openapi))
         .nest("/api/admin", admin::routes())
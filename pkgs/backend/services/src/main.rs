@@ -1,7 +1,7 @@
 use clap::{Parser, Subcommand};
 use std::io::Error;
 
-use services::{gateway, token};
+use services::{gateway, token, token::TokenCommand};
 
 #[derive(Parser, Debug)]
 #[command(name = "algorithm", about = "An all in one solution")]
@@ -13,10 +13,18 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     Gateway {},
+    /// `token <encrypt|decrypt|rotate|verify> ...`, hoặc dạng cũ
+    /// `token <master_key> <encrypt|decrypt> <payload>` (blob không gắn tenant/service)
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Token {
-        master_key: String,
-        action: String,
-        payload: String,
+        #[command(subcommand)]
+        command: Option<TokenCommand>,
+        #[arg(required = true)]
+        master_key: Option<String>,
+        #[arg(required = true)]
+        action: Option<String>,
+        #[arg(required = true)]
+        payload: Option<String>,
     },
 }
 
@@ -55,10 +63,20 @@ fn main() -> Result<(), Error> {
             match &(Cli::parse().command) {
                 Some(Commands::Gateway {}) => gateway::run().await.unwrap(),
                 Some(Commands::Token {
-                    master_key,
-                    action,
-                    payload,
-                }) => token::run(master_key, action, payload).await.unwrap(),
+                    command: Some(command),
+                    ..
+                }) => token::run(command).await.unwrap(),
+                Some(Commands::Token {
+                    master_key: Some(master_key),
+                    action: Some(action),
+                    payload: Some(payload),
+                    ..
+                }) => token::run_legacy(master_key, action, payload)
+                    .await
+                    .unwrap(),
+                Some(Commands::Token { .. }) => {
+                    unreachable!("clap yêu cầu subcommand hoặc đủ 3 tham số")
+                }
                 None => gateway::run().await.unwrap(),
             }
         });
@@ -1,7 +1,158 @@
-use algorithm::{decrypt, encrypt};
+use algorithm::{Keyring, decrypt, encrypt};
+use clap::Subcommand;
 use std::io::{Error, ErrorKind};
+use std::sync::Arc;
 
-pub async fn run(master_key: &str, action: &str, payload: &str) -> std::io::Result<()> {
+use models::entities::admin::{Admin, TokenReport, token_aad};
+use models::resolver::Resolver;
+use models::secret::Secret;
+
+#[derive(Subcommand, Debug)]
+pub enum TokenCommand {
+    /// Mã hoá một token, in ra chuỗi Hex để nhét vào `UNHEX('...')`
+    Encrypt {
+        master_key: String,
+        payload: String,
+        #[arg(long, default_value = "default")]
+        key_id: String,
+        /// Tenant và service của dòng `sys_token_map` sẽ chứa token (dùng làm AAD)
+        #[arg(long)]
+        tenant_id: i64,
+        #[arg(long)]
+        service: String,
+    },
+    /// Giải mã chuỗi Hex lấy từ DB lên (hoặc kết quả của hàm HEX(token))
+    Decrypt {
+        master_key: String,
+        payload: String,
+        #[arg(long)]
+        tenant_id: i64,
+        #[arg(long)]
+        service: String,
+    },
+    /// Mã hoá lại mọi token trong `sys_token_map` từ key cũ sang key mới.
+    ///
+    /// Cấu hình service với `MASTER_KEY`/`MASTER_KEY_ID` mới và key cũ trong
+    /// `MASTER_KEYS_RETIRED` trước, để token đã rotate vẫn đọc được trong lúc chạy
+    Rotate {
+        #[arg(long)]
+        old_key: String,
+        #[arg(long, default_value = "default")]
+        old_key_id: String,
+        #[arg(long)]
+        new_key: String,
+        #[arg(long)]
+        new_key_id: String,
+        /// Chỉ báo cáo, không ghi gì xuống DB
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Kiểm tra mọi token đang lưu đều giải mã được bằng `MASTER_KEY` hiện tại
+    Verify {},
+}
+
+pub async fn run(command: &TokenCommand) -> std::io::Result<()> {
+    match command {
+        TokenCommand::Encrypt {
+            master_key,
+            payload,
+            key_id,
+            tenant_id,
+            service,
+        } => {
+            // 1. Mã hoá ra mảng bytes bằng keyring trong module algorithm
+            let encrypted_bytes = Keyring::new(key_id, master_key.as_bytes())?
+                .encrypt(payload, &token_aad(*tenant_id, service))?;
+
+            // 2. Encode mảng bytes đó thành chuỗi Hex và in ra
+            println!("{}", hex::encode(encrypted_bytes));
+            Ok(())
+        }
+        TokenCommand::Decrypt {
+            master_key,
+            payload,
+            tenant_id,
+            service,
+        } => {
+            // 1. Decode chuỗi Hex đó ngược lại thành mảng bytes thô
+            let encrypted_bytes = hex::decode(payload).map_err(|error| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Decode chuỗi Hex thất bại: {error}"),
+                )
+            })?;
+
+            // 2. Giải mã bằng key id ghi trong blob (blob cũ không có key id)
+            let key_id = Keyring::key_id(&encrypted_bytes).unwrap_or("default");
+            println!(
+                "{}",
+                Keyring::new(key_id, master_key.as_bytes())?
+                    .decrypt(&encrypted_bytes, &token_aad(*tenant_id, service))?
+            );
+            Ok(())
+        }
+        TokenCommand::Rotate {
+            old_key,
+            old_key_id,
+            new_key,
+            new_key_id,
+            dry_run,
+        } => {
+            let keyring = Keyring::new(new_key_id, new_key.as_bytes())?
+                .with_key(old_key_id, old_key.as_bytes())?;
+
+            let report = admin()
+                .await?
+                .rotate_tokens(&keyring, *dry_run)
+                .await
+                .map_err(|error| Error::other(format!("Rotate failed: {error}")))?;
+
+            print_report(&report);
+            if !report.failures.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "{} token(s) không giải mã được, không có token nào được ghi",
+                        report.failures.len()
+                    ),
+                ));
+            }
+            if let Some(error) = &report.commit_error {
+                println!("Database đã commit: {:?}", report.committed);
+                return Err(Error::other(format!(
+                    "Commit lỗi ({error}), các database chưa commit đã rollback. \
+                     Chạy lại `token rotate` là an toàn, token đã rotate sẽ được bỏ qua"
+                )));
+            }
+            if !dry_run {
+                println!("Có thể bỏ `{old_key_id}` khỏi MASTER_KEYS_RETIRED sau khi `verify` sạch");
+            }
+            Ok(())
+        }
+        TokenCommand::Verify {} => {
+            let report = admin()
+                .await?
+                .verify_tokens()
+                .await
+                .map_err(|error| Error::other(format!("Verify failed: {error}")))?;
+
+            print_report(&report);
+            if report.failures.is_empty() {
+                Ok(())
+            } else {
+                Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{} token(s) không giải mã được", report.failures.len()),
+                ))
+            }
+        }
+    }
+}
+
+/// Dạng cũ `token <master_key> <encrypt|decrypt> <payload>`: blob không có key id
+/// và không gắn tenant/service, vẫn đọc được qua keyring và sẽ được `rotate`
+/// chuyển sang định dạng mới.
+pub async fn run_legacy(master_key: &str, action: &str, payload: &str) -> std::io::Result<()> {
     if master_key.len() != 32 {
         return Err(Error::new(
             ErrorKind::InvalidInput,
@@ -14,28 +165,17 @@ pub async fn run(master_key: &str, action: &str, payload: &str) -> std::io::Resu
 
     match action {
         "encrypt" => {
-            // payload truyền vào là một chuỗi thô cần mã hóa (ví dụ: "my_token_secret")
-            let token_plain = payload.to_string();
-
-            // 1. Mã hóa ra mảng bytes bằng hàm trong module algorithm
-            let encrypted_bytes = encrypt(master_key.as_bytes(), &token_plain)?;
-
-            // 2. Encode mảng bytes đó thành chuỗi Hex và in ra
-            // Bạn lấy chuỗi này để nhét vào hàm UNHEX('...') của SQL
+            let encrypted_bytes = encrypt(master_key.as_bytes(), &payload.to_string())?;
             println!("{}", hex::encode(encrypted_bytes));
             Ok(())
         }
         "decrypt" => {
-            // payload truyền vào lúc này phải là chuỗi Hex lấy từ DB lên (hoặc kết quả của hàm HEX(token))
-            // 1. Decode chuỗi Hex đó ngược lại thành mảng bytes thô
             let encrypted_bytes = hex::decode(payload).map_err(|error| {
                 Error::new(
                     ErrorKind::InvalidInput,
                     format!("Decode chuỗi Hex thất bại: {error}"),
                 )
             })?;
-
-            // 2. Giải mã mảng bytes về lại chuỗi gốc ban đầu
             println!("{}", decrypt(master_key.as_bytes(), &encrypted_bytes)?);
             Ok(())
         }
@@ -45,3 +185,26 @@ pub async fn run(master_key: &str, action: &str, payload: &str) -> std::io::Resu
         )),
     }
 }
+
+async fn admin() -> std::io::Result<Admin> {
+    let secret = Arc::new(Secret::new().await?);
+    let resolver = Arc::new(Resolver::new(secret).await?);
+    Ok(Admin::new(&resolver))
+}
+
+fn print_report(report: &TokenReport) {
+    println!(
+        "total: {}, current: {}, outdated: {}, rotated: {}, failed: {}",
+        report.total,
+        report.current,
+        report.outdated,
+        report.rotated,
+        report.failures.len(),
+    );
+    for failure in &report.failures {
+        println!(
+            "  - id {} (tenant {}, service {}): {}",
+            failure.id, failure.tenant_id, failure.service, failure.error,
+        );
+    }
+}
@@ -1,7 +1,33 @@
-use std::collections::{BTreeMap, VecDeque};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use smallvec::{SmallVec, smallvec};
+use thiserror::Error;
 
 use crate::storage::{self, Storage};
 
+#[derive(Debug, Error)]
+pub enum AhoCorasickError {
+    #[error("automaton is not optimized")]
+    NotOptimized,
+    #[error("automaton storage is not empty")]
+    NotEmpty,
+    #[error("unsupported snapshot version {0}")]
+    UnsupportedVersion(u32),
+    #[error("invalid snapshot: {0}")]
+    InvalidSnapshot(String),
+    #[error("storage error: {0}")]
+    Storage(String),
+}
+
+impl From<storage::StorageError> for AhoCorasickError {
+    fn from(e: storage::StorageError) -> Self {
+        AhoCorasickError::Storage(e.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, AhoCorasickError>;
+
 type MappingBox = Box<dyn Fn(&String, &BTreeMap<String, usize>) -> Option<usize> + Send + Sync>;
 type CompareBox = Box<dyn Fn(&String, &String) -> bool + Send + Sync>;
 type CollectBox = Box<dyn Fn(&String) + Send + Sync>;
@@ -355,6 +381,613 @@ impl AhoCorasick {
     }
 }
 
+// ==================== Streaming ====================
+//
+// `similar()` chạy trên block của `split_fn` và chỉ trả lời có/không cho một
+// input hoàn chỉnh. `StreamMatcher` là automaton in-memory dựng lại từ các
+// pattern đã `add`, nhận input theo từng chunk byte (websocket frame, HTTP
+// body...) và báo `(pattern_idx, start, end)` — offset byte `[start, end)`
+// tính từ đầu stream, kể cả match nằm vắt qua ranh giới chunk.
+//
+// @NOTE: matcher so khớp theo Unicode char của pattern, không qua `split_fn`.
+
+/// Cách so khớp hoa/thường.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaseMode {
+    #[default]
+    Sensitive,
+    /// Chỉ gộp `A-Z` với `a-z`.
+    AsciiInsensitive,
+    /// Gộp theo `char::to_lowercase` (vd: `Đ`/`đ`, `Ư`/`ư`).
+    UnicodeInsensitive,
+}
+
+/// Ngữ nghĩa khi nhiều pattern cùng khớp.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchKind {
+    /// Báo mọi match, kể cả chồng lấn nhau.
+    #[default]
+    Overlapping,
+    /// Không chồng lấn: ưu tiên match bắt đầu sớm nhất, cùng điểm bắt đầu thì
+    /// lấy match dài nhất.
+    LeftmostLongest,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatchOptions {
+    pub case: CaseMode,
+    pub kind: MatchKind,
+}
+
+struct StreamState {
+    next: HashMap<char, usize>,
+    fail: usize,
+    depth: usize,
+    /// Pattern kết thúc tại state này, gồm cả các pattern theo chuỗi failure
+    /// (dài trước, ngắn sau).
+    outputs: Vec<usize>,
+}
+
+/// Match của `LeftmostLongest` chưa chắc chắn, tính theo đơn vị char đã fold.
+#[derive(Clone, Copy)]
+struct Candidate {
+    unit_start: usize,
+    unit_end: usize,
+    pattern: usize,
+    start: usize,
+    end: usize,
+}
+
+pub struct StreamMatcher {
+    states: Vec<StreamState>,
+    lengths: Vec<usize>,
+    options: MatchOptions,
+
+    // @NOTE: trạng thái stream
+    state: usize,
+    units: usize,
+    offset: usize,
+    /// Offset byte bắt đầu của các char gần nhất, đủ cho pattern dài nhất.
+    window: VecDeque<usize>,
+    max_len: usize,
+    /// Byte cuối chunk trước chưa đủ một ký tự UTF-8.
+    carry: Vec<u8>,
+
+    // @NOTE: chỉ dùng cho LeftmostLongest
+    candidates: Vec<Candidate>,
+    last_end: usize,
+}
+
+impl StreamMatcher {
+    fn new(patterns: &[String], options: MatchOptions) -> Self {
+        let mut states = vec![StreamState {
+            next: HashMap::new(),
+            fail: 0,
+            depth: 0,
+            outputs: Vec::new(),
+        }];
+        let mut lengths = Vec::with_capacity(patterns.len());
+
+        // @NOTE: build trie
+        for (pattern_idx, pattern) in patterns.iter().enumerate() {
+            let mut current = 0;
+            let mut len = 0;
+
+            for c in pattern.chars().flat_map(|c| Self::fold(c, options.case)) {
+                current = match states[current].next.get(&c) {
+                    Some(&next) => next,
+                    None => {
+                        let next = states.len();
+                        let depth = states[current].depth + 1;
+                        states.push(StreamState {
+                            next: HashMap::new(),
+                            fail: 0,
+                            depth,
+                            outputs: Vec::new(),
+                        });
+                        states[current].next.insert(c, next);
+                        next
+                    }
+                };
+                len += 1;
+            }
+
+            lengths.push(len);
+            if current != 0 {
+                states[current].outputs.push(pattern_idx);
+            }
+        }
+
+        // @NOTE: build failure links + output links theo BFS
+        let mut queue: VecDeque<usize> = states[0].next.values().copied().collect();
+        while let Some(state) = queue.pop_front() {
+            let edges: Vec<(char, usize)> =
+                states[state].next.iter().map(|(&c, &s)| (c, s)).collect();
+
+            for (c, child) in edges {
+                let mut fail = states[state].fail;
+                let target = loop {
+                    if let Some(&target) = states[fail].next.get(&c) {
+                        break target;
+                    }
+                    if fail == 0 {
+                        break 0;
+                    }
+                    fail = states[fail].fail;
+                };
+
+                states[child].fail = target;
+                let inherited = states[target].outputs.clone();
+                states[child].outputs.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+
+        let max_len = lengths.iter().copied().max().unwrap_or(0);
+        Self {
+            states,
+            lengths,
+            options,
+            state: 0,
+            units: 0,
+            offset: 0,
+            window: VecDeque::with_capacity(max_len + 1),
+            max_len,
+            carry: Vec::new(),
+            candidates: Vec::new(),
+            last_end: 0,
+        }
+    }
+
+    /// `char::to_lowercase` sinh tối đa 3 char.
+    fn fold(c: char, case: CaseMode) -> SmallVec<[char; 3]> {
+        match case {
+            CaseMode::Sensitive => smallvec![c],
+            CaseMode::AsciiInsensitive => smallvec![c.to_ascii_lowercase()],
+            CaseMode::UnicodeInsensitive => c.to_lowercase().collect(),
+        }
+    }
+
+    /// Nạp thêm một chunk, trả về các match đã chắc chắn.
+    ///
+    /// Chunk có thể cắt ngang một ký tự UTF-8; phần dư được giữ lại cho chunk
+    /// sau. Byte không hợp lệ UTF-8 được bỏ qua và ngắt mọi match đang dở.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<(usize, usize, usize)> {
+        let mut matches = Vec::new();
+
+        let data: Cow<[u8]> = if self.carry.is_empty() {
+            Cow::Borrowed(chunk)
+        } else {
+            let mut data = std::mem::take(&mut self.carry);
+            data.extend_from_slice(chunk);
+            Cow::Owned(data)
+        };
+
+        let mut rest = data.as_ref();
+        while !rest.is_empty() {
+            let (valid, skip) = match std::str::from_utf8(rest) {
+                Ok(valid) => (valid, 0),
+                Err(e) => {
+                    // `valid_up_to` luôn rơi đúng ranh giới ký tự
+                    let valid = std::str::from_utf8(&rest[..e.valid_up_to()]).unwrap_or_default();
+                    match e.error_len() {
+                        Some(len) => (valid, len),
+                        None => {
+                            // Ký tự bị cắt ở cuối chunk → chờ chunk sau
+                            self.carry = rest[e.valid_up_to()..].to_vec();
+                            (valid, 0)
+                        }
+                    }
+                }
+            };
+
+            for c in valid.chars() {
+                self.step(c, &mut matches);
+            }
+            rest = &rest[valid.len()..];
+
+            if skip > 0 {
+                self.offset += skip;
+                self.state = 0;
+                rest = &rest[skip..];
+            } else if !self.carry.is_empty() {
+                break;
+            }
+        }
+
+        matches
+    }
+
+    /// Như `feed` với input đã là `&str`.
+    pub fn feed_str(&mut self, chunk: &str) -> Vec<(usize, usize, usize)> {
+        self.feed(chunk.as_bytes())
+    }
+
+    /// Kết thúc stream: trả các match `LeftmostLongest` còn đang chờ và reset
+    /// matcher để dùng cho stream mới.
+    pub fn finish(&mut self) -> Vec<(usize, usize, usize)> {
+        let mut matches = Vec::new();
+        self.settle(usize::MAX, &mut matches);
+        self.reset();
+        matches
+    }
+
+    /// Bỏ toàn bộ trạng thái stream hiện tại.
+    pub fn reset(&mut self) {
+        self.state = 0;
+        self.units = 0;
+        self.offset = 0;
+        self.window.clear();
+        self.carry.clear();
+        self.candidates.clear();
+        self.last_end = 0;
+    }
+
+    fn step(&mut self, c: char, matches: &mut Vec<(usize, usize, usize)>) {
+        let start = self.offset;
+        let end = start + c.len_utf8();
+        self.offset = end;
+
+        for c in Self::fold(c, self.options.case) {
+            self.window.push_back(start);
+            if self.window.len() > self.max_len {
+                self.window.pop_front();
+            }
+            self.units += 1;
+
+            self.state = self.transit(self.state, c);
+            for &pattern in &self.states[self.state].outputs {
+                let len = self.lengths[pattern];
+                let candidate = Candidate {
+                    unit_start: self.units - len,
+                    unit_end: self.units,
+                    pattern,
+                    start: self.window[self.window.len() - len],
+                    end,
+                };
+
+                match self.options.kind {
+                    MatchKind::Overlapping => {
+                        matches.push((candidate.pattern, candidate.start, candidate.end));
+                    }
+                    MatchKind::LeftmostLongest => {
+                        if candidate.unit_start >= self.last_end {
+                            self.candidates.push(candidate);
+                        }
+                    }
+                }
+            }
+
+            if self.options.kind == MatchKind::LeftmostLongest {
+                // Match tương lai bắt đầu không sớm hơn điểm này
+                let horizon = self.units - self.states[self.state].depth;
+                self.settle(horizon, matches);
+            }
+        }
+    }
+
+    fn transit(&self, mut state: usize, c: char) -> usize {
+        loop {
+            if let Some(&next) = self.states[state].next.get(&c) {
+                return next;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.states[state].fail;
+        }
+    }
+
+    /// Chốt các candidate bắt đầu trước `horizon` — không match nào về sau có
+    /// thể bắt đầu sớm hơn hoặc dài hơn chúng nữa.
+    fn settle(&mut self, horizon: usize, matches: &mut Vec<(usize, usize, usize)>) {
+        loop {
+            let best = self
+                .candidates
+                .iter()
+                .filter(|c| c.unit_start >= self.last_end)
+                .min_by(|a, b| {
+                    a.unit_start
+                        .cmp(&b.unit_start)
+                        .then_with(|| b.unit_end.cmp(&a.unit_end))
+                })
+                .copied();
+
+            match best {
+                Some(best) if best.unit_start < horizon => {
+                    matches.push((best.pattern, best.start, best.end));
+                    self.last_end = best.unit_end;
+                    let last_end = self.last_end;
+                    self.candidates.retain(|c| c.unit_start >= last_end);
+                }
+                _ => break,
+            }
+        }
+    }
+}
+
+impl AhoCorasick {
+    /// Tạo matcher streaming từ các pattern đã `add` (không cần `optimize`).
+    pub fn stream(&self, options: MatchOptions) -> StreamMatcher {
+        StreamMatcher::new(&self.patterns, options)
+    }
+
+    /// Tìm mọi match trong `text` hoàn chỉnh: `(pattern_idx, start, end)` theo byte.
+    pub fn find_all(&self, text: &str, options: MatchOptions) -> Vec<(usize, usize, usize)> {
+        let mut matcher = self.stream(options);
+        let mut matches = matcher.feed_str(text);
+        matches.extend(matcher.finish());
+        matches
+    }
+}
+
+// ==================== Snapshot ====================
+//
+// Automaton đã optimize được xuất ra một blob nhị phân có version để nạp lại
+// mà không phải build lại trie + failure links:
+//
+// ```text
+// header   : MAGIC (8 bytes) | VERSION (u32 LE)
+// patterns : count (u32) | [len (u32) | utf8]...
+// states   : count (u32) | [label | failure (u32) | output (u32, MAX = none)
+//                           | n (u32) | [label | to (u32)]...]...
+// roots    : count (u32) | [state (u32)]...
+// trailer  : crc32 của toàn bộ phần trước (u32 LE)
+// ```
+//
+// @NOTE: callbacks không được lưu — blob phải được nạp với cùng `split_fn`
+//        đã dùng khi build.
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"ALGOAHC\0";
+const SNAPSHOT_VERSION: u32 = 1;
+const NO_OUTPUT: u32 = u32::MAX;
+
+struct SnapshotState {
+    label: String,
+    failure: usize,
+    output: Option<usize>,
+    transitions: Vec<(String, usize)>,
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: usize) -> Result<()> {
+    let value = u32::try_from(value)
+        .map_err(|_| AhoCorasickError::InvalidSnapshot(format!("{value} does not fit u32")))?;
+    buf.extend_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+fn put_str(buf: &mut Vec<u8>, value: &str) -> Result<()> {
+    put_u32(buf, value.len())?;
+    buf.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+struct SnapshotReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SnapshotReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.data.len() - self.pos < n {
+            return Err(AhoCorasickError::InvalidSnapshot("truncated".into()));
+        }
+        let out = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(out)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let mut b = [0u8; 4];
+        b.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(b))
+    }
+
+    fn usize(&mut self) -> Result<usize> {
+        Ok(self.u32()? as usize)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.usize()?;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|e| AhoCorasickError::InvalidSnapshot(e.to_string()))
+    }
+
+    /// Đọc số phần tử, chặn trước các giá trị vô lý (mỗi phần tử tốn ít nhất
+    /// `min_size` byte) để blob hỏng không làm cấp phát quá lớn.
+    fn count(&mut self, min_size: usize) -> Result<usize> {
+        let count = self.usize()?;
+        if count.saturating_mul(min_size) > self.data.len() - self.pos {
+            return Err(AhoCorasickError::InvalidSnapshot("truncated".into()));
+        }
+        Ok(count)
+    }
+}
+
+impl AhoCorasick {
+    /// Xuất automaton đã `optimize` ra blob nhị phân (xem định dạng ở trên).
+    pub async fn snapshot(&self) -> Result<Vec<u8>> {
+        if !self.is_optimized {
+            return Err(AhoCorasickError::NotOptimized);
+        }
+
+        let mut buf = Vec::new();
+        buf.extend_from_slice(SNAPSHOT_MAGIC);
+        buf.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+
+        put_u32(&mut buf, self.patterns.len())?;
+        for pattern in &self.patterns {
+            put_str(&mut buf, pattern)?;
+        }
+
+        let num_states = self.automaton.num_states().await?;
+        put_u32(&mut buf, num_states)?;
+        for state in 0..num_states {
+            put_str(&mut buf, &self.automaton.get_label(state).await?)?;
+            put_u32(&mut buf, self.automaton.get_failure(state).await?)?;
+
+            match self.automaton.get_output(state).await? {
+                Some(pattern_idx) => put_u32(&mut buf, pattern_idx)?,
+                None => buf.extend_from_slice(&NO_OUTPUT.to_le_bytes()),
+            }
+
+            let transitions = self.automaton.get_transitions(state).await?;
+            put_u32(&mut buf, transitions.len())?;
+            for (label, to) in &transitions {
+                put_str(&mut buf, label)?;
+                put_u32(&mut buf, *to)?;
+            }
+        }
+
+        let root_inputs = self.automaton.get_root_inputs().await?;
+        put_u32(&mut buf, root_inputs.len())?;
+        for state in root_inputs {
+            put_u32(&mut buf, state)?;
+        }
+
+        let crc = storage::file::crc32(&buf);
+        buf.extend_from_slice(&crc.to_le_bytes());
+        Ok(buf)
+    }
+
+    /// Nạp automaton từ blob của `snapshot()` vào storage hiện tại (phải còn
+    /// trống), không cần gọi `optimize()` lại.
+    ///
+    /// Blob được kiểm tra toàn bộ (magic, version, checksum, state id hợp lệ)
+    /// trước khi ghi bất cứ thứ gì vào storage.
+    pub async fn load_snapshot(&mut self, blob: &[u8]) -> Result<()> {
+        let (patterns, states, root_inputs) = Self::decode_snapshot(blob)?;
+
+        if !self.patterns.is_empty() || self.automaton.num_states().await? != 1 {
+            return Err(AhoCorasickError::NotEmpty);
+        }
+
+        for (state, snapshot) in states.iter().enumerate().skip(1) {
+            let id = self.automaton.add_state(&snapshot.label).await?;
+            if id != state {
+                return Err(AhoCorasickError::Storage(format!(
+                    "expected state id {state}, storage returned {id}"
+                )));
+            }
+        }
+
+        for (state, snapshot) in states.iter().enumerate() {
+            for (label, to) in &snapshot.transitions {
+                self.automaton.set_transition(state, label, *to).await?;
+            }
+            self.automaton.set_failure(state, snapshot.failure).await?;
+            if let Some(pattern_idx) = snapshot.output {
+                self.automaton.set_output(state, pattern_idx).await?;
+            }
+        }
+
+        for state in root_inputs {
+            self.automaton.add_root_input(state).await?;
+        }
+
+        self.pattern_mapping = patterns
+            .iter()
+            .enumerate()
+            .map(|(i, pattern)| (pattern.clone(), i))
+            .collect();
+        self.patterns = patterns;
+        self.is_optimized = true;
+        Ok(())
+    }
+
+    /// Tạo `AhoCorasick` in-memory (callback mặc định) từ blob của `snapshot()`.
+    pub async fn from_snapshot(blob: &[u8]) -> Result<Self> {
+        let mut ahocorasick = Self::new();
+        ahocorasick.load_snapshot(blob).await?;
+        Ok(ahocorasick)
+    }
+
+    fn decode_snapshot(blob: &[u8]) -> Result<(Vec<String>, Vec<SnapshotState>, Vec<usize>)> {
+        let header_len = SNAPSHOT_MAGIC.len() + 4;
+        if blob.len() < header_len + 4 || &blob[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+            return Err(AhoCorasickError::InvalidSnapshot("bad magic".into()));
+        }
+
+        let (body, trailer) = blob.split_at(blob.len() - 4);
+        let mut reader = SnapshotReader {
+            data: body,
+            pos: SNAPSHOT_MAGIC.len(),
+        };
+
+        let version = reader.u32()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(AhoCorasickError::UnsupportedVersion(version));
+        }
+
+        let mut crc = [0u8; 4];
+        crc.copy_from_slice(trailer);
+        if u32::from_le_bytes(crc) != storage::file::crc32(body) {
+            return Err(AhoCorasickError::InvalidSnapshot(
+                "checksum mismatch".into(),
+            ));
+        }
+
+        let mut patterns = Vec::new();
+        for _ in 0..reader.count(4)? {
+            patterns.push(reader.string()?);
+        }
+
+        let num_states = reader.count(16)?;
+        if num_states == 0 {
+            return Err(AhoCorasickError::InvalidSnapshot(
+                "missing root state".into(),
+            ));
+        }
+
+        let mut states = Vec::with_capacity(num_states);
+        for _ in 0..num_states {
+            let label = reader.string()?;
+            let failure = reader.usize()?;
+            let output = match reader.u32()? {
+                NO_OUTPUT => None,
+                pattern_idx => Some(pattern_idx as usize),
+            };
+
+            let mut transitions = Vec::new();
+            for _ in 0..reader.count(8)? {
+                transitions.push((reader.string()?, reader.usize()?));
+            }
+
+            states.push(SnapshotState {
+                label,
+                failure,
+                output,
+                transitions,
+            });
+        }
+
+        let mut root_inputs = Vec::new();
+        for _ in 0..reader.count(4)? {
+            root_inputs.push(reader.usize()?);
+        }
+
+        if reader.pos != body.len() {
+            return Err(AhoCorasickError::InvalidSnapshot("trailing bytes".into()));
+        }
+
+        // Mọi state id / pattern index phải trỏ vào phần tử có thật
+        let valid_state = |state: usize| state < num_states;
+        let consistent = states.iter().all(|s| {
+            valid_state(s.failure)
+                && s.output.is_none_or(|idx| idx < patterns.len())
+                && s.transitions.iter().all(|(_, to)| valid_state(*to))
+        }) && root_inputs.iter().all(|&s| valid_state(s));
+
+        if !consistent {
+            return Err(AhoCorasickError::InvalidSnapshot(
+                "state or pattern index out of range".into(),
+            ));
+        }
+
+        Ok((patterns, states, root_inputs))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -717,4 +1350,199 @@ mod tests {
         assert!(results[5]); // hello
         assert!(!results[6]); // x
     }
+
+    fn matcher(patterns: &[&str]) -> AhoCorasick {
+        let mut ahocorasick = AhoCorasick::new();
+        for pattern in patterns {
+            ahocorasick.add(pattern.to_string());
+        }
+        ahocorasick
+    }
+
+    #[test]
+    fn test_stream_overlapping() {
+        let ahocorasick = matcher(&["he", "she", "his", "hers"]);
+        let matches = ahocorasick.find_all("ushers", MatchOptions::default());
+        assert_eq!(matches, vec![(1, 1, 4), (0, 2, 4), (3, 2, 6)]);
+    }
+
+    #[test]
+    fn test_stream_across_chunks() {
+        let ahocorasick = matcher(&["select", "union"]);
+        let mut stream = ahocorasick.stream(MatchOptions::default());
+
+        let mut matches = Vec::new();
+        for chunk in ["id=1 un", "ion sel", "e", "ct * from"] {
+            matches.extend(stream.feed_str(chunk));
+        }
+        matches.extend(stream.finish());
+        assert_eq!(matches, vec![(1, 5, 10), (0, 11, 17)]);
+    }
+
+    #[test]
+    fn test_stream_utf8_split_inside_char() {
+        let ahocorasick = matcher(&["vàng"]);
+        let mut stream = ahocorasick.stream(MatchOptions::default());
+        let bytes = "giá vàng".as_bytes();
+
+        // Cắt từng byte một, kể cả giữa ký tự nhiều byte
+        let mut matches = Vec::new();
+        for byte in bytes {
+            matches.extend(stream.feed(std::slice::from_ref(byte)));
+        }
+        let start = "giá ".len();
+        assert_eq!(matches, vec![(0, start, bytes.len())]);
+
+        // Byte rác ngắt match đang dở
+        let mut stream = ahocorasick.stream(MatchOptions::default());
+        assert!(stream.feed(b"v\xE0 v\xFFang").is_empty());
+    }
+
+    #[test]
+    fn test_stream_case_insensitive() {
+        let ahocorasick = matcher(&["SELECT", "đồng"]);
+
+        let sensitive = ahocorasick.find_all("Select ĐỒNG", MatchOptions::default());
+        assert!(sensitive.is_empty());
+
+        let ascii = MatchOptions {
+            case: CaseMode::AsciiInsensitive,
+            ..Default::default()
+        };
+        assert_eq!(ahocorasick.find_all("Select ĐỒNG", ascii), vec![(0, 0, 6)]);
+
+        let unicode = MatchOptions {
+            case: CaseMode::UnicodeInsensitive,
+            ..Default::default()
+        };
+        let text = "Select ĐỒNG";
+        assert_eq!(
+            ahocorasick.find_all(text, unicode),
+            vec![(0, 0, 6), (1, 7, text.len())]
+        );
+    }
+
+    #[test]
+    fn test_stream_leftmost_longest() {
+        let options = MatchOptions {
+            kind: MatchKind::LeftmostLongest,
+            ..Default::default()
+        };
+
+        let ahocorasick = matcher(&["he", "she", "his", "hers"]);
+        assert_eq!(ahocorasick.find_all("ushers", options), vec![(1, 1, 4)]);
+
+        let ahocorasick = matcher(&["abcd", "abc", "bcde", "e"]);
+        assert_eq!(
+            ahocorasick.find_all("abcdeabce", options),
+            vec![(0, 0, 4), (3, 4, 5), (1, 5, 8), (3, 8, 9)]
+        );
+
+        // Match ngắn bắt đầu sớm đang chờ, match sau không chồng lấn vẫn được giữ
+        let ahocorasick = matcher(&["abcd", "a", "bc"]);
+        let mut stream = ahocorasick.stream(options);
+        let mut matches = stream.feed_str("ab");
+        assert!(matches.is_empty(), "'a' chưa chắc chắn khi 'abcd' còn dở");
+        matches.extend(stream.feed_str("cx"));
+        matches.extend(stream.finish());
+        assert_eq!(matches, vec![(1, 0, 1), (2, 1, 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_roundtrip() {
+        let mut ahocorasick = AhoCorasick::new();
+        for pattern in ["he", "she", "his", "hers", "ới"] {
+            ahocorasick.add(pattern.to_string());
+        }
+        ahocorasick.optimize().await;
+
+        let blob = ahocorasick.snapshot().await.unwrap();
+        let restored = AhoCorasick::from_snapshot(&blob).await.unwrap();
+
+        for sample in ["she", "hello", "ushers", "his", "us", "x", "ưới", "ơi"] {
+            let sample = sample.to_string();
+            assert_eq!(
+                restored.similar(&sample).await,
+                ahocorasick.similar(&sample).await,
+                "sample {sample:?}"
+            );
+        }
+
+        // Snapshot lại từ bản đã nạp cho ra đúng blob cũ
+        assert_eq!(restored.snapshot().await.unwrap(), blob);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_into_file_storage() {
+        let path = std::env::temp_dir().join(format!(
+            "algorithm-ahocorasick-snapshot-{}.log",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut ahocorasick = AhoCorasick::new();
+        ahocorasick.add("CGT".to_string());
+        ahocorasick.add("TGC".to_string());
+        ahocorasick.optimize().await;
+        let blob = ahocorasick.snapshot().await.unwrap();
+
+        let storage = storage::file::FileStorage::open(&path).unwrap();
+        let mut restored = AhoCorasick::with_storage(storage);
+        restored.load_snapshot(&blob).await.unwrap();
+        assert!(restored.similar(&"ACGT".to_string()).await);
+        assert!(!restored.similar(&"GGG".to_string()).await);
+
+        // Storage đã có dữ liệu → không nạp đè
+        assert!(matches!(
+            restored.load_snapshot(&blob).await,
+            Err(AhoCorasickError::NotEmpty)
+        ));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_errors() {
+        let mut ahocorasick = AhoCorasick::new();
+        ahocorasick.add("hers".to_string());
+        assert!(matches!(
+            ahocorasick.snapshot().await,
+            Err(AhoCorasickError::NotOptimized)
+        ));
+
+        ahocorasick.optimize().await;
+        let blob = ahocorasick.snapshot().await.unwrap();
+
+        // Sai checksum
+        let mut corrupted = blob.clone();
+        corrupted[14] ^= 0xFF;
+        assert!(matches!(
+            AhoCorasick::from_snapshot(&corrupted).await,
+            Err(AhoCorasickError::InvalidSnapshot(_))
+        ));
+
+        // Bị cắt cụt
+        for len in [0, 5, 12, blob.len() - 1] {
+            assert!(AhoCorasick::from_snapshot(&blob[..len]).await.is_err());
+        }
+
+        // Version lạ
+        let mut future = blob.clone();
+        future[8..12].copy_from_slice(&2u32.to_le_bytes());
+        assert!(matches!(
+            AhoCorasick::from_snapshot(&future).await,
+            Err(AhoCorasickError::UnsupportedVersion(2))
+        ));
+
+        // Checksum đúng nhưng state id trỏ ra ngoài
+        let mut body = blob[..blob.len() - 4].to_vec();
+        let last = body.len() - 4;
+        body[last..].copy_from_slice(&999u32.to_le_bytes());
+        let crc = storage::file::crc32(&body);
+        body.extend_from_slice(&crc.to_le_bytes());
+        assert!(matches!(
+            AhoCorasick::from_snapshot(&body).await,
+            Err(AhoCorasickError::InvalidSnapshot(_))
+        ));
+    }
 }
@@ -104,4 +104,51 @@ mod tests {
         let target = Person { age: 40 };
         assert_eq!(binary_search(&people, &target, age_comparator), None);
     }
+
+    #[test]
+    fn test_lower_and_upper_bound() {
+        let arr = vec![1, 3, 3, 3, 5, 8];
+        let ascending_comparator = |a: &i32, b: &i32| a.cmp(b);
+
+        assert_eq!(lower_bound(&arr, &3, ascending_comparator), 1);
+        assert_eq!(upper_bound(&arr, &3, ascending_comparator), 4);
+        assert_eq!(lower_bound(&arr, &4, ascending_comparator), 4);
+        assert_eq!(upper_bound(&arr, &4, ascending_comparator), 4);
+        assert_eq!(lower_bound(&arr, &0, ascending_comparator), 0);
+        assert_eq!(upper_bound(&arr, &9, ascending_comparator), arr.len());
+
+        let empty_arr: Vec<i32> = vec![];
+        assert_eq!(lower_bound(&empty_arr, &1, ascending_comparator), 0);
+        assert_eq!(upper_bound(&empty_arr, &1, ascending_comparator), 0);
+    }
+
+    #[test]
+    fn bench_lower_and_upper_bound() {
+        use std::time::Instant;
+
+        let size: i64 = 1_000_000;
+        let lookups: usize = 1_000_000;
+        let arr = (0..size).map(|i| i / 4).collect::<Vec<i64>>();
+        let ascending_comparator = |a: &i64, b: &i64| a.cmp(b);
+
+        let start = Instant::now();
+        let mut checksum = 0;
+        for i in 0..size {
+            let target = i * 7919 % size / 4;
+            checksum += upper_bound(&arr, &target, ascending_comparator)
+                - lower_bound(&arr, &target, ascending_comparator);
+        }
+        let duration = start.elapsed();
+
+        assert_eq!(checksum, 4 * lookups);
+        println!(
+            "\n⏱️ lower_bound + upper_bound ({} lookups on {} items):",
+            lookups, size
+        );
+        println!(
+            " - {:?} ({:.2} lookups/sec)",
+            duration,
+            lookups as f64 / duration.as_secs_f64()
+        );
+    }
 }
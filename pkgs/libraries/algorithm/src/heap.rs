@@ -1,151 +1,597 @@
-#[derive(Clone)]
-pub struct Heap<T: Clone> {
-    items: Vec<T>,
-    all: usize,
-    branch: usize,
-    comparator: fn(&T, &T) -> i64,
+//! Indexed d-ary heap: ngoài `push`/`pop` còn sửa và xoá được phần tử bất kỳ qua
+//! `Handle` trong O(log_D n).
+//!
+//! ## Idea
+//! Phần tử nằm trong `items` theo thứ tự heap, mỗi phần tử nhớ `slot` của nó.
+//! `slots[slot]` giữ vị trí hiện tại trong `items` và được cập nhật mỗi lần swap,
+//! nhờ vậy `Handle` (slot + generation) luôn tìm lại được phần tử dù nó đã bị
+//! đẩy lên/xuống:
+//!
+//! ```text
+//! items:  [ (9, s2) | (7, s0) | (8, s1) ]      slots: s0 → 1, s1 → 2, s2 → 0
+//! ```
+//!
+//! Slot được tái sử dụng sau khi phần tử rời heap; `generation` tăng mỗi lần như
+//! vậy để handle cũ trả `None` thay vì trỏ nhầm sang phần tử mới.
+//!
+//! `D = 4` thường nhanh hơn binary heap khi `push`/`update` nhiều hơn `pop` (cây
+//! thấp hơn, các con nằm liền nhau trong cache line).
+
+use std::cmp::Ordering;
+
+const VACANT: usize = usize::MAX;
+
+/// Tham chiếu tới một phần tử đã `push` vào `Heap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    slot: usize,
+    generation: u32,
+}
+
+struct Entry<T> {
+    value: T,
+    slot: usize,
+}
+
+struct Slot {
+    /// Vị trí trong `items`, `VACANT` nếu slot đang trống.
+    position: usize,
+    generation: u32,
+}
+
+/// Max-heap theo `comparator`: `peek`/`pop` trả phần tử lớn nhất, giống
+/// `std::collections::BinaryHeap`. Đảo comparator để có min-heap.
+pub struct Heap<T, F, const D: usize = 2>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    items: Vec<Entry<T>>,
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+    comparator: F,
 }
 
-impl<T: Clone> Heap<T> {
-    pub fn new(comparator: fn(&T, &T) -> i64) -> Self {
+impl<T, F, const D: usize> Heap<T, F, D>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    pub fn new(comparator: F) -> Self {
+        Self::with_capacity(0, comparator)
+    }
+
+    pub fn with_capacity(capacity: usize, comparator: F) -> Self {
+        assert!(D >= 2, "Heap cần ít nhất 2 nhánh");
+
         Self {
-            items: vec![],
-            all: 0,
-            branch: 0,
+            items: Vec::with_capacity(capacity),
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
             comparator,
         }
     }
 
-    pub fn size(&self) -> usize {
-        self.all
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.items.first().map(|entry| &entry.value)
+    }
+
+    pub fn peek_handle(&self) -> Option<Handle> {
+        self.items.first().map(|entry| self.handle_of(entry.slot))
+    }
+
+    pub fn push(&mut self, value: T) -> Handle {
+        let slot = match self.free.pop() {
+            Some(slot) => slot,
+            None => {
+                self.slots.push(Slot {
+                    position: VACANT,
+                    generation: 0,
+                });
+                self.slots.len() - 1
+            }
+        };
+
+        let position = self.items.len();
+        self.items.push(Entry { value, slot });
+        self.slots[slot].position = position;
+        self.sift_up(position);
+
+        self.handle_of(slot)
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.remove_at(0)
+    }
+
+    /// Thay `peek` bằng `value` trong một lượt sift, rẻ hơn `pop` rồi `push`.
+    pub fn replace_top(&mut self, value: T) -> Option<T> {
+        let top = self.items.first_mut()?;
+        let old = std::mem::replace(&mut top.value, value);
+        self.sift_down(0);
+        Some(old)
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.position(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.position(handle)
+            .map(|position| &self.items[position].value)
+    }
+
+    /// Đổi giá trị của phần tử rồi đưa về đúng vị trí — vừa là decrease-key vừa là
+    /// increase-key. Trả về giá trị cũ, `None` nếu handle không còn trong heap.
+    pub fn update(&mut self, handle: Handle, value: T) -> Option<T> {
+        let position = self.position(handle)?;
+        let old = std::mem::replace(&mut self.items[position].value, value);
+        self.restore(position);
+        Some(old)
+    }
+
+    /// Như `update` nhưng sửa tại chỗ.
+    pub fn modify(&mut self, handle: Handle, f: impl FnOnce(&mut T)) -> bool {
+        let Some(position) = self.position(handle) else {
+            return false;
+        };
+        f(&mut self.items[position].value);
+        self.restore(position);
+        true
+    }
+
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let position = self.position(handle)?;
+        self.remove_at(position)
     }
 
-    pub fn get(&self) -> Option<&T> {
-        if self.all == 0 {
-            None
-        } else {
-            self.items.first()
+    pub fn clear(&mut self) {
+        for entry in self.items.drain(..) {
+            let slot = &mut self.slots[entry.slot];
+            slot.position = VACANT;
+            slot.generation = slot.generation.wrapping_add(1);
+            self.free.push(entry.slot);
         }
     }
 
-    pub fn get_mut(&mut self) -> Option<&T> {
-        if self.all == 0 {
-            None
-        } else {
-            self.items.first()
+    /// Lấy hết phần tử, lớn nhất trước.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(value) = self.pop() {
+            sorted.push(value);
         }
+        sorted
     }
 
-    pub fn pop(&mut self) -> bool {
-        self.refresh(true)
+    fn handle_of(&self, slot: usize) -> Handle {
+        Handle {
+            slot,
+            generation: self.slots[slot].generation,
+        }
     }
 
-    pub fn push(&mut self, value: T) -> bool {
-        if self.all == self.items.len() {
-            self.items.push(value);
-        } else {
-            self.items[self.all] = value;
+    fn position(&self, handle: Handle) -> Option<usize> {
+        self.slots
+            .get(handle.slot)
+            .filter(|slot| slot.generation == handle.generation && slot.position != VACANT)
+            .map(|slot| slot.position)
+    }
+
+    fn remove_at(&mut self, position: usize) -> Option<T> {
+        if position >= self.items.len() {
+            return None;
         }
 
-        self.all += 1;
-        self.branch = self.all / 2;
+        let last = self.items.len() - 1;
+        self.swap(position, last);
+        let entry = self.items.pop()?;
 
-        self.refresh(false)
+        let slot = &mut self.slots[entry.slot];
+        slot.position = VACANT;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(entry.slot);
+
+        if position < self.items.len() {
+            self.restore(position);
+        }
+        Some(entry.value)
     }
 
-    fn refresh(&mut self, sorting: bool) -> bool {
-        if self.branch > 0 && sorting {
-            return false;
+    /// Phần tử tại `position` vừa đổi giá trị: chỉ một trong hai hướng có tác dụng.
+    fn restore(&mut self, position: usize) {
+        if self.sift_up(position) == position {
+            self.sift_down(position);
         }
+    }
 
-        loop {
-            if self.branch > 0 {
-                self.branch -= 1;
-            } else if sorting {
-                self.all -= 1;
+    fn greater(&self, left: usize, right: usize) -> bool {
+        (self.comparator)(&self.items[left].value, &self.items[right].value) == Ordering::Greater
+    }
 
-                if self.all > 0 {
-                    self.swap(0, self.all);
-                }
-            } else {
+    fn sift_up(&mut self, mut position: usize) -> usize {
+        while position > 0 {
+            let parent = (position - 1) / D;
+            if !self.greater(position, parent) {
+                break;
+            }
+            self.swap(position, parent);
+            position = parent;
+        }
+        position
+    }
+
+    fn sift_down(&mut self, mut position: usize) {
+        loop {
+            let first = position * D + 1;
+            if first >= self.items.len() {
                 break;
             }
 
-            let mut copy_branch = self.branch;
-            let mut left_leaf: usize;
-            let mut right_leaf: usize;
-
-            loop {
-                left_leaf = 2 * copy_branch + 1;
-                right_leaf = left_leaf + 1;
-
-                if right_leaf < self.all {
-                    let left = self.items.get(left_leaf).unwrap();
-                    let right = self.items.get(right_leaf).unwrap();
-
-                    if (self.comparator)(left, right) > 0 {
-                        copy_branch = left_leaf;
-                    } else {
-                        copy_branch = right_leaf;
-                    }
-                } else {
-                    break;
+            let last = (first + D).min(self.items.len());
+            let mut best = first;
+            for child in first + 1..last {
+                if self.greater(child, best) {
+                    best = child;
                 }
             }
 
-            if right_leaf == self.all {
-                copy_branch = left_leaf;
+            if !self.greater(best, position) {
+                break;
             }
+            self.swap(position, best);
+            position = best;
+        }
+    }
 
-            loop {
-                if copy_branch == self.branch {
-                    break;
-                }
-                let top = self.items.get(self.branch).unwrap();
-                let bot = self.items.get(copy_branch).unwrap();
+    fn swap(&mut self, left: usize, right: usize) {
+        if left == right {
+            return;
+        }
+        self.items.swap(left, right);
+        self.slots[self.items[left].slot].position = left;
+        self.slots[self.items[right].slot].position = right;
+    }
+}
 
-                if (self.comparator)(top, bot) <= 0 {
-                    break;
-                }
+// ==================== Top-K ====================
 
-                copy_branch = (copy_branch + copy_branch % 2) / 2 - 1;
-            }
+/// Số slot đặt trước tối đa của `TopK`, `k` lớn hơn thì heap tự lớn dần
+const TOP_K_RESERVED: usize = 1024;
 
-            let cache = copy_branch;
+/// Giữ `k` phần tử đứng đầu theo thứ tự của `comparator` — tương đương
+/// `sort_by(comparator)` rồi `truncate(k)` nhưng chỉ tốn O(n log k) và O(k) bộ
+/// nhớ.
+///
+/// Bên trong là max-heap theo `comparator`: đỉnh heap là phần tử kém nhất đang
+/// giữ, phần tử mới chỉ vào được khi đứng trước nó.
+pub struct TopK<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    heap: Heap<T, F, 4>,
+    k: usize,
+}
 
-            while copy_branch != self.branch {
-                copy_branch = (copy_branch + copy_branch % 2) / 2 - 1;
-                self.swap(copy_branch, cache);
-            }
+impl<T, F> TopK<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    pub fn new(k: usize, comparator: F) -> Self {
+        Self::with_reserved(k, TOP_K_RESERVED, comparator)
+    }
 
-            if self.branch == 0 && sorting {
-                break;
+    /// @NOTE: `k` thường là `limit` từ request (có thể `usize::MAX`), nên chỉ đặt
+    /// trước `min(k, reserved)` slot
+    fn with_reserved(k: usize, reserved: usize, comparator: F) -> Self {
+        Self {
+            heap: Heap::with_capacity(k.min(reserved), comparator),
+            k,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Phần tử kém nhất đang được giữ — ngưỡng để lọt vào top.
+    pub fn threshold(&self) -> Option<&T> {
+        self.heap.peek()
+    }
+
+    /// Trả `true` nếu `value` được giữ lại.
+    pub fn push(&mut self, value: T) -> bool {
+        if self.heap.len() < self.k {
+            self.heap.push(value);
+            return true;
+        }
+
+        match self.heap.peek() {
+            Some(worst) if (self.heap.comparator)(&value, worst) == Ordering::Less => {
+                self.heap.replace_top(value);
+                true
             }
+            _ => false,
         }
+    }
 
-        true
+    /// Kết quả theo thứ tự của `comparator` (tốt nhất trước).
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let mut sorted = self.heap.into_sorted_vec();
+        sorted.reverse();
+        sorted
     }
+}
 
-    fn swap(&mut self, left_index: usize, right_index: usize) {
-        self.items.swap(left_index, right_index);
+impl<T, F> Extend<T> for TopK<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
     }
 }
 
+/// `k` phần tử đầu tiên của `iter` nếu sắp theo `comparator`.
+pub fn top_k<T, F, I>(iter: I, k: usize, comparator: F) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+    F: Fn(&T, &T) -> Ordering,
+{
+    let iter = iter.into_iter();
+    let (lower, _) = iter.size_hint();
+    let mut top = TopK::with_reserved(k, lower.min(TOP_K_RESERVED), comparator);
+    top.extend(iter);
+    top.into_sorted_vec()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+    use std::collections::BinaryHeap;
+    use std::time::Instant;
+
+    fn drain<T, F: Fn(&T, &T) -> Ordering, const D: usize>(mut heap: Heap<T, F, D>) -> Vec<T> {
+        let mut out = Vec::new();
+        while let Some(value) = heap.pop() {
+            out.push(value);
+        }
+        out
+    }
+
+    fn check_sorted<const D: usize>(values: &[i64]) {
+        let mut heap = Heap::<i64, _, D>::new(|l: &i64, r: &i64| l.cmp(r));
+        for &value in values {
+            heap.push(value);
+        }
+
+        let mut expected = values.to_vec();
+        expected.sort_unstable_by(|l, r| r.cmp(l));
+        assert_eq!(drain(heap), expected, "D = {D}");
+    }
 
     #[test]
     fn test_simple_heap() {
-        let mut heap = Heap::new(|l: &i64, r: &i64| -> i64 { l - r });
+        let mut heap = Heap::<i64, _>::new(|l: &i64, r: &i64| l.cmp(r));
 
         for i in 0..100 {
-            assert_eq!(heap.push(i), true);
+            heap.push(i);
         }
 
         for i in (0..100).rev() {
-            assert_eq!(heap.get().unwrap_or(&-1), &i);
-            assert_eq!(heap.pop(), true);
+            assert_eq!(heap.peek(), Some(&i));
+            assert_eq!(heap.pop(), Some(i));
+        }
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn test_d_ary_orders_match_sort() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let values = (0..2_000)
+            .map(|_| rng.gen_range(-500..500))
+            .collect::<Vec<i64>>();
+
+        check_sorted::<2>(&values);
+        check_sorted::<3>(&values);
+        check_sorted::<4>(&values);
+        check_sorted::<8>(&values);
+    }
+
+    #[test]
+    fn test_update_and_remove_by_handle() {
+        // Min-heap: đảo comparator
+        let mut heap =
+            Heap::<(u32, &str), _, 4>::new(|l: &(u32, &str), r: &(u32, &str)| r.0.cmp(&l.0));
+
+        let a = heap.push((50, "a"));
+        let b = heap.push((40, "b"));
+        let c = heap.push((30, "c"));
+        let d = heap.push((20, "d"));
+        assert_eq!(heap.peek(), Some(&(20, "d")));
+
+        // decrease-key: a lên đỉnh
+        assert_eq!(heap.update(a, (10, "a")), Some((50, "a")));
+        assert_eq!(heap.peek_handle(), Some(a));
+
+        // increase-key: a chìm xuống cuối
+        assert!(heap.modify(a, |value| value.0 = 60));
+        assert_eq!(heap.peek(), Some(&(20, "d")));
+
+        assert_eq!(heap.remove(c), Some((30, "c")));
+        assert!(!heap.contains(c));
+        assert_eq!(heap.remove(c), None);
+        assert_eq!(heap.get(b), Some(&(40, "b")));
+
+        // Slot của `c` được tái sử dụng nhưng handle cũ không trỏ sang phần tử mới
+        let e = heap.push((35, "e"));
+        assert_eq!(heap.get(c), None);
+        assert_eq!(heap.update(c, (0, "c")), None);
+
+        assert_eq!(heap.pop(), Some((20, "d")));
+        assert!(!heap.contains(d));
+        assert_eq!(heap.pop(), Some((35, "e")));
+        assert!(!heap.contains(e));
+        assert_eq!(drain(heap), vec![(40, "b"), (60, "a")]);
+    }
+
+    #[test]
+    fn test_random_operations_keep_invariant() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut heap = Heap::<i64, _, 3>::new(|l: &i64, r: &i64| l.cmp(r));
+        let mut live: Vec<(Handle, i64)> = Vec::new();
+
+        for _ in 0..5_000 {
+            match rng.gen_range(0..4) {
+                0 | 1 => {
+                    let value = rng.gen_range(0..1_000);
+                    live.push((heap.push(value), value));
+                }
+                2 if !live.is_empty() => {
+                    let index = rng.gen_range(0..live.len());
+                    let value = rng.gen_range(0..1_000);
+                    heap.update(live[index].0, value).unwrap();
+                    live[index].1 = value;
+                }
+                _ if !live.is_empty() => {
+                    let index = rng.gen_range(0..live.len());
+                    let (handle, value) = live.swap_remove(index);
+                    assert_eq!(heap.remove(handle), Some(value));
+                }
+                _ => {}
+            }
+
+            assert_eq!(heap.len(), live.len());
+            assert_eq!(
+                heap.peek().copied(),
+                live.iter().map(|(_, value)| *value).max()
+            );
+        }
+
+        let mut expected = live.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+        expected.sort_unstable_by(|l, r| r.cmp(l));
+        assert_eq!(heap.into_sorted_vec(), expected);
+    }
+
+    #[test]
+    fn test_top_k_matches_sort_truncate() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut values = (0..1_000)
+            .map(|i| (rng.gen_range(0..100), i))
+            .collect::<Vec<(i32, i32)>>();
+        values.shuffle(&mut rng);
+
+        // Điểm giảm dần, bằng điểm thì id nhỏ trước
+        let order = |l: &(i32, i32), r: &(i32, i32)| r.0.cmp(&l.0).then(l.1.cmp(&r.1));
+
+        let mut expected = values.clone();
+        expected.sort_by(order);
+
+        for k in [0, 1, 7, 100, 1_000, 5_000] {
+            let mut truncated = expected.clone();
+            truncated.truncate(k);
+            assert_eq!(
+                top_k(values.iter().copied(), k, order),
+                truncated,
+                "k = {k}"
+            );
         }
+
+        // `k` khổng lồ không được đặt trước bộ nhớ theo `k`
+        assert_eq!(top_k(values.iter().copied(), usize::MAX, order), expected);
+        let mut huge = TopK::new(usize::MAX, |l: &i32, r: &i32| l.cmp(r));
+        huge.extend([3, 1, 2]);
+        assert_eq!(huge.into_sorted_vec(), vec![1, 2, 3]);
+
+        let mut top = TopK::new(2, |l: &i32, r: &i32| l.cmp(r));
+        assert!(top.push(5));
+        assert!(top.push(3));
+        assert!(!top.push(9));
+        assert!(top.push(1));
+        assert_eq!(top.threshold(), Some(&3));
+        assert_eq!(top.into_sorted_vec(), vec![1, 3]);
+    }
+
+    #[test]
+    fn bench_heap_push_pop() {
+        let size = 1_000_000;
+        let mut rng = StdRng::seed_from_u64(3);
+        let values = (0..size).map(|_| rng.r#gen::<u64>()).collect::<Vec<_>>();
+
+        let start = Instant::now();
+        let mut std_heap = BinaryHeap::with_capacity(size);
+        for &value in &values {
+            std_heap.push(value);
+        }
+        while std_heap.pop().is_some() {}
+        let std_duration = start.elapsed();
+
+        let start = Instant::now();
+        let mut binary = Heap::<u64, _, 2>::with_capacity(size, |l: &u64, r: &u64| l.cmp(r));
+        for &value in &values {
+            binary.push(value);
+        }
+        while binary.pop().is_some() {}
+        let binary_duration = start.elapsed();
+
+        let start = Instant::now();
+        let mut quaternary = Heap::<u64, _, 4>::with_capacity(size, |l: &u64, r: &u64| l.cmp(r));
+        let handles = values
+            .iter()
+            .map(|&value| quaternary.push(value))
+            .collect::<Vec<_>>();
+        for (handle, &value) in handles.iter().zip(&values).step_by(2) {
+            quaternary.update(*handle, value / 2);
+        }
+        while quaternary.pop().is_some() {}
+        let quaternary_duration = start.elapsed();
+
+        println!("\n⏱️ Heap push + pop ({} items):", size);
+        println!(" - std BinaryHeap: {:?}", std_duration);
+        println!(" - Heap<2>: {:?}", binary_duration);
+        println!(
+            " - Heap<4> + {} updates: {:?}",
+            size / 2,
+            quaternary_duration
+        );
+    }
+
+    #[test]
+    fn bench_top_k() {
+        let size = 1_000_000;
+        let k = 50;
+        let mut rng = StdRng::seed_from_u64(5);
+        let values = (0..size)
+            .map(|_| rng.gen_range(0.0..1.0))
+            .collect::<Vec<f64>>();
+        let order = |l: &f64, r: &f64| r.total_cmp(l);
+
+        let start = Instant::now();
+        let mut sorted = values.clone();
+        sorted.sort_by(order);
+        sorted.truncate(k);
+        let sort_duration = start.elapsed();
+
+        let start = Instant::now();
+        let top = top_k(values.iter().copied(), k, order);
+        let top_duration = start.elapsed();
+
+        assert_eq!(top, sorted);
+        println!("\n⏱️ Top {} of {} items:", k, size);
+        println!(" - sort + truncate: {:?}", sort_duration);
+        println!(" - top_k: {:?}", top_duration);
     }
 }
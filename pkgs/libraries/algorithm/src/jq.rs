@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::io::{Error, ErrorKind};
 use utoipa::ToSchema;
 
@@ -9,11 +11,79 @@ pub enum Operator {
     Access(usize),
     Iter,
     Select(Vec<String>),
+    /// Index âm, đếm từ cuối mảng: `[-1]` là phần tử cuối.
+    Index(i64),
+    /// `[start:end:step]`, cùng ngữ nghĩa slice của Python (index âm, step âm).
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: i64,
+    },
+    /// `[?(@.path op value)]`: giữ các phần tử con thoả điều kiện.
+    Filter {
+        path: Vec<String>,
+        op: Comparison,
+        value: Value,
+    },
+    /// `..`: chính node hiện tại và mọi node con cháu (pre-order), nên
+    /// `..field` là `Recurse` rồi `Match(field)`.
+    Recurse,
+    /// `*`: mọi phần tử con của mảng/object.
+    Wildcard,
+    /// `| function(...)`: biến đổi từng giá trị, chỉ chạy trong `execute`.
+    Call(Function),
+}
+
+/// Hàm dùng sau dấu `|`, áp lên từng giá trị của bước trước (như jq): `data.t |
+/// sum` cộng cả mảng, còn `data.t[] | multiply(2)` nhân từng phần tử.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub enum Function {
+    /// Số phần tử của mảng/object, số ký tự của chuỗi, trị tuyệt đối của số.
+    Length,
+    /// Key (đã sort) của object, hoặc danh sách index của mảng.
+    Keys,
+    /// Số giữ nguyên, chuỗi như `"26,108.00"` được parse (bỏ dấu phẩy ngăn cách).
+    ToNumber,
+    ToString,
+    Split(String),
+    Join(String),
+    Sum,
+    Min,
+    Max,
+    Avg,
+    /// `map(query)`: chạy `query` trên từng phần tử mảng, gom kết quả thành mảng.
+    #[schema(no_recursion)]
+    Map(Vec<Operator>),
+    /// `select(query op value)`: giữ giá trị nếu kết quả đầu tiên của `query`
+    /// thoả điều kiện.
+    #[schema(no_recursion)]
+    Select {
+        path: Vec<Operator>,
+        op: Comparison,
+        value: Value,
+    },
+    Multiply(f64),
+    Divide(f64),
+    /// Chuỗi ngày giờ (ISO 8601 hoặc `dd/mm/yyyy`) → epoch giây. Tham số là
+    /// offset (giây) dùng khi chuỗi không kèm timezone.
+    ToEpoch(i32),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum Comparison {
+    /// `[?(@.field)]` — field tồn tại, bỏ qua `value`.
+    Exists,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JsonQuery {
-    operators: Vec<Operator>,
+    pub(crate) operators: Vec<Operator>,
 }
 
 impl JsonQuery {
@@ -21,98 +91,73 @@ impl JsonQuery {
         Self { operators }
     }
 
-    pub fn parse(path: &str) -> Result<Self, Error> {
+    /// Parse một query dạng `path | function | .path ...`. Bước đầu luôn là path
+    /// (giữ tương thích với path cũ như `data.t[]`); các bước sau là path nếu bắt
+    /// đầu bằng `.`/`[`, ngược lại là function.
+    pub fn parse(query: &str) -> Result<Self, Error> {
+        Self::parse_pipeline(query, true)
+    }
+
+    /// Query con trong `map(...)`/`select(...)` được bắt đầu bằng function.
+    fn parse_pipeline(query: &str, path_first: bool) -> Result<Self, Error> {
         let mut operators = Vec::new();
-        let mut chars = path.chars().peekable();
 
-        while let Some(c) = chars.next() {
-            match c {
-                '.' => continue,
-                '[' => {
-                    let mut content = String::new();
-                    let mut found_close = false;
-                    while let Some(&next) = chars.peek() {
-                        if next == ']' {
-                            chars.next();
-                            found_close = true;
-                            break;
-                        }
-                        content.push(chars.next().unwrap());
-                    }
+        for (i, stage) in split_top_level(query, '|').into_iter().enumerate() {
+            let stage = stage.trim();
+            if i == 0 && (path_first || stage.is_empty()) {
+                operators.extend(Self::parse_path(stage)?);
+            } else if stage.is_empty() {
+                return Err(invalid(format!("Empty stage after `|` in `{query}`")));
+            } else if stage.starts_with(['.', '[']) {
+                operators.extend(Self::parse_path(stage)?);
+            } else {
+                operators.push(Operator::Call(parse_call(stage)?));
+            }
+        }
+        Ok(Self { operators })
+    }
 
-                    if !found_close {
-                        return Err(Error::new(
-                            ErrorKind::InvalidData,
-                            "Missing closing bracket ']'",
-                        ));
-                    }
+    fn parse_path(path: &str) -> Result<Vec<Operator>, Error> {
+        let chars = path.chars().collect::<Vec<_>>();
+        let mut operators = Vec::new();
+        let mut i = 0;
 
-                    if content.is_empty() {
-                        operators.push(Operator::Iter);
-                    } else if let Ok(index) = content.parse::<usize>() {
-                        operators.push(Operator::Access(index));
+        while i < chars.len() {
+            match chars[i] {
+                '.' => {
+                    if chars.get(i + 1) == Some(&'.') {
+                        operators.push(Operator::Recurse);
+                        i += 2;
                     } else {
-                        let clean_field = content.trim_matches(|c| c == '"' || c == '\'');
-                        operators.push(Operator::Match(clean_field.to_string()));
+                        i += 1;
                     }
                 }
+                '[' => {
+                    let (content, next) = read_bracket(&chars, i + 1)?;
+                    operators.push(parse_bracket(content.trim())?);
+                    i = next;
+                }
                 _ => {
-                    let mut field = String::new();
-                    field.push(c);
-                    while let Some(&next) = chars.peek() {
-                        if next == '.' || next == '[' {
-                            break;
-                        }
-                        field.push(chars.next().unwrap());
-                    }
-                    if let Ok(index) = field.parse::<usize>() {
-                        operators.push(Operator::Access(index));
-                    } else {
-                        operators.push(Operator::Match(field));
+                    let start = i;
+                    while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                        i += 1;
                     }
+                    operators.push(parse_field(chars[start..i].iter().collect()));
                 }
             }
         }
-        Ok(Self { operators })
+        Ok(operators)
     }
 
+    /// Trích tham chiếu (zero-copy). `Select` và function cần tạo giá trị mới nên
+    /// không cho kết quả ở đây — dùng `execute` cho query có `|`.
     pub fn pick<'a>(&self, data: &'a Value) -> Vec<&'a Value> {
         let mut collection = vec![data];
 
         for op in &self.operators {
             let mut next_collection = Vec::new();
             for item in collection {
-                match op {
-                    Operator::Match(field) => {
-                        if let Some(v) = item.get(field) {
-                            next_collection.push(v);
-                        }
-                    }
-                    Operator::Access(index) => {
-                        if let Some(v) = item.get(*index) {
-                            next_collection.push(v);
-                        } else if let Some(v) = item.get(index.to_string()) {
-                            next_collection.push(v);
-                        }
-                    }
-                    Operator::Iter => {
-                        if let Some(arr) = item.as_array() {
-                            for v in arr {
-                                next_collection.push(v);
-                            }
-                        } else if let Some(obj) = item.as_object() {
-                            for v in obj.values() {
-                                next_collection.push(v);
-                            }
-                        } else {
-                            println!(
-                                "Warning: Cannot iterate over non-array/object value: {:?}",
-                                item
-                            );
-                        }
-                    }
-                    _ => {}
-                }
+                step(op, item, &mut next_collection);
             }
             collection = next_collection;
             if collection.is_empty() {
@@ -123,54 +168,702 @@ impl JsonQuery {
     }
 
     pub fn execute(&self, data: &Value) -> Vec<Value> {
-        let mut collection = vec![data.clone()];
+        run(&self.operators, data)
+    }
+}
 
-        for op in &self.operators {
-            let mut next_collection = Vec::new();
-            for item in collection {
-                match op {
-                    Operator::Match(field) => {
+/// Giá trị còn nằm trong `data` được giữ dạng tham chiếu, chỉ giá trị do
+/// `Select`/function tạo ra mới là owned — nên query không có `|` chỉ clone kết
+/// quả cuối.
+fn run(operators: &[Operator], data: &Value) -> Vec<Value> {
+    let mut collection = vec![Cow::Borrowed(data)];
+
+    for op in operators {
+        let mut next_collection = Vec::new();
+        for item in &collection {
+            match op {
+                // CHỖ NÀY HẾT LỖI: Vì chúng ta trả về Value sở hữu (Owned)
+                Operator::Select(fields) => {
+                    let mut new_obj = serde_json::Map::new();
+                    for field in fields {
                         if let Some(v) = item.get(field) {
-                            next_collection.push(v.clone());
+                            new_obj.insert(field.clone(), v.clone());
                         }
                     }
-                    Operator::Access(index) => {
-                        if let Some(v) = item.get(*index) {
-                            next_collection.push(v.clone());
-                        } else if let Some(v) = item.get(index.to_string()) {
-                            next_collection.push(v.clone());
-                        }
-                    }
-                    Operator::Iter => {
-                        if let Some(arr) = item.as_array() {
-                            for v in arr {
-                                next_collection.push(v.clone());
-                            }
-                        } else if let Some(obj) = item.as_object() {
-                            for v in obj.values() {
-                                next_collection.push(v.clone());
-                            }
-                        }
+                    next_collection.push(Cow::Owned(Value::Object(new_obj)));
+                }
+                Operator::Call(function) => {
+                    let mut produced = Vec::new();
+                    call(function, item, &mut produced);
+                    next_collection.extend(produced.into_iter().map(Cow::Owned));
+                }
+                _ => match item {
+                    Cow::Borrowed(item) => {
+                        let mut picked = Vec::new();
+                        step(op, item, &mut picked);
+                        next_collection.extend(picked.into_iter().map(Cow::Borrowed));
                     }
-                    // CHỖ NÀY HẾT LỖI: Vì chúng ta trả về Value sở hữu (Owned)
-                    Operator::Select(fields) => {
-                        let mut new_obj = serde_json::Map::new();
-                        for field in fields {
-                            if let Some(v) = item.get(field) {
-                                new_obj.insert(field.clone(), v.clone());
-                            }
-                        }
-                        next_collection.push(Value::Object(new_obj));
+                    Cow::Owned(item) => {
+                        let mut picked = Vec::new();
+                        step(op, item, &mut picked);
+                        next_collection.extend(picked.into_iter().cloned().map(Cow::Owned));
                     }
+                },
+            }
+        }
+        collection = next_collection;
+        if collection.is_empty() {
+            break;
+        }
+    }
+    collection.into_iter().map(Cow::into_owned).collect()
+}
+
+// ==================== Evaluate ====================
+
+/// Áp một operator lên `item`, đẩy các node kết quả vào `out`. `Select` cần tạo
+/// Value mới nên chỉ được xử lý trong `execute`.
+fn step<'a>(op: &Operator, item: &'a Value, out: &mut Vec<&'a Value>) {
+    match op {
+        Operator::Match(field) => {
+            if let Some(v) = item.get(field) {
+                out.push(v);
+            }
+        }
+        Operator::Access(index) => {
+            if let Some(v) = item.get(*index) {
+                out.push(v);
+            } else if let Some(v) = item.get(index.to_string()) {
+                out.push(v);
+            }
+        }
+        Operator::Index(index) => {
+            if let Some(arr) = item.as_array()
+                && let Some(index) = resolve_index(arr.len(), *index)
+            {
+                out.push(&arr[index]);
+            }
+        }
+        Operator::Iter => {
+            if item.is_array() || item.is_object() {
+                children(item, out);
+            } else {
+                log::warn!("Cannot iterate over non-array/object value: {:?}", item);
+            }
+        }
+        Operator::Wildcard => children(item, out),
+        Operator::Slice { start, end, step } => {
+            if let Some(arr) = item.as_array() {
+                for index in slice_indices(arr.len(), *start, *end, *step) {
+                    out.push(&arr[index]);
                 }
             }
-            collection = next_collection;
-            if collection.is_empty() {
-                break;
+        }
+        Operator::Filter { path, op, value } => {
+            let mut candidates = Vec::new();
+            children(item, &mut candidates);
+            out.extend(
+                candidates
+                    .into_iter()
+                    .filter(|child| compare(*op, resolve_path(child, path), value)),
+            );
+        }
+        Operator::Recurse => descend(item, out),
+        Operator::Select(_) | Operator::Call(_) => {}
+    }
+}
+
+fn children<'a>(item: &'a Value, out: &mut Vec<&'a Value>) {
+    match item {
+        Value::Array(arr) => out.extend(arr.iter()),
+        Value::Object(obj) => out.extend(obj.values()),
+        _ => {}
+    }
+}
+
+fn descend<'a>(item: &'a Value, out: &mut Vec<&'a Value>) {
+    out.push(item);
+    match item {
+        Value::Array(arr) => arr.iter().for_each(|v| descend(v, out)),
+        Value::Object(obj) => obj.values().for_each(|v| descend(v, out)),
+        _ => {}
+    }
+}
+
+fn resolve_index(len: usize, index: i64) -> Option<usize> {
+    let index = if index < 0 { index + len as i64 } else { index };
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+fn slice_indices(len: usize, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize> {
+    let len = len as i64;
+    let normalize = |x: i64| if x < 0 { x + len } else { x };
+    let mut indices = Vec::new();
+
+    if step > 0 {
+        let mut i = start.map_or(0, normalize).clamp(0, len);
+        let end = end.map_or(len, normalize).clamp(0, len);
+        while i < end {
+            indices.push(i as usize);
+            i += step;
+        }
+    } else if step < 0 {
+        // -1 ở đây nghĩa là "trước phần tử đầu tiên", không phải phần tử cuối
+        let mut i = start.map_or(len - 1, normalize).clamp(-1, len - 1);
+        let end = end.map_or(-1, normalize).clamp(-1, len - 1);
+        while i > end {
+            indices.push(i as usize);
+            i += step;
+        }
+    }
+    indices
+}
+
+fn resolve_path<'a>(item: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(item, |current, segment| {
+        current.get(segment).or_else(|| {
+            segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| current.get(index))
+        })
+    })
+}
+
+/// So sánh theo RFC 9535: field không tồn tại chỉ thoả `!=`, so thứ tự chỉ áp
+/// dụng cho số với số và chuỗi với chuỗi.
+fn compare(op: Comparison, actual: Option<&Value>, expected: &Value) -> bool {
+    let Some(actual) = actual else {
+        return op == Comparison::Ne;
+    };
+
+    let equal = || match (actual.as_f64(), expected.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => actual == expected,
+    };
+    let ordering = || match (actual, expected) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+
+    match op {
+        Comparison::Exists => true,
+        Comparison::Eq => equal(),
+        Comparison::Ne => !equal(),
+        Comparison::Lt => ordering() == Some(Ordering::Less),
+        Comparison::Le => matches!(ordering(), Some(Ordering::Less | Ordering::Equal)),
+        Comparison::Gt => ordering() == Some(Ordering::Greater),
+        Comparison::Ge => matches!(ordering(), Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
+
+// ==================== Functions ====================
+
+/// Số nguyên khi kết quả là số nguyên (vd: timestamp ms / 1000), ngược lại f64.
+fn number(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        Value::from(value as i64)
+    } else {
+        serde_json::Number::from_f64(value).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn to_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(text) => text.trim().replace(',', "").parse().ok(),
+        Value::Bool(flag) => Some(if *flag { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+pub(crate) fn call(function: &Function, item: &Value, out: &mut Vec<Value>) {
+    let numbers = || {
+        item.as_array()
+            .map(|arr| arr.iter().filter_map(to_f64).collect::<Vec<_>>())
+    };
+
+    let result = match function {
+        Function::Length => match item {
+            Value::Array(arr) => Value::from(arr.len()),
+            Value::Object(obj) => Value::from(obj.len()),
+            Value::String(text) => Value::from(text.chars().count()),
+            Value::Number(n) => n.as_f64().map_or(Value::Null, |n| number(n.abs())),
+            Value::Null => Value::from(0),
+            Value::Bool(_) => Value::Null,
+        },
+        Function::Keys => match item {
+            Value::Object(obj) => {
+                let mut keys = obj.keys().cloned().collect::<Vec<_>>();
+                keys.sort();
+                Value::from(keys)
+            }
+            Value::Array(arr) => Value::from((0..arr.len()).collect::<Vec<_>>()),
+            _ => Value::Null,
+        },
+        Function::ToNumber => to_f64(item).map_or(Value::Null, number),
+        Function::ToString => match item {
+            Value::String(_) => item.clone(),
+            _ => Value::String(item.to_string()),
+        },
+        Function::Split(separator) => match item.as_str() {
+            Some(text) => Value::from(text.split(separator.as_str()).collect::<Vec<_>>()),
+            None => Value::Null,
+        },
+        Function::Join(separator) => match item.as_array() {
+            Some(arr) => Value::String(
+                arr.iter()
+                    .map(|v| match v {
+                        Value::String(text) => text.clone(),
+                        Value::Null => String::new(),
+                        _ => v.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(separator),
+            ),
+            None => Value::Null,
+        },
+        Function::Sum => numbers().map_or(Value::Null, |n| number(n.iter().sum())),
+        Function::Min => numbers()
+            .and_then(|n| n.into_iter().reduce(f64::min))
+            .map_or(Value::Null, number),
+        Function::Max => numbers()
+            .and_then(|n| n.into_iter().reduce(f64::max))
+            .map_or(Value::Null, number),
+        Function::Avg => numbers()
+            .filter(|n| !n.is_empty())
+            .map_or(Value::Null, |n| {
+                number(n.iter().sum::<f64>() / n.len() as f64)
+            }),
+        Function::Map(operators) => match item {
+            Value::Array(arr) => Value::Array(arr.iter().flat_map(|v| run(operators, v)).collect()),
+            Value::Object(obj) => {
+                Value::Array(obj.values().flat_map(|v| run(operators, v)).collect())
             }
+            _ => Value::Null,
+        },
+        Function::Select { path, op, value } => {
+            if compare(*op, run(path, item).first(), value) {
+                out.push(item.clone());
+            }
+            return;
+        }
+        Function::Multiply(factor) => to_f64(item).map_or(Value::Null, |n| number(n * factor)),
+        Function::Divide(divisor) => to_f64(item).map_or(Value::Null, |n| number(n / divisor)),
+        Function::ToEpoch(offset) => match item {
+            Value::Number(_) => item.clone(),
+            Value::String(text) => {
+                parse_epoch(text, *offset as i64).map_or(Value::Null, Value::from)
+            }
+            _ => Value::Null,
+        },
+    };
+    out.push(result);
+}
+
+/// Parse `yyyy-mm-dd[(T| )hh:mm[:ss[.fff]]][Z|±hh[:mm]]` hoặc `dd/mm/yyyy[ hh:mm[:ss]]`
+/// thành epoch giây; `offset` (giây) áp dụng khi chuỗi không có timezone.
+fn parse_epoch(text: &str, offset: i64) -> Option<i64> {
+    let text = text.trim();
+    let (date, time) = match text.find(['T', ' ']) {
+        Some(i) => (&text[..i], Some(text[i + 1..].trim())),
+        None => (text, None),
+    };
+
+    let parts = date
+        .split(['-', '/'])
+        .map(|part| part.parse::<i64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let (year, month, day) = match parts[..] {
+        [year, month, day] if date.contains('-') => (year, month, day),
+        [day, month, year] if date.contains('/') => (year, month, day),
+        _ => return None,
+    };
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    let mut seconds = days_from_civil(year, month, day) * 86_400;
+    let mut offset = offset;
+
+    if let Some(time) = time {
+        let (clock, zone) = match time.find(['Z', 'z', '+', '-']) {
+            Some(i) => (&time[..i], Some(&time[i..])),
+            None => (time, None),
+        };
+        if let Some(zone) = zone {
+            offset = parse_zone(zone)?;
+        }
+
+        let clock = clock
+            .split(':')
+            .map(|part| part.split('.').next()?.parse::<i64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        let (hour, minute, second) = match clock[..] {
+            [hour, minute] => (hour, minute, 0),
+            [hour, minute, second] => (hour, minute, second),
+            _ => return None,
+        };
+        if hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+        seconds += hour * 3600 + minute * 60 + second;
+    }
+
+    Some(seconds - offset)
+}
+
+/// `Z`, `+07:00`, `+0700` hoặc `+07` → offset giây.
+fn parse_zone(zone: &str) -> Option<i64> {
+    if zone.eq_ignore_ascii_case("z") {
+        return Some(0);
+    }
+
+    let sign = match zone.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let digits = zone[1..].replace(':', "");
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i64>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i64>().ok()?,
+            digits[2..].parse::<i64>().ok()?,
+        ),
+        _ => return None,
+    };
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Số ngày từ 1970-01-01 (thuật toán `days_from_civil` của Howard Hinnant).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// ==================== Parse ====================
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Đọc nội dung trong `[...]` bắt đầu từ `start`, bỏ qua `]` nằm trong chuỗi
+/// hoặc cặp ngoặc lồng. Trả về nội dung và vị trí ngay sau `]`.
+fn read_bracket(chars: &[char], start: usize) -> Result<(String, usize), Error> {
+    let mut quote = None;
+    let mut depth = 0;
+
+    for (i, &c) in chars.iter().enumerate().skip(start) {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') if depth == 0 => {
+                return Ok((chars[start..i].iter().collect(), i + 1));
+            }
+            (None, ']') => depth -= 1,
+            _ => {}
         }
-        collection
     }
+
+    Err(invalid("Missing closing bracket ']'".to_string()))
+}
+
+fn parse_field(field: String) -> Operator {
+    if field == "*" {
+        Operator::Wildcard
+    } else if let Ok(index) = field.parse::<usize>() {
+        Operator::Access(index)
+    } else if let Ok(index) = field.parse::<i64>() {
+        Operator::Index(index)
+    } else {
+        Operator::Match(field)
+    }
+}
+
+fn parse_bracket(content: &str) -> Result<Operator, Error> {
+    if content.is_empty() {
+        Ok(Operator::Iter)
+    } else if let Some(expr) = content.strip_prefix('?') {
+        parse_filter(expr.trim())
+    } else if content.starts_with(['"', '\'']) {
+        Ok(Operator::Match(unquote(content).to_string()))
+    } else if content.contains(':') {
+        parse_slice(content)
+    } else {
+        Ok(parse_field(unquote(content).to_string()))
+    }
+}
+
+fn unquote(content: &str) -> &str {
+    content.trim_matches(|c| c == '"' || c == '\'')
+}
+
+fn parse_slice(content: &str) -> Result<Operator, Error> {
+    let parts = content.split(':').map(str::trim).collect::<Vec<_>>();
+    if parts.len() > 3 {
+        return Err(invalid(format!("Invalid slice `[{content}]`")));
+    }
+
+    let bound = |part: Option<&&str>| -> Result<Option<i64>, Error> {
+        match part {
+            None => Ok(None),
+            Some(&"") => Ok(None),
+            Some(part) => part
+                .parse::<i64>()
+                .map(Some)
+                .map_err(|_| invalid(format!("Invalid slice bound `{part}` in `[{content}]`"))),
+        }
+    };
+
+    let start = bound(parts.first())?;
+    let end = bound(parts.get(1))?;
+    let step = bound(parts.get(2))?.unwrap_or(1);
+    if step == 0 {
+        return Err(invalid(format!(
+            "Slice step cannot be zero in `[{content}]`"
+        )));
+    }
+
+    Ok(Operator::Slice { start, end, step })
+}
+
+/// Parse `?(@.a.b op literal)` (ngoặc tròn không bắt buộc). Literal là chuỗi
+/// trong nháy đơn/kép hoặc một giá trị JSON (số, `true`, `false`, `null`).
+fn parse_filter(expr: &str) -> Result<Operator, Error> {
+    let expr = match expr.strip_prefix('(') {
+        Some(inner) => inner
+            .strip_suffix(')')
+            .ok_or_else(|| invalid(format!("Missing closing ')' in filter `{expr}`")))?
+            .trim(),
+        None => expr,
+    };
+    let rest = expr
+        .strip_prefix('@')
+        .ok_or_else(|| invalid(format!("Filter `{expr}` must start with `@`")))?;
+
+    // Đường dẫn `.a.b` hoặc `['a'][0]` tính từ phần tử đang xét
+    let mut path = Vec::new();
+    let chars = rest.chars().collect::<Vec<_>>();
+    let mut i = 0;
+    loop {
+        match chars.get(i) {
+            Some('.') => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len()
+                    && !chars[i].is_whitespace()
+                    && !matches!(chars[i], '.' | '[' | '=' | '!' | '<' | '>')
+                {
+                    i += 1;
+                }
+                if i == start {
+                    return Err(invalid(format!("Empty field in filter `{expr}`")));
+                }
+                path.push(chars[start..i].iter().collect());
+            }
+            Some('[') => {
+                let (content, next) = read_bracket(&chars, i + 1)?;
+                path.push(unquote(content.trim()).to_string());
+                i = next;
+            }
+            _ => break,
+        }
+    }
+
+    let rest = chars[i..].iter().collect::<String>();
+    let rest = rest.trim();
+    if rest.is_empty() {
+        return Ok(Operator::Filter {
+            path,
+            op: Comparison::Exists,
+            value: Value::Null,
+        });
+    }
+
+    let (op, literal) = COMPARISONS
+        .into_iter()
+        .find_map(|(token, op)| rest.strip_prefix(token).map(|literal| (op, literal.trim())))
+        .ok_or_else(|| invalid(format!("Unknown comparison in filter `{expr}`")))?;
+    let value = parse_literal(literal)?;
+
+    Ok(Operator::Filter { path, op, value })
+}
+
+/// Thứ tự quan trọng: `<=`/`>=` phải được thử trước `<`/`>`.
+const COMPARISONS: [(&str, Comparison); 6] = [
+    ("==", Comparison::Eq),
+    ("!=", Comparison::Ne),
+    ("<=", Comparison::Le),
+    (">=", Comparison::Ge),
+    ("<", Comparison::Lt),
+    (">", Comparison::Gt),
+];
+
+/// Literal là chuỗi trong nháy đơn/kép hoặc một giá trị JSON.
+fn parse_literal(literal: &str) -> Result<Value, Error> {
+    if literal.len() >= 2
+        && (literal.starts_with('\'') && literal.ends_with('\'')
+            || literal.starts_with('"') && literal.ends_with('"'))
+    {
+        Ok(Value::String(literal[1..literal.len() - 1].to_string()))
+    } else {
+        serde_json::from_str(literal).map_err(|_| invalid(format!("Invalid literal `{literal}`")))
+    }
+}
+
+/// Tách `text` theo `separator` nằm ngoài chuỗi và ngoài mọi cặp ngoặc.
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut depth = 0i32;
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[' | '(') => depth += 1,
+            (None, ']' | ')') => depth -= 1,
+            (None, _) if c == separator && depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Parse `name` hoặc `name(arg)` thành `Function`.
+fn parse_call(stage: &str) -> Result<Function, Error> {
+    let (name, arg) = match stage.find('(') {
+        Some(open) => {
+            let arg = stage[open + 1..]
+                .strip_suffix(')')
+                .ok_or_else(|| invalid(format!("Missing closing ')' in `{stage}`")))?;
+            (stage[..open].trim(), Some(arg.trim()))
+        }
+        None => (stage, None),
+    };
+
+    let required = || arg.ok_or_else(|| invalid(format!("`{name}` requires an argument")));
+    let string_arg = || match parse_literal(required()?)? {
+        Value::String(text) => Ok(text),
+        other => Err(invalid(format!("`{name}` expects a string, got {other}"))),
+    };
+    let number_arg = || {
+        parse_literal(required()?)?
+            .as_f64()
+            .ok_or_else(|| invalid(format!("`{name}` expects a number")))
+    };
+
+    let function = match name {
+        "length" => Function::Length,
+        "keys" => Function::Keys,
+        "to_number" => Function::ToNumber,
+        "to_string" => Function::ToString,
+        "sum" => Function::Sum,
+        "min" => Function::Min,
+        "max" => Function::Max,
+        "avg" => Function::Avg,
+        "split" => Function::Split(string_arg()?),
+        "join" => Function::Join(string_arg()?),
+        "multiply" => Function::Multiply(number_arg()?),
+        "divide" => match number_arg()? {
+            0.0 => return Err(invalid(format!("Division by zero in `{stage}`"))),
+            divisor => Function::Divide(divisor),
+        },
+        "map" => Function::Map(JsonQuery::parse_pipeline(required()?, false)?.operators),
+        "select" => parse_condition(required()?)?,
+        "to_epoch" => match arg {
+            None | Some("") => Function::ToEpoch(0),
+            Some(_) => {
+                let zone = string_arg()?;
+                Function::ToEpoch(
+                    parse_zone(&zone)
+                        .ok_or_else(|| invalid(format!("Invalid timezone `{zone}`")))?
+                        as i32,
+                )
+            }
+        },
+        _ => return Err(invalid(format!("Unknown function `{name}`"))),
+    };
+
+    // Hàm không tham số mà vẫn truyền tham số thì coi là lỗi cấu hình
+    let takes_arg = matches!(
+        function,
+        Function::Split(_)
+            | Function::Join(_)
+            | Function::Multiply(_)
+            | Function::Divide(_)
+            | Function::Map(_)
+            | Function::Select { .. }
+            | Function::ToEpoch(_)
+    );
+    if !takes_arg && arg.is_some_and(|arg| !arg.is_empty()) {
+        return Err(invalid(format!("`{name}` takes no argument")));
+    }
+    Ok(function)
+}
+
+/// `select(...)`: `query op literal`, hoặc chỉ `query` (kiểm tra tồn tại).
+fn parse_condition(condition: &str) -> Result<Function, Error> {
+    let mut quote = None;
+    let mut depth = 0i32;
+
+    for (i, c) in condition.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[' | '(') => depth += 1,
+            (None, ']' | ')') => depth -= 1,
+            (None, '=' | '!' | '<' | '>') if depth == 0 => {
+                let rest = &condition[i..];
+                let (op, literal) = COMPARISONS
+                    .into_iter()
+                    .find_map(|(token, op)| {
+                        rest.strip_prefix(token).map(|literal| (op, literal.trim()))
+                    })
+                    .ok_or_else(|| invalid(format!("Unknown comparison in `{condition}`")))?;
+
+                return Ok(Function::Select {
+                    path: JsonQuery::parse_pipeline(condition[..i].trim(), false)?.operators,
+                    op,
+                    value: parse_literal(literal)?,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Function::Select {
+        path: JsonQuery::parse_pipeline(condition.trim(), false)?.operators,
+        op: Comparison::Exists,
+        value: Value::Null,
+    })
 }
 
 #[cfg(test)]
@@ -372,4 +1065,238 @@ mod tests {
             serde_json::to_string_pretty(&results).unwrap(),
         );
     }
+
+    #[test]
+    fn test_negative_index_and_slices() {
+        let data = json!({"c": [1, 2, 3, 4, 5, 6]});
+        let run = |path: &str| JsonQuery::parse(path).unwrap().execute(&data);
+
+        assert_eq!(run("c[-1]"), vec![json!(6)]);
+        assert_eq!(run("c.-2"), vec![json!(5)]);
+        assert!(run("c[-7]").is_empty());
+        assert_eq!(run("c[1:3]"), vec![json!(2), json!(3)]);
+        assert_eq!(run("c[-2:]"), vec![json!(5), json!(6)]);
+        assert_eq!(run("c[:2]"), vec![json!(1), json!(2)]);
+        assert_eq!(run("c[::2]"), vec![json!(1), json!(3), json!(5)]);
+        assert_eq!(run("c[::-2]"), vec![json!(6), json!(4), json!(2)]);
+        assert_eq!(run("c[4:1:-1]"), vec![json!(5), json!(4), json!(3)]);
+        assert!(run("c[10:]").is_empty());
+
+        assert!(JsonQuery::parse("c[::0]").is_err());
+        assert!(JsonQuery::parse("c[1:x]").is_err());
+    }
+
+    #[test]
+    fn test_filter_predicates() {
+        let data = json!({
+            "items": [
+                {"symbol": "FPT", "type": "stock", "price": 120.5, "meta": {"lot": 100}},
+                {"symbol": "E1VFVN30", "type": "etf", "price": 25},
+                {"symbol": "VNM", "type": "stock", "price": 70},
+                {"symbol": "BTC", "price": 60000}
+            ]
+        });
+        let symbols = |path: &str| JsonQuery::parse(path).unwrap().execute(&data);
+
+        assert_eq!(
+            symbols("items[?(@.type == 'stock')].symbol"),
+            vec![json!("FPT"), json!("VNM")]
+        );
+        assert_eq!(
+            symbols("items[?(@.type != \"stock\")].symbol"),
+            vec![json!("E1VFVN30"), json!("BTC")]
+        );
+        assert_eq!(
+            symbols("items[?(@.price >= 70)].symbol"),
+            vec![json!("FPT"), json!("VNM"), json!("BTC")]
+        );
+        assert_eq!(
+            symbols("items[?@.price<30].symbol"),
+            vec![json!("E1VFVN30")]
+        );
+        assert_eq!(
+            symbols("items[?(@.meta.lot == 100)].symbol"),
+            vec![json!("FPT")]
+        );
+        assert_eq!(symbols("items[?(@.type)].symbol").len(), 3);
+        assert_eq!(symbols("items[?(@.price > 'a')]").len(), 0);
+
+        assert!(JsonQuery::parse("items[?(@.price ~ 1)]").is_err());
+        assert!(JsonQuery::parse("items[?(price == 1)]").is_err());
+    }
+
+    #[test]
+    fn test_recursive_descent_and_wildcard() {
+        let data = json!({
+            "vn": {"hose": [{"symbol": "FPT"}, {"symbol": "VNM"}], "hnx": [{"symbol": "SHS"}]},
+            "symbol": "ROOT"
+        });
+
+        let query = JsonQuery::parse("..symbol").unwrap();
+        let mut symbols = query.execute(&data);
+        symbols.sort_by_key(|v| v.as_str().unwrap().to_string());
+        assert_eq!(
+            symbols,
+            vec![json!("FPT"), json!("ROOT"), json!("SHS"), json!("VNM")]
+        );
+
+        let query = JsonQuery::parse("vn.*[0].symbol").unwrap();
+        let mut firsts = query.pick(&data);
+        firsts.sort_by_key(|v| v.as_str().unwrap().to_string());
+        assert_eq!(firsts, vec![&json!("FPT"), &json!("SHS")]);
+
+        let query = JsonQuery::parse("vn.hose[*]").unwrap();
+        assert_eq!(query.pick(&data).len(), 2);
+        // Wildcard trên giá trị vô hướng không trả về gì
+        assert!(JsonQuery::parse("symbol.*").unwrap().pick(&data).is_empty());
+    }
+
+    #[test]
+    fn test_new_operators_roundtrip_serde() {
+        let query = JsonQuery::parse("data..items[?(@.type == 'stock')][-3::1].*").unwrap();
+        assert_eq!(
+            query.operators,
+            vec![
+                Operator::Match("data".to_string()),
+                Operator::Recurse,
+                Operator::Match("items".to_string()),
+                Operator::Filter {
+                    path: vec!["type".to_string()],
+                    op: Comparison::Eq,
+                    value: json!("stock"),
+                },
+                Operator::Slice {
+                    start: Some(-3),
+                    end: None,
+                    step: 1,
+                },
+                Operator::Wildcard,
+            ]
+        );
+
+        let stored = serde_json::to_string(&query.operators).unwrap();
+        let restored: Vec<Operator> = serde_json::from_str(&stored).unwrap();
+        assert_eq!(restored, query.operators);
+    }
+
+    #[test]
+    fn test_pipe_functions() {
+        let data = json!({
+            "items": [
+                {"symbol": "FPT", "type": "stock", "price": "120,500.5", "tags": "vn30,it"},
+                {"symbol": "E1VFVN30", "type": "etf", "price": 25},
+                {"symbol": "VNM", "type": "stock", "price": 70}
+            ],
+            "meta": {"b": 1, "a": 2}
+        });
+        let run = |query: &str| JsonQuery::parse(query).unwrap().execute(&data);
+
+        assert_eq!(run("items | length"), vec![json!(3)]);
+        assert_eq!(run("meta | keys"), vec![json!(["a", "b"])]);
+        assert_eq!(run(". | keys | length"), vec![json!(2)]);
+        assert_eq!(
+            run("items[].price | to_number"),
+            vec![json!(120500.5), json!(25), json!(70)]
+        );
+        assert_eq!(
+            run("items[0].price | to_number | multiply(2)"),
+            vec![json!(241001)]
+        );
+        assert_eq!(run("items[1].price | to_string"), vec![json!("25")]);
+        assert_eq!(
+            run("items[0].tags | split(',')"),
+            vec![json!(["vn30", "it"])]
+        );
+        assert_eq!(
+            run("items | map(.symbol) | join(\"-\")"),
+            vec![json!("FPT-E1VFVN30-VNM")]
+        );
+        assert_eq!(
+            run("items[] | select(.type == 'stock') | .symbol"),
+            vec![json!("FPT"), json!("VNM")]
+        );
+        assert_eq!(
+            run("items | map(.price | to_number) | sum"),
+            vec![json!(120595.5)]
+        );
+        assert_eq!(run("items | map(.price) | min"), vec![json!(25)]);
+        assert_eq!(run("items | map(.price) | max"), vec![json!(120500.5)]);
+        assert_eq!(
+            run("items | map(select(.type == 'stock') | .price | to_number) | avg"),
+            vec![json!(60285.25)]
+        );
+        assert_eq!(run("meta | map(.) | avg"), vec![json!(1.5)]);
+
+        // `pick` không tạo được giá trị mới nên function không trả về gì
+        let query = JsonQuery::parse("items[1].price | multiply(2)").unwrap();
+        assert!(query.pick(&data).is_empty());
+    }
+
+    #[test]
+    fn test_binance_millisecond_timestamps() {
+        let data = json!([[1700000000000i64, "100.5"], [1700000060000i64, "100.8"]]);
+        let query = JsonQuery::parse("[].0 | divide(1000)").unwrap();
+        assert_eq!(
+            query.execute(&data),
+            vec![json!(1700000000), json!(1700000060)]
+        );
+    }
+
+    #[test]
+    fn test_to_epoch() {
+        let data = json!([
+            "2024-01-02",
+            "2024-01-02T03:04:05Z",
+            "2024-01-02 10:04:05.123+07:00",
+            "02/01/2024 10:04",
+            "2024-02-30",
+            1700000000
+        ]);
+
+        let utc = JsonQuery::parse("[] | to_epoch").unwrap().execute(&data);
+        assert_eq!(
+            utc,
+            vec![
+                json!(1704153600),
+                json!(1704164645),
+                json!(1704164645),
+                json!(1704189840),
+                Value::Null,
+                json!(1700000000),
+            ]
+        );
+
+        // Chuỗi không kèm timezone dùng offset cấu hình
+        let local = JsonQuery::parse("[3] | to_epoch('+07:00')")
+            .unwrap()
+            .execute(&data);
+        assert_eq!(local, vec![json!(1704189840 - 7 * 3600)]);
+    }
+
+    #[test]
+    fn test_invalid_functions() {
+        assert!(JsonQuery::parse("a | unknown").is_err());
+        assert!(JsonQuery::parse("a | divide(0)").is_err());
+        assert!(JsonQuery::parse("a | split").is_err());
+        assert!(JsonQuery::parse("a | split(1)").is_err());
+        assert!(JsonQuery::parse("a | length(1)").is_err());
+        assert!(JsonQuery::parse("a | map(.b").is_err());
+        assert!(JsonQuery::parse("a |").is_err());
+        assert!(JsonQuery::parse("a | to_epoch('GMT')").is_err());
+
+        // Dấu `|` trong chuỗi hoặc ngoặc không tách stage
+        let data = json!({"a|b": "x|y"});
+        let query = JsonQuery::parse("[\"a|b\"] | split('|')").unwrap();
+        assert_eq!(query.execute(&data), vec![json!(["x", "y"])]);
+
+        // Operator lồng nhau vẫn serialize/deserialize và sinh được schema
+        let stored = serde_json::to_string(&query.operators).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<Operator>>(&stored).unwrap(),
+            query.operators
+        );
+        let mut schemas = Vec::new();
+        <Operator as utoipa::ToSchema>::schemas(&mut schemas);
+        assert!(schemas.iter().any(|(name, _)| name == "Function"));
+    }
 }
@@ -1,9 +1,11 @@
 mod binarysearch;
-//mod heap;
+mod heap;
 
 mod ahocorasick;
 mod jq;
 mod lru;
+mod normalize;
+mod query_plan;
 mod radixtree;
 mod search_index;
 mod snowflake_id;
@@ -12,8 +14,11 @@ pub mod storage;
 
 pub use ahocorasick::*;
 pub use binarysearch::*;
+pub use heap::*;
 pub use jq::*;
 pub use lru::*;
+pub use normalize::*;
+pub use query_plan::*;
 pub use radixtree::*;
 pub use search_index::SearchIndex;
 pub use snowflake_id::*;
@@ -1,20 +1,48 @@
 use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
 use parking_lot::Mutex;
+use serde::Serialize;
+use std::any::Any;
 use std::collections::hash_map::DefaultHasher;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
 
 const NULL: usize = usize::MAX;
+const NO_DEADLINE: u64 = 0;
 
 // --- CẤU TRÚC DỮ LIỆU ---
 
 struct Node<K, V> {
-    key: Option<K>,
-    value: Option<V>,
+    /// Key/value của slot. Chỉ được ghi khi đang giữ lock của shard; `get` đọc
+    /// (clone) qua lock riêng của node nên không phải chờ lock shard.
+    entry: Mutex<Option<(K, V)>>,
     next: AtomicUsize,
     prev: AtomicUsize,
+    /// Thời điểm hết hạn (nanos tính từ `epoch` của cache), `NO_DEADLINE` = không hết hạn.
+    deadline: AtomicU64,
+}
+
+/// Hook gọi khi một entry rời khỏi cache.
+pub type RemovingHook<K, V> = Arc<dyn Fn(K, V, RemovalReason) + Send + Sync>;
+
+/// Kết quả một lượt load, chia sẻ cho các caller đang chờ cùng key. Lỗi được
+/// xoá kiểu để `LruCache` không phải generic theo kiểu lỗi của từng loader.
+type Flight<V> = Result<V, Arc<dyn Any + Send + Sync>>;
+
+/// Lý do một entry rời khỏi cache, truyền cho `on_removing`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovalReason {
+    /// Shard đầy, entry ít dùng nhất bị đẩy ra.
+    Evicted,
+    /// Hết TTL — phát hiện khi `get`, khi slot bị tái sử dụng hoặc khi sweep.
+    Expired,
+    /// Bị xoá chủ động qua `remove`.
+    Removed,
 }
 
 struct HeadTail {
@@ -27,6 +55,122 @@ struct HeadTail {
 #[repr(align(64))]
 struct AlignedShard {
     mutex: Mutex<HeadTail>,
+    stats: ShardStats,
+}
+
+/// Bộ đếm của một shard. Nằm cùng cache line với mutex của shard nên các
+/// luồng ghi vào shard khác nhau không tranh chấp counter của nhau.
+#[derive(Default)]
+struct ShardStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    inserts: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+    promotion_skips: AtomicU64,
+    len: AtomicU64,
+}
+
+impl ShardStats {
+    #[inline]
+    fn incr(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            inserts: self.inserts.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            expirations: self.expirations.load(Ordering::Relaxed),
+            promotion_skips: self.promotion_skips.load(Ordering::Relaxed),
+            len: self.len.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Ảnh chụp bộ đếm của cache (hoặc một shard). Các counter được đọc riêng lẻ
+/// bằng `Relaxed` nên có thể lệch nhau vài đơn vị khi cache đang chạy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Số key mới được ghi (không tính ghi đè key đã có).
+    pub inserts: u64,
+    /// Số entry bị đẩy ra vì shard đầy.
+    pub evictions: u64,
+    /// Số entry bị xoá vì hết TTL.
+    pub expirations: u64,
+    /// Số lần bỏ qua move-to-front vì `try_lock` thất bại.
+    pub promotion_skips: u64,
+    /// Số entry đang nằm trong cache.
+    pub len: u64,
+}
+
+impl CacheStats {
+    /// Tỉ lệ hit trên tổng số lượt đọc, 0 nếu chưa có lượt đọc nào.
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+impl std::ops::Add for CacheStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            hits: self.hits + other.hits,
+            misses: self.misses + other.misses,
+            inserts: self.inserts + other.inserts,
+            evictions: self.evictions + other.evictions,
+            expirations: self.expirations + other.expirations,
+            promotion_skips: self.promotion_skips + other.promotion_skips,
+            len: self.len + other.len,
+        }
+    }
+}
+
+impl std::iter::Sum for CacheStats {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |acc, stats| acc + stats)
+    }
+}
+
+/// Lượt load đang chạy của leader. Gỡ key khỏi danh sách đang load khi kết
+/// thúc — kể cả khi future của leader bị drop giữa chừng, để caller sau không
+/// chờ mãi.
+struct LoadingGuard<'a, K: Hash + Eq, V> {
+    loading: &'a DashMap<K, watch::Receiver<Option<Flight<V>>>>,
+    key: &'a K,
+    sender: watch::Sender<Option<Flight<V>>>,
+}
+
+impl<K: Hash + Eq, V> LoadingGuard<'_, K, V> {
+    /// Gỡ key trước rồi mới báo kết quả: caller thức dậy mà không dùng được kết
+    /// quả sẽ tự đăng ký lượt load mới thay vì quay vòng trên channel cũ.
+    fn finish(self, flight: Flight<V>) {
+        self.release();
+        self.sender.send_replace(Some(flight));
+    }
+
+    fn release(&self) {
+        // Chỉ gỡ đúng channel của mình, không đụng lượt load mới của leader khác
+        self.loading.remove_if(self.key, |_, receiver| {
+            receiver.same_channel(&self.sender.subscribe())
+        });
+    }
+}
+
+impl<K: Hash + Eq, V> Drop for LoadingGuard<'_, K, V> {
+    fn drop(&mut self) {
+        self.release();
+    }
 }
 
 pub struct LruCache<K, V, const S: usize> {
@@ -34,7 +178,10 @@ pub struct LruCache<K, V, const S: usize> {
     caching: Box<[Node<K, V>]>,
     shards: [AlignedShard; S],
     shard_mask: usize,
-    pub on_removing: Option<Arc<dyn Fn(K, V) + Send + Sync>>,
+    epoch: Instant,
+    default_ttl: Option<Duration>,
+    loading: DashMap<K, watch::Receiver<Option<Flight<V>>>>,
+    pub on_removing: Option<RemovingHook<K, V>>,
     pub on_updating: Option<Arc<dyn Fn(K, V) + Send + Sync>>,
 }
 
@@ -48,6 +195,7 @@ where
             .field("mapping", &self.mapping)
             .field("caching_len", &self.caching.len())
             .field("shard_mask", &self.shard_mask)
+            .field("default_ttl", &self.default_ttl)
             .field("on_removing", &self.on_removing.as_ref().map(|_| "Closure"))
             .field("on_updating", &self.on_updating.as_ref().map(|_| "Closure"))
             .finish()
@@ -77,14 +225,14 @@ where
             for i in 0..capacity_per_shard {
                 let current = offset + i;
                 caching_vec.push(Node {
-                    key: None,
-                    value: None,
+                    entry: Mutex::new(None),
                     next: AtomicUsize::new(if i + 1 < capacity_per_shard {
                         current + 1
                     } else {
                         NULL
                     }),
                     prev: AtomicUsize::new(if i > 0 { current - 1 } else { NULL }),
+                    deadline: AtomicU64::new(NO_DEADLINE),
                 });
             }
         }
@@ -101,6 +249,7 @@ where
                         NULL
                     },
                 }),
+                stats: ShardStats::default(),
             }
         });
 
@@ -109,11 +258,20 @@ where
             caching: caching_vec.into_boxed_slice(),
             shards,
             shard_mask: S - 1,
+            epoch: Instant::now(),
+            default_ttl: None,
+            loading: DashMap::new(),
             on_removing: None,
             on_updating: None,
         }
     }
 
+    /// TTL mặc định cho mọi entry ghi qua `put`.
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = Some(ttl);
+        self
+    }
+
     #[inline]
     pub fn get_shard_idx(&self, key: &K) -> usize {
         let mut s = DefaultHasher::new();
@@ -122,23 +280,238 @@ where
     }
 
     pub fn get(&self, key: &K) -> Option<V> {
+        self.get_in_shard(key, self.get_shard_idx(key))
+    }
+
+    /// `get` với shard đã biết, tránh hash key thêm lần nữa.
+    fn get_in_shard(&self, key: &K, shard_idx: usize) -> Option<V> {
+        let shard = &self.shards[shard_idx];
+        let value = self.lookup(key, shard_idx);
+
+        if value.is_some() {
+            ShardStats::incr(&shard.stats.hits);
+        } else {
+            ShardStats::incr(&shard.stats.misses);
+        }
+        value
+    }
+
+    /// Ảnh chụp bộ đếm gộp của mọi shard.
+    pub fn stats(&self) -> CacheStats {
+        self.shards.iter().map(|shard| shard.stats.snapshot()).sum()
+    }
+
+    /// Ảnh chụp bộ đếm của từng shard, theo thứ tự shard.
+    pub fn shard_stats(&self) -> Vec<CacheStats> {
+        self.shards
+            .iter()
+            .map(|shard| shard.stats.snapshot())
+            .collect()
+    }
+
+    /// Số entry đang nằm trong cache.
+    pub fn len(&self) -> usize {
+        self.mapping.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mapping.is_empty()
+    }
+
+    /// Tổng số slot của cache (đã làm tròn theo số shard).
+    pub fn capacity(&self) -> usize {
+        self.caching.len()
+    }
+
+    /// `get` không ghi nhận hit/miss — dùng nội bộ khi đọc lại cùng một key.
+    /// `shard_idx` là `get_shard_idx(key)` caller đã tính sẵn.
+    fn lookup(&self, key: &K, shard_idx: usize) -> Option<V> {
         let index = *self.mapping.get(key)?;
+        let shard = &self.shards[shard_idx];
 
-        // Đọc giá trị an toàn (Node này chắc chắn tồn tại vì mapping đang giữ nó)
-        let val = self.caching[index].value.as_ref()?.clone();
+        // Lazy expiry: entry hết hạn bị xoá ngay tại lần đọc đầu tiên
+        if self.is_expired(index) {
+            let mut ht = shard.mutex.lock();
+            self.remove_inside_lock(&mut ht, shard_idx, key, index, RemovalReason::Expired);
+            return None;
+        }
+
+        // Clone dưới lock của node: giữa lúc đọc mapping và lúc này slot có thể
+        // đã bị xoá hoặc ghi lại cho key khác
+        let val = match &*self.caching[index].entry.lock() {
+            Some((stored, value)) if stored == key => value.clone(),
+            _ => return None,
+        };
 
         // Optimistic LRU Update: Dùng try_lock để không làm chậm luồng Read
-        let shard_idx = self.get_shard_idx(key);
-        if let Some(mut ht) = self.shards[shard_idx].mutex.try_lock() {
+        if let Some(mut ht) = shard.mutex.try_lock() {
             self.move_to_front_inside_lock(&mut ht, index);
+        } else {
+            ShardStats::incr(&shard.stats.promotion_skips);
         }
 
         Some(val)
     }
 
+    /// Ghi entry với TTL mặc định (nếu có).
     pub fn put(&self, key: K, value: V) {
+        self.insert(key, value, self.default_ttl);
+    }
+
+    /// Ghi entry với TTL riêng, bỏ qua TTL mặc định.
+    pub fn put_with_ttl(&self, key: K, value: V, ttl: Duration) {
+        self.insert(key, value, Some(ttl));
+    }
+
+    /// Xoá `key` khỏi cache, trả về giá trị cũ.
+    pub fn remove(&self, key: &K) -> Option<V> {
+        let index = *self.mapping.get(key)?;
+        let shard_idx = self.get_shard_idx(key);
+        let mut ht = self.shards[shard_idx].mutex.lock();
+        self.remove_inside_lock(&mut ht, shard_idx, key, index, RemovalReason::Removed)
+    }
+
+    /// Quét toàn bộ cache, xoá mọi entry đã hết hạn. Trả về số entry bị xoá.
+    pub fn purge_expired(&self) -> usize {
+        let mut purged = 0;
+
+        for (shard_idx, shard) in self.shards.iter().enumerate() {
+            let mut ht = shard.mutex.lock();
+
+            let mut expired = Vec::new();
+            let mut index = ht.first;
+            while index != NULL {
+                let node = &self.caching[index];
+                if let Some((key, _)) = &*node.entry.lock()
+                    && self.is_expired(index)
+                {
+                    expired.push((key.clone(), index));
+                }
+                index = node.next.load(Ordering::Acquire);
+            }
+
+            for (key, index) in expired {
+                if self
+                    .remove_inside_lock(&mut ht, shard_idx, &key, index, RemovalReason::Expired)
+                    .is_some()
+                {
+                    purged += 1;
+                }
+            }
+        }
+
+        purged
+    }
+
+    /// Chạy `purge_expired` định kỳ trên một thread riêng. Thread chỉ giữ `Weak`
+    /// nên tự dừng sau khi cache bị drop.
+    pub fn spawn_sweeper(self: &Arc<Self>, interval: Duration) -> JoinHandle<()>
+    where
+        K: 'static,
+        V: 'static,
+    {
+        let cache = Arc::downgrade(self);
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(interval);
+                match cache.upgrade() {
+                    Some(cache) => {
+                        cache.purge_expired();
+                    }
+                    None => break,
+                }
+            }
+        })
+    }
+
+    /// Đọc `key`, nếu miss thì gọi `loader` rồi ghi kết quả vào cache.
+    ///
+    /// Các lượt miss đồng thời trên cùng key chỉ chạy **một** loader, các caller
+    /// còn lại chờ và nhận chung kết quả. Giá trị `Ok` luôn được cache (kể cả
+    /// `None` khi `V = Option<T>` — negative caching); `Err` chỉ trả về cho các
+    /// caller đang chờ, không ghi vào cache nên lượt sau sẽ load lại.
+    ///
+    /// @NOTE: nếu loader bị huỷ giữa chừng (future bị drop) hoặc caller đang chờ
+    /// dùng kiểu lỗi khác, caller đó tự load lại thay vì nhận kết quả chung.
+    pub async fn get_or_load<F, Fut, E>(&self, key: K, loader: F) -> Result<V, Arc<E>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+        E: Send + Sync + 'static,
+    {
+        let mut loader = Some(loader);
         let shard_idx = self.get_shard_idx(&key);
 
+        loop {
+            if let Some(value) = self.get_in_shard(&key, shard_idx) {
+                return Ok(value);
+            }
+
+            let leader = match self.loading.entry(key.clone()) {
+                Entry::Occupied(entry) => Err(entry.get().clone()),
+                Entry::Vacant(entry) => {
+                    let (sender, receiver) = watch::channel(None);
+                    entry.insert(receiver);
+                    Ok(sender)
+                }
+            };
+
+            match leader {
+                Ok(sender) => {
+                    let guard = LoadingGuard {
+                        loading: &self.loading,
+                        key: &key,
+                        sender,
+                    };
+
+                    // Leader trước có thể vừa ghi xong giữa lần `get` và lúc đăng ký
+                    if let Some(value) = self.lookup(&key, shard_idx) {
+                        guard.finish(Ok(value.clone()));
+                        return Ok(value);
+                    }
+
+                    let Some(loader) = loader.take() else {
+                        unreachable!("caller chỉ làm leader một lần");
+                    };
+
+                    return match loader().await {
+                        Ok(value) => {
+                            self.put(key.clone(), value.clone());
+                            guard.finish(Ok(value.clone()));
+                            Ok(value)
+                        }
+                        Err(error) => {
+                            let error = Arc::new(error);
+                            guard.finish(Err(error.clone()));
+                            Err(error)
+                        }
+                    };
+                }
+                Err(mut receiver) => {
+                    let flight = match receiver.wait_for(Option::is_some).await {
+                        Ok(flight) => flight.clone(),
+                        // Leader bị huỷ trước khi có kết quả
+                        Err(_) => None,
+                    };
+
+                    match flight {
+                        Some(Ok(value)) => return Ok(value),
+                        Some(Err(error)) => {
+                            if let Ok(error) = error.downcast::<E>() {
+                                return Err(error);
+                            }
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+    }
+
+    fn insert(&self, key: K, value: V, ttl: Option<Duration>) {
+        let shard_idx = self.get_shard_idx(&key);
+        let deadline = self.deadline_after(ttl);
+
         // Case 1: Key đã tồn tại (Update)
         if let Some(entry) = self.mapping.get_mut(&key) {
             let index = *entry.value();
@@ -146,15 +519,21 @@ where
                 cb(key.clone(), value.clone());
             }
 
-            unsafe {
-                let node_ptr = &self.caching[index] as *const Node<K, V> as *mut Node<K, V>;
-                (*node_ptr).value = Some(value);
+            // `entry` giữ lock của key trong mapping: slot chưa thể bị xoá hay
+            // ghi lại cho key khác cho tới khi `entry` được drop
+            if let Some((_, stored)) = self.caching[index].entry.lock().as_mut() {
+                *stored = value;
             }
+            self.caching[index]
+                .deadline
+                .store(deadline, Ordering::Release);
             drop(entry);
 
             // Cập nhật thứ tự (Có thể dùng try_lock hoặc lock tùy độ ưu tiên)
             if let Some(mut ht) = self.shards[shard_idx].mutex.try_lock() {
                 self.move_to_front_inside_lock(&mut ht, index);
+            } else {
+                ShardStats::incr(&self.shards[shard_idx].stats.promotion_skips);
             }
             return;
         }
@@ -167,26 +546,116 @@ where
         }
 
         let node = &self.caching[last_idx];
-
-        // Đuổi dữ liệu cũ nếu có
-        if let Some(ref old_key) = node.key {
-            self.mapping.remove(old_key);
-            if let Some(cb) = &self.on_removing {
-                cb(old_key.clone(), node.value.as_ref().unwrap().clone());
+        let stats = &self.shards[shard_idx].stats;
+        ShardStats::incr(&stats.inserts);
+
+        // Đuổi dữ liệu cũ nếu có. Bỏ key khỏi mapping trước rồi mới lấy value ra:
+        // `put` đang cập nhật key cũ (giữ lock mapping) ghi xong trước, và không
+        // giữ lock node khi động vào mapping để không đảo thứ tự lock với `insert`.
+        let old_key = node.entry.lock().as_ref().map(|(k, _)| k.clone());
+        if let Some(old_key) = old_key {
+            self.mapping.remove(&old_key);
+            let old_value = node.entry.lock().take().map(|(_, v)| v);
+
+            let reason = if self.is_expired(last_idx) {
+                ShardStats::incr(&stats.expirations);
+                RemovalReason::Expired
+            } else {
+                ShardStats::incr(&stats.evictions);
+                RemovalReason::Evicted
+            };
+            if let (Some(cb), Some(old_value)) = (&self.on_removing, old_value) {
+                cb(old_key, old_value, reason);
             }
+        } else {
+            ShardStats::incr(&stats.len);
         }
 
         // Ghi dữ liệu mới vào Node cuối của Shard
-        unsafe {
-            let node_ptr = node as *const Node<K, V> as *mut Node<K, V>;
-            (*node_ptr).key = Some(key.clone());
-            (*node_ptr).value = Some(value);
-        }
+        *node.entry.lock() = Some((key.clone(), value));
+        node.deadline.store(deadline, Ordering::Release);
 
         self.mapping.insert(key, last_idx);
         self.move_to_front_inside_lock(&mut ht, last_idx);
     }
 
+    fn deadline_after(&self, ttl: Option<Duration>) -> u64 {
+        match ttl {
+            // max(1) để TTL = 0 vẫn khác NO_DEADLINE
+            Some(ttl) => ((self.epoch.elapsed() + ttl).as_nanos() as u64).max(1),
+            None => NO_DEADLINE,
+        }
+    }
+
+    fn is_expired(&self, index: usize) -> bool {
+        let deadline = self.caching[index].deadline.load(Ordering::Acquire);
+        deadline != NO_DEADLINE && self.epoch.elapsed().as_nanos() as u64 >= deadline
+    }
+
+    /// Xoá entry tại `index` (nếu mapping vẫn trỏ `key` vào đó) và đưa slot về
+    /// cuối shard để được tái sử dụng trước. `shard_idx` là shard đang giữ `ht`.
+    fn remove_inside_lock(
+        &self,
+        ht: &mut HeadTail,
+        shard_idx: usize,
+        key: &K,
+        index: usize,
+        reason: RemovalReason,
+    ) -> Option<V> {
+        // Giữa lúc đọc mapping và lúc lấy lock, slot có thể đã được ghi lại
+        // (key khác chiếm slot, hoặc `put` vừa gia hạn TTL)
+        self.mapping.remove_if(key, |_, &current| {
+            current == index && (reason != RemovalReason::Expired || self.is_expired(index))
+        })?;
+
+        let node = &self.caching[index];
+        let removed = node.entry.lock().take();
+        node.deadline.store(NO_DEADLINE, Ordering::Release);
+        self.move_to_back_inside_lock(ht, index);
+
+        let stats = &self.shards[shard_idx].stats;
+        stats.len.fetch_sub(1, Ordering::Relaxed);
+        if reason == RemovalReason::Expired {
+            ShardStats::incr(&stats.expirations);
+        }
+
+        let (old_key, old_value) = removed?;
+        if let Some(cb) = &self.on_removing {
+            cb(old_key, old_value.clone(), reason);
+        }
+        Some(old_value)
+    }
+
+    fn move_to_back_inside_lock(&self, ht: &mut HeadTail, index: usize) {
+        if ht.last == index || ht.last == NULL {
+            return;
+        }
+
+        let node = &self.caching[index];
+        let p = node.prev.load(Ordering::Acquire);
+        let n = node.next.load(Ordering::Acquire);
+
+        // Cắt node ra khỏi vị trí hiện tại
+        if p != NULL {
+            self.caching[p].next.store(n, Ordering::Release);
+        }
+        if n != NULL {
+            self.caching[n].prev.store(p, Ordering::Release);
+        }
+
+        if index == ht.first {
+            ht.first = n;
+        }
+
+        // Đưa xuống cuối danh sách của Shard
+        let old_last = ht.last;
+        node.prev.store(old_last, Ordering::Release);
+        node.next.store(NULL, Ordering::Release);
+        self.caching[old_last].next.store(index, Ordering::Release);
+
+        ht.last = index;
+    }
+
     fn move_to_front_inside_lock(&self, ht: &mut HeadTail, index: usize) {
         if ht.first == index || ht.first == NULL {
             return;
@@ -320,8 +789,10 @@ mod tests {
             let index = *entry.value();
 
             let node = &cache.caching[index];
-            let stored_key = node.key.expect("Node trong mapping phải có key");
-            let stored_val = node.value.expect("Node trong mapping phải có value");
+            let (stored_key, stored_val) = node
+                .entry
+                .lock()
+                .expect("Node trong mapping phải có key/value");
 
             assert_eq!(
                 key, stored_key,
@@ -450,15 +921,272 @@ mod tests {
         let mru_node = &cache.caching[mru_index];
         let lru_node = &cache.caching[lru_index];
 
-        assert_eq!(mru_node.key, Some(k3));
+        assert_eq!(mru_node.entry.lock().map(|(k, _)| k), Some(k3));
         assert_eq!(mru_node.next.load(Ordering::Relaxed), lru_index);
         assert_eq!(mru_node.prev.load(Ordering::Relaxed), NULL);
 
-        assert_eq!(lru_node.key, Some(k2));
+        assert_eq!(lru_node.entry.lock().map(|(k, _)| k), Some(k2));
         assert_eq!(lru_node.next.load(Ordering::Relaxed), NULL);
         assert_eq!(lru_node.prev.load(Ordering::Relaxed), mru_index);
     }
 
+    #[test]
+    fn test_default_ttl_expires_on_get() {
+        let mut cache =
+            LruCache::<u32, u32, 4>::new(16).with_default_ttl(Duration::from_millis(20));
+        let removed = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let sink = removed.clone();
+        cache.on_removing = Some(Arc::new(move |k, v, reason| {
+            sink.lock().push((k, v, reason))
+        }));
+
+        cache.put(1, 10);
+        assert_eq!(cache.get(&1), Some(10));
+
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(*removed.lock(), vec![(1, 10, RemovalReason::Expired)]);
+
+        // Slot được giải phóng, ghi lại key vẫn hoạt động bình thường
+        cache.put(1, 11);
+        assert_eq!(cache.get(&1), Some(11));
+    }
+
+    #[test]
+    fn test_per_entry_ttl_and_refresh_on_update() {
+        let cache = LruCache::<u32, u32, 4>::new(16);
+
+        cache.put(1, 10);
+        cache.put_with_ttl(2, 20, Duration::from_millis(20));
+        thread::sleep(Duration::from_millis(30));
+
+        // Entry không TTL sống mãi, entry có TTL riêng hết hạn
+        assert_eq!(cache.get(&1), Some(10));
+        assert_eq!(cache.get(&2), None);
+
+        // Ghi đè làm mới deadline
+        cache.put_with_ttl(3, 30, Duration::from_millis(40));
+        thread::sleep(Duration::from_millis(25));
+        cache.put_with_ttl(3, 31, Duration::from_millis(40));
+        thread::sleep(Duration::from_millis(25));
+        assert_eq!(cache.get(&3), Some(31));
+    }
+
+    #[test]
+    fn test_remove_and_eviction_reasons() {
+        let mut cache = LruCache::<u32, u32, 1>::new(2);
+        let removed = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let sink = removed.clone();
+        cache.on_removing = Some(Arc::new(move |k, _, reason| sink.lock().push((k, reason))));
+
+        cache.put(1, 10);
+        cache.put(2, 20);
+        assert_eq!(cache.remove(&1), Some(10));
+        assert_eq!(cache.remove(&1), None);
+        assert_eq!(cache.get(&1), None);
+
+        // Slot vừa xoá được dùng trước, key 2 chưa bị đuổi
+        cache.put(3, 30);
+        assert_eq!(cache.get(&2), Some(20));
+
+        // Entry hết hạn nằm ở đuôi bị đuổi với lý do Expired
+        cache.put_with_ttl(4, 40, Duration::ZERO);
+        assert_eq!(cache.get(&2), Some(20));
+        cache.put(5, 50);
+
+        assert_eq!(
+            *removed.lock(),
+            vec![
+                (1, RemovalReason::Removed),
+                (3, RemovalReason::Evicted),
+                (4, RemovalReason::Expired),
+            ]
+        );
+        assert_eq!(cache.get(&2), Some(20));
+        assert_eq!(cache.get(&5), Some(50));
+    }
+
+    #[test]
+    fn test_purge_expired_and_sweeper() {
+        let cache = LruCache::<u32, u32, 4>::new(64);
+        for i in 0..10 {
+            cache.put_with_ttl(i, i, Duration::from_millis(10));
+        }
+        for i in 10..20 {
+            cache.put(i, i);
+        }
+
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(cache.purge_expired(), 10);
+        assert_eq!(cache.purge_expired(), 0);
+        assert_eq!(cache.mapping.len(), 10);
+
+        let mut cache =
+            LruCache::<u32, u32, 4>::new(64).with_default_ttl(Duration::from_millis(10));
+        let expired = Arc::new(AtomicUsize::new(0));
+        let counter = expired.clone();
+        cache.on_removing = Some(Arc::new(move |_, _, reason| {
+            assert_eq!(reason, RemovalReason::Expired);
+            counter.fetch_add(1, Ordering::Relaxed);
+        }));
+        let cache = Arc::new(cache);
+        for i in 0..10 {
+            cache.put(i, i);
+        }
+
+        let sweeper = cache.spawn_sweeper(Duration::from_millis(5));
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(expired.load(Ordering::Relaxed), 10);
+        assert_eq!(cache.mapping.len(), 0);
+
+        // Drop cache thì thread sweeper tự thoát
+        drop(cache);
+        sweeper.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_or_load_coalesces_concurrent_misses() {
+        let cache = Arc::new(LruCache::<u32, u32, 4>::new(16));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let gate = Arc::new(tokio::sync::Semaphore::new(0));
+
+        let mut tasks = Vec::new();
+        for _ in 0..10 {
+            let (cache, calls, gate) = (cache.clone(), calls.clone(), gate.clone());
+            tasks.push(tokio::spawn(async move {
+                cache
+                    .get_or_load(1, || async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        let _permit = gate.acquire().await.unwrap();
+                        Ok::<_, String>(42)
+                    })
+                    .await
+            }));
+        }
+
+        // Cho mọi task chạy tới điểm chờ rồi mới mở cổng cho loader
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        gate.add_permits(1);
+
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), Ok(42));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.get(&1), Some(42));
+        assert!(cache.loading.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_or_load_errors_do_not_poison() {
+        let cache = Arc::new(LruCache::<u32, Option<u32>, 4>::new(16));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let gate = Arc::new(tokio::sync::Semaphore::new(0));
+
+        let mut tasks = Vec::new();
+        for _ in 0..5 {
+            let (cache, calls, gate) = (cache.clone(), calls.clone(), gate.clone());
+            tasks.push(tokio::spawn(async move {
+                cache
+                    .get_or_load(1, || async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        let _permit = gate.acquire().await.unwrap();
+                        Err::<Option<u32>, _>("db down".to_string())
+                    })
+                    .await
+            }));
+        }
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        gate.add_permits(1);
+
+        // Mọi caller đang chờ nhận chung một lỗi, cache không bị ghi
+        for task in tasks {
+            assert_eq!(*task.await.unwrap().unwrap_err(), "db down");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.get(&1), None);
+
+        // Negative caching: `Ok(None)` được cache, lượt sau không gọi loader
+        let value = cache
+            .get_or_load(1, || async { Ok::<_, String>(None) })
+            .await;
+        assert_eq!(value, Ok(None));
+        let value = cache
+            .get_or_load(1, || async {
+                Err::<Option<u32>, _>("unreachable".to_string())
+            })
+            .await;
+        assert_eq!(value, Ok(None));
+    }
+
+    #[tokio::test]
+    async fn test_get_or_load_recovers_from_cancelled_leader() {
+        let cache = Arc::new(LruCache::<u32, u32, 4>::new(16));
+        let gate = Arc::new(tokio::sync::Semaphore::new(0));
+
+        let leader = {
+            let (cache, gate) = (cache.clone(), gate.clone());
+            tokio::spawn(async move {
+                cache
+                    .get_or_load(1, || async move {
+                        let _permit = gate.acquire().await.unwrap();
+                        Ok::<_, String>(1)
+                    })
+                    .await
+            })
+        };
+        tokio::task::yield_now().await;
+
+        let waiter = {
+            let cache = cache.clone();
+            tokio::spawn(async move { cache.get_or_load(1, || async { Ok::<_, String>(2) }).await })
+        };
+        tokio::task::yield_now().await;
+
+        // Leader bị huỷ: waiter tự load lại bằng loader của mình
+        leader.abort();
+        assert_eq!(waiter.await.unwrap(), Ok(2));
+        assert_eq!(cache.get(&1), Some(2));
+        assert!(cache.loading.is_empty());
+    }
+
+    #[test]
+    fn test_stats_counters() {
+        let cache = LruCache::<u32, u32, 1>::new(2);
+
+        cache.put(1, 10);
+        cache.put(2, 20);
+        cache.put(2, 21);
+        assert_eq!(cache.get(&1), Some(10));
+        assert_eq!(cache.get(&3), None);
+
+        // Shard đầy: key 2 ở đuôi bị đuổi
+        cache.put(3, 30);
+        cache.put_with_ttl(4, 40, Duration::ZERO);
+        assert_eq!(cache.get(&4), None);
+        cache.remove(&3);
+
+        let stats = cache.stats();
+        assert_eq!(
+            stats,
+            CacheStats {
+                hits: 1,
+                misses: 2,
+                inserts: 4,
+                evictions: 2,
+                expirations: 1,
+                promotion_skips: 0,
+                len: 0,
+            }
+        );
+        assert_eq!(stats.len as usize, cache.len());
+        assert!((stats.hit_ratio() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(cache.shard_stats(), vec![stats]);
+    }
+
     #[test]
     fn test_lru_deadlock() {
         // Khởi tạo cache với capacity 10
@@ -613,7 +1341,7 @@ mod tests {
         // Setup cache với callback đếm số lần bị đuổi
         let evicted_clone = Arc::clone(&evicted_count);
         let mut cache = LruCache::<usize, usize, 32>::new(total_capacity);
-        cache.on_removing = Some(Arc::new(move |_, _| {
+        cache.on_removing = Some(Arc::new(move |_, _, _| {
             evicted_clone.fetch_add(1, Ordering::SeqCst);
         }));
 
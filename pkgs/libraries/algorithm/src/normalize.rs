@@ -0,0 +1,103 @@
+//! Chuẩn hoá text tiếng Việt cho search — bỏ dấu, lowercase, tách từ.
+//!
+//! ## Idea
+//! Người dùng hay gõ không dấu ("bao tin minh chau") trong khi tên lưu có dấu
+//! ("Bảo Tín Minh Châu"). Cả key lúc index lẫn query lúc search đều đi qua
+//! cùng một phép `normalize` nên so khớp trên byte là đủ:
+//!
+//! ```text
+//! "Bảo Tín  Minh Châu" ─lowercase─► NFD ─bỏ combining mark─► đ→d ─gộp space─► "bao tin minh chau"
+//! ```
+//!
+//! `tokenize` tách kết quả đã chuẩn hoá theo khoảng trắng, bỏ dấu câu ở hai đầu
+//! mỗi từ và bỏ các token không chứa chữ/số (vd: "-" trong "PNJ - Vàng").
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+// ==================== Normalize ====================
+
+/// Chuẩn hoá `text`: lowercase, bỏ dấu thanh/dấu mũ/móc (NFD + bỏ combining
+/// mark), `đ` → `d`, gộp mọi khoảng trắng/ký tự điều khiển thành một space và
+/// trim hai đầu.
+pub fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pending_space = false;
+
+    // Lowercase trước NFD: một số ký tự hoa khi lowercase sinh ra combining mark
+    for c in text.chars().flat_map(char::to_lowercase).nfd() {
+        if is_combining_mark(c) {
+            continue;
+        }
+
+        if c.is_whitespace() || c.is_control() {
+            pending_space = !out.is_empty();
+            continue;
+        }
+
+        if pending_space {
+            out.push(' ');
+            pending_space = false;
+        }
+
+        // @NOTE: `đ` không có dạng phân tách trong Unicode nên phải map tay
+        out.push(if c == 'đ' { 'd' } else { c });
+    }
+
+    out
+}
+
+// ==================== Tokenize ====================
+
+/// Chuẩn hoá rồi tách `text` thành các từ, giữ nguyên thứ tự xuất hiện.
+pub fn tokenize(text: &str) -> Vec<String> {
+    normalize(text)
+        .split(' ')
+        .map(|token| token.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// ==================== Tests ====================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_strips_vietnamese_marks() {
+        assert_eq!(normalize("Bảo Tín Minh Châu"), "bao tin minh chau");
+        assert_eq!(normalize("Vàng Bạc Đá Quý"), "vang bac da quy");
+        assert_eq!(normalize("Nữ Trang Ưu Đãi"), "nu trang uu dai");
+        assert_eq!(normalize("Phở Hà Nội"), "pho ha noi");
+    }
+
+    #[test]
+    fn test_normalize_is_idempotent_and_precomposed_agnostic() {
+        // "ế" dạng dựng sẵn (U+1EBF) và dạng tổ hợp (e + U+0302 + U+0301)
+        let composed = "Thế Giới";
+        let decomposed = "The\u{302}\u{301} Giới";
+        assert_eq!(normalize(composed), normalize(decomposed));
+        assert_eq!(normalize(&normalize(composed)), normalize(composed));
+    }
+
+    #[test]
+    fn test_normalize_collapses_whitespace() {
+        assert_eq!(
+            normalize("  Tiệm\tVàng \n Kim  Thành "),
+            "tiem vang kim thanh"
+        );
+        assert_eq!(normalize("   "), "");
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("PNJ - Vàng Bạc, Đá Quý!"),
+            vec!["pnj", "vang", "bac", "da", "quy"]
+        );
+        assert_eq!(tokenize("Vàng 24K"), vec!["vang", "24k"]);
+        assert!(tokenize(" - ").is_empty());
+    }
+}
@@ -0,0 +1,644 @@
+//! Chạy nhiều `JsonQuery` trong một lượt đọc JSON, decode thẳng ra cột số.
+//!
+//! ## Idea
+//! `JsonQuery::pick` cần cả cây `serde_json::Value` đã parse, còn `execute` clone
+//! từng giá trị trung gian. Với response 10k nến, phần lớn thời gian nằm ở việc
+//! dựng cây rồi lại đọc ra f64. `QueryPlan` biên dịch các path thành các bước
+//! đơn giản rồi đi thẳng trên token của `serde_json::Deserializer`:
+//!
+//! ```text
+//! [[t, o, h, l, c, v], ...]      cursors tại root: (t,0) (o,0) ... (v,0)
+//!  └─ phần tử i  ── `[]` ──►     (t,1) (o,1) ... (v,1)
+//!      └─ phần tử 0 ── `.0` ──►  (t,2) = hết path → decode vào cột t
+//!      └─ phần tử 1 ── `.1` ──►  (o,2) = hết path → decode vào cột o
+//! ```
+//!
+//! Node không còn cursor nào được bỏ qua bằng `IgnoredAny` — không cấp phát.
+//!
+//! Chỉ các operator đi được một chiều theo thứ tự tài liệu mới biên dịch được:
+//! `Match`, `Access`, `Iter`, `Wildcard`, `Slice` không âm với step dương, kèm
+//! các function theo từng giá trị ở cuối (`multiply`, `divide`, `to_number`,
+//! `to_epoch`). Plan có path khác (filter, `..`, index âm, `map`, ...) tự lùi về
+//! parse `Value` rồi `execute`, kết quả giống nhau.
+//!
+//! @NOTE: khi duyệt object bằng `[]`/`*`, plan đi theo thứ tự key trong tài liệu,
+//! còn `Value` mặc định sắp key theo thứ tự chữ cái.
+
+use serde::Deserialize;
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use serde_json::value::RawValue;
+use smallvec::SmallVec;
+use std::fmt;
+use std::io::{Error, ErrorKind, Read};
+
+use crate::jq::{Function, JsonQuery, Operator, call};
+
+// ==================== Columns ====================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    F64,
+    I64,
+}
+
+/// Kết quả của một path. Giá trị không đổi được sang số thành `0`, giống
+/// `as_f64_lossy` của các provider.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Column {
+    F64(Vec<f64>),
+    I64(Vec<i64>),
+}
+
+impl Column {
+    fn new(kind: ColumnKind) -> Self {
+        match kind {
+            ColumnKind::F64 => Self::F64(Vec::new()),
+            ColumnKind::I64 => Self::I64(Vec::new()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::F64(values) => values.len(),
+            Self::I64(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_f64(&self) -> Option<&[f64]> {
+        match self {
+            Self::F64(values) => Some(values),
+            Self::I64(_) => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<&[i64]> {
+        match self {
+            Self::I64(values) => Some(values),
+            Self::F64(_) => None,
+        }
+    }
+
+    fn push(&mut self, leaf: Leaf<'_>) {
+        match self {
+            Self::F64(values) => values.push(match leaf {
+                Leaf::I64(n) => n as f64,
+                Leaf::U64(n) => n as f64,
+                Leaf::F64(n) => n,
+                Leaf::Str(text) => text.parse().unwrap_or(0.0),
+                Leaf::Bool(_) | Leaf::Null | Leaf::Container => 0.0,
+            }),
+            Self::I64(values) => values.push(match leaf {
+                Leaf::I64(n) => n,
+                Leaf::U64(n) => n as i64,
+                Leaf::F64(n) => n as i64,
+                Leaf::Str(text) => text
+                    .parse::<i64>()
+                    .or_else(|_| text.parse::<f64>().map(|n| n as i64))
+                    .unwrap_or(0),
+                Leaf::Bool(_) | Leaf::Null | Leaf::Container => 0,
+            }),
+        }
+    }
+}
+
+/// Giá trị tại cuối path, mượn thẳng từ input khi có thể.
+#[derive(Clone, Copy)]
+enum Leaf<'a> {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(&'a str),
+    Bool(bool),
+    Null,
+    /// Mảng/object — không đổi được sang số.
+    Container,
+}
+
+impl<'a> Leaf<'a> {
+    fn from_value(value: &'a Value) -> Self {
+        match value {
+            Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+                (Some(n), _) => Leaf::I64(n),
+                (None, Some(n)) => Leaf::U64(n),
+                _ => Leaf::F64(n.as_f64().unwrap_or(0.0)),
+            },
+            Value::String(text) => Leaf::Str(text),
+            Value::Bool(flag) => Leaf::Bool(*flag),
+            Value::Null => Leaf::Null,
+            Value::Array(_) | Value::Object(_) => Leaf::Container,
+        }
+    }
+
+    fn to_value(self) -> Value {
+        match self {
+            Leaf::I64(n) => Value::from(n),
+            Leaf::U64(n) => Value::from(n),
+            Leaf::F64(n) => serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number),
+            Leaf::Str(text) => Value::from(text),
+            Leaf::Bool(flag) => Value::Bool(flag),
+            Leaf::Null | Leaf::Container => Value::Null,
+        }
+    }
+}
+
+// ==================== Compile ====================
+
+enum Step {
+    /// `.field` — với mảng thì không khớp.
+    Key(String),
+    /// `[n]`/`.n` — index của mảng, hoặc key `"n"` của object như `Access`.
+    Index(usize),
+    /// `[]`/`*`
+    Each,
+    /// `[start:end:step]` với start/end không âm và step dương.
+    Range {
+        start: usize,
+        end: Option<usize>,
+        step: usize,
+    },
+}
+
+impl Step {
+    fn matches_key(&self, key: &str) -> bool {
+        match self {
+            Step::Key(field) => field == key,
+            Step::Index(index) => key.parse::<usize>().is_ok_and(|key| key == *index),
+            Step::Each => true,
+            Step::Range { .. } => false,
+        }
+    }
+
+    fn matches_index(&self, i: usize) -> bool {
+        match self {
+            Step::Key(_) => false,
+            Step::Index(index) => *index == i,
+            Step::Each => true,
+            Step::Range { start, end, step } => {
+                i >= *start && end.is_none_or(|end| i < end) && (i - start).is_multiple_of(*step)
+            }
+        }
+    }
+}
+
+struct CompiledPath {
+    steps: Vec<Step>,
+    /// Các `Operator::Call` chạy trên giá trị cuối trước khi decode.
+    transforms: Vec<Function>,
+    kind: ColumnKind,
+}
+
+impl CompiledPath {
+    fn compile(query: &JsonQuery, kind: ColumnKind) -> Option<Self> {
+        let mut steps = Vec::new();
+        let mut transforms = Vec::new();
+
+        for op in &query.operators {
+            // Path sau function làm việc trên giá trị mới, không đi theo tài liệu được
+            if !transforms.is_empty() && !matches!(op, Operator::Call(_)) {
+                return None;
+            }
+
+            match op {
+                Operator::Match(field) => steps.push(Step::Key(field.clone())),
+                Operator::Access(index) => steps.push(Step::Index(*index)),
+                Operator::Iter | Operator::Wildcard => steps.push(Step::Each),
+                Operator::Slice { start, end, step } => {
+                    let start = usize::try_from(start.unwrap_or(0)).ok()?;
+                    let end = end.map(usize::try_from).transpose().ok()?;
+                    let step = usize::try_from(*step).ok().filter(|step| *step > 0)?;
+                    steps.push(Step::Range { start, end, step });
+                }
+                Operator::Call(
+                    function @ (Function::Multiply(_)
+                    | Function::Divide(_)
+                    | Function::ToNumber
+                    | Function::ToEpoch(_)),
+                ) => transforms.push(function.clone()),
+                _ => return None,
+            }
+        }
+
+        Some(Self {
+            steps,
+            transforms,
+            kind,
+        })
+    }
+
+    fn record(&self, column: &mut Column, leaf: Leaf<'_>) {
+        if self.transforms.is_empty() {
+            column.push(leaf);
+            return;
+        }
+
+        let mut value = leaf.to_value();
+        for function in &self.transforms {
+            let mut out = Vec::with_capacity(1);
+            call(function, &value, &mut out);
+            value = out.pop().unwrap_or(Value::Null);
+        }
+        column.push(Leaf::from_value(&value));
+    }
+}
+
+// ==================== Plan ====================
+
+/// Nhiều `JsonQuery` được biên dịch để chạy chung một lượt trên JSON thô.
+pub struct QueryPlan {
+    queries: Vec<(JsonQuery, ColumnKind)>,
+    /// `None` khi có path không biên dịch được → lùi về `execute`.
+    compiled: Option<Vec<CompiledPath>>,
+}
+
+impl QueryPlan {
+    pub fn new(queries: Vec<(JsonQuery, ColumnKind)>) -> Self {
+        let compiled = queries
+            .iter()
+            .map(|(query, kind)| CompiledPath::compile(query, *kind))
+            .collect::<Option<Vec<_>>>();
+
+        Self { queries, compiled }
+    }
+
+    /// Parse rồi biên dịch từng path, tiện cho cấu hình dạng chuỗi.
+    pub fn parse(paths: &[(&str, ColumnKind)]) -> Result<Self, Error> {
+        let queries = paths
+            .iter()
+            .map(|(path, kind)| Ok((JsonQuery::parse(path)?, *kind)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Self::new(queries))
+    }
+
+    /// `true` nếu mọi path chạy trực tiếp trên token, không cần dựng `Value`.
+    pub fn is_streaming(&self) -> bool {
+        self.compiled.is_some()
+    }
+
+    pub fn run_str(&self, json: &str) -> Result<Vec<Column>, Error> {
+        self.run_slice(json.as_bytes())
+    }
+
+    pub fn run_raw(&self, raw: &RawValue) -> Result<Vec<Column>, Error> {
+        self.run_str(raw.get())
+    }
+
+    pub fn run_slice(&self, json: &[u8]) -> Result<Vec<Column>, Error> {
+        self.run(&mut serde_json::Deserializer::from_slice(json))
+    }
+
+    /// Đọc dần từ `reader`, không cần giữ cả payload trong bộ nhớ.
+    pub fn run_reader<R: Read>(&self, reader: R) -> Result<Vec<Column>, Error> {
+        self.run(&mut serde_json::Deserializer::from_reader(reader))
+    }
+
+    /// Chạy trên `Value` đã có sẵn (luôn qua `execute`).
+    pub fn run_value(&self, data: &Value) -> Vec<Column> {
+        self.queries
+            .iter()
+            .map(|(query, kind)| {
+                let mut column = Column::new(*kind);
+                for value in query.execute(data) {
+                    column.push(Leaf::from_value(&value));
+                }
+                column
+            })
+            .collect()
+    }
+
+    fn run<'de, R: serde_json::de::Read<'de>>(
+        &self,
+        de: &mut serde_json::Deserializer<R>,
+    ) -> Result<Vec<Column>, Error> {
+        let invalid = |error: serde_json::Error| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to parse JSON: {error}"),
+            )
+        };
+
+        let Some(paths) = &self.compiled else {
+            let data = Value::deserialize(&mut *de).map_err(invalid)?;
+            de.end().map_err(invalid)?;
+            return Ok(self.run_value(&data));
+        };
+
+        let mut columns = paths
+            .iter()
+            .map(|path| Column::new(path.kind))
+            .collect::<Vec<_>>();
+        let cursors = (0..paths.len()).map(|path| (path, 0)).collect();
+
+        NodeSeed {
+            paths,
+            columns: &mut columns,
+            cursors,
+        }
+        .deserialize(&mut *de)
+        .map_err(invalid)?;
+        de.end().map_err(invalid)?;
+
+        Ok(columns)
+    }
+}
+
+// ==================== Walk ====================
+
+/// `(path, số bước đã khớp)` của các path còn đi qua node hiện tại.
+type Cursors = SmallVec<[(usize, usize); 8]>;
+
+struct NodeSeed<'p, 'c> {
+    paths: &'p [CompiledPath],
+    columns: &'c mut [Column],
+    cursors: Cursors,
+}
+
+impl NodeSeed<'_, '_> {
+    fn leaf(&mut self, leaf: Leaf<'_>) {
+        for &(path, pos) in &self.cursors {
+            let compiled = &self.paths[path];
+            if pos == compiled.steps.len() {
+                compiled.record(&mut self.columns[path], leaf);
+            }
+        }
+    }
+
+    fn advance(&self, matches: impl Fn(&Step) -> bool) -> Cursors {
+        self.cursors
+            .iter()
+            .filter(|&&(path, pos)| self.paths[path].steps.get(pos).is_some_and(&matches))
+            .map(|&(path, pos)| (path, pos + 1))
+            .collect()
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for NodeSeed<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for NodeSeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(mut self, v: bool) -> Result<(), E> {
+        self.leaf(Leaf::Bool(v));
+        Ok(())
+    }
+
+    fn visit_i64<E>(mut self, v: i64) -> Result<(), E> {
+        self.leaf(Leaf::I64(v));
+        Ok(())
+    }
+
+    fn visit_u64<E>(mut self, v: u64) -> Result<(), E> {
+        self.leaf(Leaf::U64(v));
+        Ok(())
+    }
+
+    fn visit_f64<E>(mut self, v: f64) -> Result<(), E> {
+        self.leaf(Leaf::F64(v));
+        Ok(())
+    }
+
+    fn visit_str<E>(mut self, v: &str) -> Result<(), E> {
+        self.leaf(Leaf::Str(v));
+        Ok(())
+    }
+
+    fn visit_unit<E>(mut self) -> Result<(), E> {
+        self.leaf(Leaf::Null);
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        self.leaf(Leaf::Container);
+
+        let mut index = 0;
+        loop {
+            let cursors = self.advance(|step| step.matches_index(index));
+            let more = if cursors.is_empty() {
+                seq.next_element::<IgnoredAny>()?.is_some()
+            } else {
+                seq.next_element_seed(NodeSeed {
+                    paths: self.paths,
+                    columns: &mut *self.columns,
+                    cursors,
+                })?
+                .is_some()
+            };
+            if !more {
+                return Ok(());
+            }
+            index += 1;
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+        self.leaf(Leaf::Container);
+
+        while let Some(cursors) = map.next_key_seed(KeySeed { node: &self })? {
+            if cursors.is_empty() {
+                map.next_value::<IgnoredAny>()?;
+            } else {
+                map.next_value_seed(NodeSeed {
+                    paths: self.paths,
+                    columns: &mut *self.columns,
+                    cursors,
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Đọc key của object và trả về luôn các cursor đi tiếp vào value — so khớp
+/// trên `&str` mượn từ input nên không cấp phát `String` cho key.
+struct KeySeed<'n, 'p, 'c> {
+    node: &'n NodeSeed<'p, 'c>,
+}
+
+impl<'de> DeserializeSeed<'de> for KeySeed<'_, '_, '_> {
+    type Value = Cursors;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Cursors, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeySeed<'_, '_, '_> {
+    type Value = Cursors;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an object key")
+    }
+
+    fn visit_str<E>(self, key: &str) -> Result<Cursors, E> {
+        Ok(self.node.advance(|step| step.matches_key(key)))
+    }
+}
+
+// ==================== Tests ====================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Instant;
+
+    const OHLCV: [&str; 6] = ["[].0", "[].1", "[].2", "[].3", "[].4", "[].5"];
+
+    fn ohlcv_plan(paths: [&str; 6]) -> QueryPlan {
+        let kinds = [ColumnKind::I64].into_iter().chain([ColumnKind::F64; 5]);
+        QueryPlan::parse(&paths.into_iter().zip(kinds).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn test_plan_extracts_columns_in_one_pass() {
+        let data = json!([
+            [1700000000000i64, "100.5", "101.0", "99.0", "100.8", "5000"],
+            [1700000060000i64, "100.8", "102.0", "100.5", "101.5", 6000.5]
+        ]);
+        let plan = ohlcv_plan([
+            "[].0 | divide(1000)",
+            "[].1",
+            "[].2",
+            "[].3",
+            "[].4",
+            "[].5",
+        ]);
+        assert!(plan.is_streaming());
+
+        let columns = plan.run_str(&data.to_string()).unwrap();
+        assert_eq!(columns[0].as_i64().unwrap(), &[1700000000, 1700000060]);
+        assert_eq!(columns[1].as_f64().unwrap(), &[100.5, 100.8]);
+        assert_eq!(columns[5].as_f64().unwrap(), &[5000.0, 6000.5]);
+        assert_eq!(columns, plan.run_value(&data));
+
+        let raw = RawValue::from_string(data.to_string()).unwrap();
+        assert_eq!(plan.run_raw(&raw).unwrap(), columns);
+        assert_eq!(
+            plan.run_reader(data.to_string().as_bytes()).unwrap(),
+            columns
+        );
+    }
+
+    #[test]
+    fn test_plan_matches_execute() {
+        let data = json!({
+            "data": {
+                "t": [1, 2, 3, 4, 5],
+                "c": ["10.5", 11, null, {"x": 1}, 12.25],
+            },
+            "items": [
+                {"info": {"price": 100}, "type": "stock"},
+                {"error": "not found"},
+                {"info": {"price": "200"}, "type": "etf"},
+                {"0": 7}
+            ],
+            // Key có ký tự phải escape trong JSON → serde không mượn được `&str`
+            "tab\tkey": [42]
+        });
+        let text = data.to_string();
+
+        let streaming = [
+            "data.t[]",
+            "data.c[]",
+            "data.t[1:4:2]",
+            "data.t[3:]",
+            "items[].info.price",
+            "items[3].0",
+            "items.*.info.price | multiply(2)",
+            "[\"tab\tkey\"][0]",
+            "data.t",
+            "",
+        ];
+        let fallback = [
+            "data.t[-2:]",
+            "items[?(@.type == 'stock')].info.price",
+            "..price",
+            "data.c | length",
+        ];
+
+        for (path, expect_streaming) in streaming
+            .iter()
+            .map(|path| (path, true))
+            .chain(fallback.iter().map(|path| (path, false)))
+        {
+            for kind in [ColumnKind::F64, ColumnKind::I64] {
+                let plan = QueryPlan::parse(&[(path, kind)]).unwrap();
+                assert_eq!(plan.is_streaming(), expect_streaming, "{path}");
+                assert_eq!(
+                    plan.run_str(&text).unwrap(),
+                    plan.run_value(&data),
+                    "{path} {kind:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_plan_rejects_invalid_json() {
+        let plan = ohlcv_plan(OHLCV);
+        assert!(plan.run_str("[[1, 2]").is_err());
+        assert!(plan.run_str("[[1, 2]] trailing").is_err());
+        assert!(plan.run_reader("[[1,".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn run_benchmark() {
+        let size = 10_000;
+        let rows = (0..size)
+            .map(|i| {
+                json!([
+                    1700000000000i64 + i as i64 * 60_000,
+                    format!("{:.2}", 100.0 + i as f64 * 0.01),
+                    "101.0",
+                    "99.0",
+                    "100.8",
+                    "5000"
+                ])
+            })
+            .collect::<Vec<_>>();
+        let text = Value::Array(rows).to_string();
+        let plan = ohlcv_plan(OHLCV);
+        let queries = OHLCV.map(|path| JsonQuery::parse(path).unwrap());
+
+        let start = Instant::now();
+        let data = serde_json::from_str::<Value>(&text).unwrap();
+        let decoded = queries
+            .iter()
+            .map(|query| {
+                query
+                    .execute(&data)
+                    .into_iter()
+                    .map(|v| match v {
+                        Value::String(text) => text.parse::<f64>().unwrap_or(0.0),
+                        _ => v.as_f64().unwrap_or(0.0),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let tree_duration = start.elapsed();
+
+        let start = Instant::now();
+        let columns = plan.run_str(&text).unwrap();
+        let plan_duration = start.elapsed();
+
+        println!("\n🚀 Parse Value + execute ({size} rows): {tree_duration:?}");
+        println!("🚀 QueryPlan one pass ({size} rows): {plan_duration:?}");
+
+        assert_eq!(columns[4].as_f64().unwrap(), decoded[4].as_slice());
+        assert!(columns.iter().all(|column| column.len() == size));
+    }
+}
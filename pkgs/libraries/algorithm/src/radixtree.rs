@@ -26,12 +26,18 @@ impl From<storage::StorageError> for RadixError {
 pub type Result<T> = std::result::Result<T, RadixError>;
 
 pub type OnSplitCallback = Arc<dyn Fn(usize, usize, &[u8], usize) -> Result<()> + Send + Sync>;
+/// `(node_id, absorbed_child_id, absorbed_prefix)` — node nuốt prefix + children của child duy nhất.
+pub type OnMergeCallback = Arc<dyn Fn(usize, usize, &[u8]) -> Result<()> + Send + Sync>;
+/// `(node_id, prefix)` — node bị xoá khỏi tree.
+pub type OnPruneCallback = Arc<dyn Fn(usize, &[u8]) -> Result<()> + Send + Sync>;
 
 pub struct RadixTree {
     endpoints: Vec<usize>,
     sharding: usize,
     storage: Box<dyn Storage>,
     on_split: Option<OnSplitCallback>,
+    on_merge: Option<OnMergeCallback>,
+    on_prune: Option<OnPruneCallback>,
 }
 
 pub(crate) fn shard_of(byte: u8, sharding: usize) -> usize {
@@ -45,13 +51,33 @@ impl RadixTree {
             sharding: sharding.max(1),
             storage: Box::new(storage),
             on_split: None,
+            on_merge: None,
+            on_prune: None,
         }
     }
 
+    /// Dựng lại tree từ storage đã có dữ liệu (vd `FileStorage` mở lại sau restart),
+    /// nạp root của từng shard thay vì bắt đầu từ tree rỗng.
+    pub async fn open<S: Storage + 'static>(sharding: usize, storage: S) -> Result<Self> {
+        let mut tree = Self::new(sharding, storage);
+        for si in 0..tree.sharding {
+            tree.endpoints[si] = tree.storage.get_root(si).await?;
+        }
+        Ok(tree)
+    }
+
     pub fn with_callback(&mut self, cb: OnSplitCallback) {
         self.on_split = Some(cb);
     }
 
+    pub fn with_merge_callback(&mut self, cb: OnMergeCallback) {
+        self.on_merge = Some(cb);
+    }
+
+    pub fn with_prune_callback(&mut self, cb: OnPruneCallback) {
+        self.on_prune = Some(cb);
+    }
+
     pub async fn insert(&mut self, key: &[u8], index: usize) -> Result<(usize, usize)> {
         if index == EMPTY {
             return Err(RadixError::InvalidIndex);
@@ -82,6 +108,11 @@ impl RadixTree {
 
             tail += common;
             if tail == key.len() {
+                // Key kết thúc đúng tại node đã có: chỉ gán record nếu key chưa tồn tại
+                // (node trung gian sinh ra do split hoặc do remove trước đó).
+                if self.record_holder(node_id).await?.is_none() {
+                    self.storage.update_node(node_id, None, Some(index)).await?;
+                }
                 return Ok((EMPTY, tail));
             }
 
@@ -109,42 +140,158 @@ impl RadixTree {
     }
 
     pub async fn r#match(&self, key: &[u8]) -> Result<usize> {
+        let path = self.locate(key).await?.ok_or(RadixError::NotFound)?;
+        let (_, record) = self.storage.get_node(path[path.len() - 1]).await?;
+        Ok(record)
+    }
+
+    /// Cập nhật record của key đã tồn tại, trả về record cũ.
+    pub async fn update(&mut self, key: &[u8], index: usize) -> Result<usize> {
+        if index == EMPTY {
+            return Err(RadixError::InvalidIndex);
+        }
+
+        let path = self.locate(key).await?.ok_or(RadixError::NotFound)?;
+        let node_id = path[path.len() - 1];
+        let (_, old_record) = self.storage.get_node(node_id).await?;
+        self.storage.update_node(node_id, None, Some(index)).await?;
+        Ok(old_record)
+    }
+
+    /// Xoá key khỏi tree, trả về record đã gắn với key.
+    ///
+    /// Sau khi bỏ record, tree được thu gọn ngược từ node đó lên root:
+    /// - node không còn record lẫn children → bị xoá khỏi parent (prune)
+    /// - node không còn record và chỉ còn một child → gộp child vào node (merge)
+    pub async fn remove(&mut self, key: &[u8]) -> Result<usize> {
+        let path = self.locate(key).await?.ok_or(RadixError::NotFound)?;
+        let node_id = path[path.len() - 1];
+        let (_, record) = self.storage.get_node(node_id).await?;
+
+        self.storage.update_node(node_id, None, Some(EMPTY)).await?;
+        self.compact_path(shard_of(key[0], self.sharding), &path)
+            .await?;
+        Ok(record)
+    }
+
+    /// Đường đi từ root của shard tới node đang giữ record của `key`
+    /// (phần tử cuối). `None` nếu key không tồn tại.
+    async fn locate(&self, key: &[u8]) -> Result<Option<Vec<usize>>> {
+        if key.is_empty() {
+            return Ok(None);
+        }
+
         let mut node_id = self.endpoints[shard_of(key[0], self.sharding)];
         let mut pos = 0;
+        let mut path = Vec::new();
 
         while node_id != EMPTY {
+            let (prefix, _) = self.storage.get_node(node_id).await?;
+            if !key[pos..].starts_with(&prefix) {
+                return Ok(None);
+            }
+
+            path.push(node_id);
+            pos += prefix.len();
+
+            if pos == key.len() {
+                return Ok(self.record_holder(node_id).await?.map(|holder| {
+                    if holder != node_id {
+                        path.push(holder);
+                    }
+                    path
+                }));
+            }
+
+            node_id = self.find_child(node_id, key[pos]).await?.unwrap_or(EMPTY);
+        }
+
+        Ok(None)
+    }
+
+    /// Node giữ record cho key kết thúc tại `node_id`: chính nó, hoặc child có
+    /// prefix rỗng (sinh ra khi insert một key là prefix của key đã có).
+    async fn record_holder(&self, node_id: usize) -> Result<Option<usize>> {
+        let (_, record) = self.storage.get_node(node_id).await?;
+        if record != EMPTY {
+            return Ok(Some(node_id));
+        }
+
+        for child in self.storage.get_children(node_id).await? {
+            let (p, r) = self.storage.get_node(child).await?;
+            if p.is_empty() && r != EMPTY {
+                return Ok(Some(child));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn find_child(&self, node_id: usize, byte: u8) -> Result<Option<usize>> {
+        for child in self.storage.get_children(node_id).await? {
+            let (p, _) = self.storage.get_node(child).await?;
+            if !p.is_empty() && p[0] == byte {
+                return Ok(Some(child));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Thu gọn các node trên `path` (từ cuối lên) sau khi một record bị bỏ.
+    async fn compact_path(&mut self, si: usize, path: &[usize]) -> Result<()> {
+        let mut i = path.len() - 1;
+
+        loop {
+            let node_id = path[i];
             let (prefix, record) = self.storage.get_node(node_id).await?;
-            let common = prefix
-                .iter()
-                .zip(&key[pos..])
-                .take_while(|(a, b)| a == b)
-                .count();
+            if record != EMPTY {
+                return Ok(());
+            }
 
-            if common == prefix.len() {
-                pos += common;
-                if pos == key.len() {
-                    return Ok(record);
-                }
-                let next_byte = key[pos];
-                let children = self.storage.get_children(node_id).await?;
-                let mut found_child = None;
-                for &c in &children {
-                    if let Ok((p, _)) = self.storage.get_node(c).await
-                        && !p.is_empty()
-                        && p[0] == next_byte
-                    {
-                        found_child = Some(c);
-                        break;
+            let children = self.storage.get_children(node_id).await?;
+            match children.len() {
+                0 => {
+                    if i == 0 {
+                        self.storage.set_root(si, EMPTY).await?;
+                        self.endpoints[si] = EMPTY;
+                    } else {
+                        self.storage.remove_child(path[i - 1], node_id).await?;
                     }
+                    self.storage.remove_node(node_id).await?;
+
+                    if let Some(cb) = &self.on_prune {
+                        cb(node_id, &prefix)?;
+                    }
+
+                    if i == 0 {
+                        return Ok(());
+                    }
+                    // Parent có thể vừa mất child → xét tiếp
+                    i -= 1;
                 }
-                if let Some(child) = found_child {
-                    node_id = child;
-                    continue;
+                1 => {
+                    let child = children[0];
+                    let (child_prefix, child_record) = self.storage.get_node(child).await?;
+                    let grandchildren = self.storage.get_children(child).await?;
+
+                    let mut merged = prefix;
+                    merged.extend_from_slice(&child_prefix);
+                    self.storage
+                        .update_node(node_id, Some(merged), Some(child_record))
+                        .await?;
+                    self.storage.remove_child(node_id, child).await?;
+                    for grandchild in grandchildren {
+                        self.storage.add_child(node_id, grandchild).await?;
+                    }
+                    self.storage.remove_node(child).await?;
+
+                    if let Some(cb) = &self.on_merge {
+                        cb(node_id, child, &child_prefix)?;
+                    }
+                    return Ok(());
                 }
+                _ => return Ok(()),
             }
-            break;
         }
-        Err(RadixError::NotFound)
     }
 
     async fn extend(&mut self, parent: usize, suffix: &[u8], value: usize) -> Result<usize> {
@@ -171,6 +318,13 @@ impl RadixTree {
         self.storage
             .update_node(parent, Some(root_prefix), Some(EMPTY))
             .await?;
+
+        // Children cũ nối tiếp phần prefix đã tách ra → chuyển sang leg
+        for child in self.storage.get_children(parent).await? {
+            self.storage.remove_child(parent, child).await?;
+            self.storage.add_child(leg_id, child).await?;
+        }
+
         self.storage.add_child(parent, leg_id).await?;
         self.storage.add_child(parent, new_id).await?;
 
@@ -207,6 +361,19 @@ impl RadixTree {
         Ok(self.storage.get_children(id).await?)
     }
 
+    /// Root node của từng shard (`EMPTY` nếu shard chưa có key).
+    pub(crate) fn root_ids(&self) -> &[usize] {
+        &self.endpoints
+    }
+
+    pub(crate) fn storage(&self) -> &dyn Storage {
+        self.storage.as_ref()
+    }
+
+    pub(crate) fn storage_mut(&mut self) -> &mut dyn Storage {
+        self.storage.as_mut()
+    }
+
     // ==================== PREFIX SEARCH ====================
 
     /// Tìm tất cả record có key bắt đầu bằng `prefix`.
@@ -305,6 +472,240 @@ impl RadixTree {
 
         Ok(())
     }
+
+    // ==================== ORDERED ITERATION ====================
+
+    /// Duyệt mọi key bắt đầu bằng `prefix` theo thứ tự từ điển.
+    /// `prefix` rỗng = toàn bộ tree.
+    pub fn iter_prefix(&self, prefix: &[u8]) -> KeyIter<'_> {
+        KeyIter::new(self, prefix, None, None)
+    }
+
+    /// Duyệt các key trong `[start, end)` theo thứ tự từ điển.
+    pub fn range(&self, start: &[u8], end: &[u8]) -> KeyIter<'_> {
+        KeyIter::new(self, &[], Some(start), Some(end))
+    }
+
+    /// Số key bắt đầu bằng `prefix`.
+    pub async fn count_prefix(&self, prefix: &[u8]) -> Result<usize> {
+        self.iter_prefix(prefix).count_keys().await
+    }
+
+    // ==================== FUZZY SEARCH ====================
+
+    /// Tìm các key có khoảng cách Levenshtein tới `key` không vượt quá `max_distance`.
+    ///
+    /// Duyệt tree như một Levenshtein automaton: mỗi byte đi qua sinh một hàng DP
+    /// mới từ hàng của node cha; nhánh nào có giá trị nhỏ nhất trong hàng đã vượt
+    /// `max_distance` thì bỏ, vì không key nào bên dưới còn có thể khớp.
+    ///
+    /// Trả về `Vec<(full_key, record, distance)>`, khoảng cách tăng dần.
+    pub async fn search_fuzzy(
+        &self,
+        key: &[u8],
+        max_distance: usize,
+    ) -> Result<Vec<(Vec<u8>, usize, usize)>> {
+        if key.is_empty() {
+            return Err(RadixError::NotFound);
+        }
+
+        let first_row: Vec<usize> = (0..=key.len()).collect();
+        let mut stack: Vec<(usize, Vec<u8>, Vec<usize>)> = self
+            .endpoints
+            .iter()
+            .filter(|&&id| id != EMPTY)
+            .map(|&id| (id, Vec::new(), first_row.clone()))
+            .collect();
+        let mut results = Vec::new();
+
+        'walk: while let Some((node_id, mut path, mut row)) = stack.pop() {
+            let (prefix, record) = self.storage.get_node(node_id).await?;
+
+            for &byte in &prefix {
+                row = Self::levenshtein_step(key, &row, byte);
+                path.push(byte);
+
+                if row.iter().min().is_some_and(|&d| d > max_distance) {
+                    continue 'walk;
+                }
+            }
+
+            let distance = row[key.len()];
+            if record != EMPTY && distance <= max_distance {
+                results.push((path.clone(), record, distance));
+            }
+
+            for child in self.storage.get_children(node_id).await? {
+                stack.push((child, path.clone(), row.clone()));
+            }
+        }
+
+        if results.is_empty() {
+            return Err(RadixError::NotFound);
+        }
+
+        results.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| a.0.cmp(&b.0)));
+        Ok(results)
+    }
+
+    /// Một bước của Levenshtein automaton: hàng DP sau khi đọc thêm `byte`.
+    fn levenshtein_step(key: &[u8], prev: &[usize], byte: u8) -> Vec<usize> {
+        let mut row = Vec::with_capacity(prev.len());
+        row.push(prev[0] + 1);
+
+        for i in 1..prev.len() {
+            let cost = usize::from(key[i - 1] != byte);
+            let value = (prev[i] + 1).min(row[i - 1] + 1).min(prev[i - 1] + cost);
+            row.push(value);
+        }
+        row
+    }
+}
+
+// ==================== ORDERED ITERATION ====================
+
+/// Cursor duyệt `(key, record)` theo thứ tự từ điển, nạp node lười qua `Storage`
+/// nên chạy được trên mọi backend (in-memory, file, Redis).
+///
+/// Mỗi shard có một stack DFS riêng (children xếp theo prefix); các shard được
+/// merge theo key nhỏ nhất. Subtree nằm hẳn ngoài `prefix`/`start..end` bị bỏ
+/// qua mà không cần nạp.
+pub struct KeyIter<'a> {
+    tree: &'a RadixTree,
+    prefix: Vec<u8>,
+    start: Option<Vec<u8>>,
+    end: Option<Vec<u8>>,
+    /// Stack `(node_id, key tính tới hết prefix của node)` của từng shard.
+    stacks: Vec<Vec<(usize, Vec<u8>)>>,
+    /// Phần tử kế tiếp đã nạp sẵn của từng shard.
+    heads: Vec<Option<(Vec<u8>, usize)>>,
+    started: bool,
+}
+
+impl<'a> KeyIter<'a> {
+    fn new(tree: &'a RadixTree, prefix: &[u8], start: Option<&[u8]>, end: Option<&[u8]>) -> Self {
+        // Có prefix → chỉ cần shard của byte đầu
+        let roots: Vec<usize> = match prefix.first() {
+            Some(&byte) => vec![tree.endpoints[shard_of(byte, tree.sharding)]],
+            None => tree.endpoints.clone(),
+        };
+
+        let stacks: Vec<Vec<(usize, Vec<u8>)>> = roots
+            .into_iter()
+            .filter(|&id| id != EMPTY)
+            .map(|id| vec![(id, Vec::new())])
+            .collect();
+
+        Self {
+            tree,
+            prefix: prefix.to_vec(),
+            start: start.map(<[u8]>::to_vec),
+            end: end.map(<[u8]>::to_vec),
+            heads: vec![None; stacks.len()],
+            stacks,
+            started: false,
+        }
+    }
+
+    /// Phần tử kế tiếp, `None` khi đã hết.
+    pub async fn next_key(&mut self) -> Result<Option<(Vec<u8>, usize)>> {
+        if !self.started {
+            for si in 0..self.stacks.len() {
+                self.heads[si] = self.advance(si).await?;
+            }
+            self.started = true;
+        }
+
+        let next = self
+            .heads
+            .iter()
+            .enumerate()
+            .filter_map(|(si, head)| head.as_ref().map(|(key, _)| (si, key)))
+            .min_by(|a, b| a.1.cmp(b.1))
+            .map(|(si, _)| si);
+
+        let Some(si) = next else {
+            return Ok(None);
+        };
+
+        let item = self.heads[si].take();
+        self.heads[si] = self.advance(si).await?;
+        Ok(item)
+    }
+
+    /// Gom toàn bộ phần tử còn lại.
+    pub async fn collect_keys(mut self) -> Result<Vec<(Vec<u8>, usize)>> {
+        let mut results = Vec::new();
+        while let Some(item) = self.next_key().await? {
+            results.push(item);
+        }
+        Ok(results)
+    }
+
+    /// Đếm số phần tử còn lại mà không giữ lại key.
+    pub async fn count_keys(mut self) -> Result<usize> {
+        let mut count = 0;
+        while self.next_key().await?.is_some() {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// DFS tiếp trên stack của shard `si` tới record kế tiếp nằm trong giới hạn.
+    async fn advance(&mut self, si: usize) -> Result<Option<(Vec<u8>, usize)>> {
+        let storage = &self.tree.storage;
+
+        while let Some((node_id, parent_key)) = self.stacks[si].pop() {
+            let (prefix, record) = storage.get_node(node_id).await?;
+            let mut key = parent_key;
+            key.extend_from_slice(&prefix);
+
+            if !self.admits_subtree(&key) {
+                continue;
+            }
+
+            // Children theo thứ tự prefix (prefix rỗng = key ngắn hơn → đứng đầu);
+            // push ngược để child nhỏ nhất được pop trước
+            let mut children = Vec::new();
+            for child in storage.get_children(node_id).await? {
+                let (child_prefix, _) = storage.get_node(child).await?;
+                children.push((child_prefix, child));
+            }
+            children.sort();
+            for (_, child) in children.into_iter().rev() {
+                self.stacks[si].push((child, key.clone()));
+            }
+
+            if record != EMPTY && self.admits_key(&key) {
+                return Ok(Some((key, record)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Subtree có mọi key bắt đầu bằng `path` còn có thể chứa key hợp lệ không.
+    fn admits_subtree(&self, path: &[u8]) -> bool {
+        let compatible = path.starts_with(&self.prefix) || self.prefix.starts_with(path);
+        // path < start và không là prefix của start → mọi key bên dưới đều < start
+        let below_start = self
+            .start
+            .as_ref()
+            .is_some_and(|start| path < start.as_slice() && !start.starts_with(path));
+        // Mọi key bên dưới đều >= path
+        let past_end = self.end.as_ref().is_some_and(|end| path >= end.as_slice());
+
+        compatible && !below_start && !past_end
+    }
+
+    fn admits_key(&self, key: &[u8]) -> bool {
+        key.starts_with(&self.prefix)
+            && self
+                .start
+                .as_ref()
+                .is_none_or(|start| key >= start.as_slice())
+            && self.end.as_ref().is_none_or(|end| key < end.as_slice())
+    }
 }
 
 #[cfg(test)]
@@ -401,6 +802,234 @@ mod tests {
         assert_eq!(results[0].1, 1);
     }
 
+    #[tokio::test]
+    async fn test_match_key_ending_at_split_node() {
+        let mut tree = RadixTree::in_memory(4);
+        tree.insert(b"hello world", 1).await.unwrap();
+        tree.insert(b"hello wonder", 2).await.unwrap();
+        assert!(tree.r#match(b"hello wo").await.is_err());
+
+        // "hello wo" là node trung gian sinh ra do split
+        tree.insert(b"hello wo", 3).await.unwrap();
+        assert_eq!(tree.r#match(b"hello wo").await.unwrap(), 3);
+
+        // Key là prefix của key đã có → record nằm ở child prefix rỗng
+        tree.insert(b"hello world!", 4).await.unwrap();
+        tree.insert(b"hello world", 5).await.unwrap();
+        assert_eq!(tree.r#match(b"hello world").await.unwrap(), 1);
+        assert_eq!(tree.r#match(b"hello world!").await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_split_node_with_children() {
+        let mut tree = RadixTree::in_memory(4);
+        tree.insert(b"bao tin", 1).await.unwrap();
+        tree.insert(b"bao", 2).await.unwrap();
+
+        // Tách "bao" (đã có child " tin") tại "ba" → child phải theo leg "o"
+        tree.insert(b"bac", 3).await.unwrap();
+        assert_eq!(tree.r#match(b"bao").await.unwrap(), 2);
+        assert_eq!(tree.r#match(b"bao tin").await.unwrap(), 1);
+        assert_eq!(tree.r#match(b"bac").await.unwrap(), 3);
+        assert_eq!(tree.search_prefix(b"bao").await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_fuzzy() {
+        let mut tree = RadixTree::in_memory(4);
+        tree.insert(b"vang", 1).await.unwrap();
+        tree.insert(b"vanh", 2).await.unwrap();
+        tree.insert(b"bac", 3).await.unwrap();
+        tree.insert(b"vang bac", 4).await.unwrap();
+
+        assert_eq!(
+            tree.search_fuzzy(b"vang", 1).await.unwrap(),
+            vec![(b"vang".to_vec(), 1, 0), (b"vanh".to_vec(), 2, 1)]
+        );
+
+        // Hoán vị hai ký tự = 2 phép sửa
+        let found = tree.search_fuzzy(b"vnag", 2).await.unwrap();
+        assert_eq!(found[0], (b"vang".to_vec(), 1, 2));
+        assert!(found.iter().all(|(_, _, d)| *d <= 2));
+
+        assert_eq!(
+            tree.search_fuzzy(b"bax", 1).await.unwrap(),
+            vec![(b"bac".to_vec(), 3, 1)]
+        );
+        assert!(tree.search_fuzzy(b"xyz", 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_iter_prefix_in_order() {
+        let mut tree = RadixTree::in_memory(4);
+        let keys: [&[u8]; 8] = [
+            b"vnm", b"vn30", b"vcb", b"vnindex", b"hpg", b"vn", b"vic", b"fpt",
+        ];
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert(key, i + 1).await.unwrap();
+        }
+
+        let found = tree.iter_prefix(b"vn").collect_keys().await.unwrap();
+        assert_eq!(
+            found,
+            vec![
+                (b"vn".to_vec(), 6),
+                (b"vn30".to_vec(), 2),
+                (b"vnindex".to_vec(), 4),
+                (b"vnm".to_vec(), 1),
+            ]
+        );
+
+        // Prefix rỗng → merge mọi shard theo thứ tự
+        let all: Vec<Vec<u8>> = tree
+            .iter_prefix(b"")
+            .collect_keys()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        let mut sorted: Vec<Vec<u8>> = keys.iter().map(|k| k.to_vec()).collect();
+        sorted.sort();
+        assert_eq!(all, sorted);
+
+        assert!(
+            tree.iter_prefix(b"x")
+                .collect_keys()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            tree.iter_prefix(b"vnx")
+                .collect_keys()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_range_and_count_prefix() {
+        let mut tree = RadixTree::in_memory(4);
+        for (i, key) in ["acb", "bid", "ctg", "mbb", "tcb", "vcb", "vib", "vpb"]
+            .iter()
+            .enumerate()
+        {
+            tree.insert(key.as_bytes(), i + 1).await.unwrap();
+        }
+
+        let keys = |found: Vec<(Vec<u8>, usize)>| -> Vec<String> {
+            found
+                .into_iter()
+                .map(|(k, _)| String::from_utf8(k).unwrap())
+                .collect()
+        };
+
+        // start inclusive, end exclusive
+        assert_eq!(
+            keys(tree.range(b"bid", b"tcb").collect_keys().await.unwrap()),
+            vec!["bid", "ctg", "mbb"]
+        );
+        assert_eq!(
+            keys(tree.range(b"c", b"v").collect_keys().await.unwrap()),
+            vec!["ctg", "mbb", "tcb"]
+        );
+        assert_eq!(
+            keys(tree.range(b"vc", b"zz").collect_keys().await.unwrap()),
+            vec!["vcb", "vib", "vpb"]
+        );
+        assert!(
+            tree.range(b"x", b"y")
+                .collect_keys()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Duyệt từng phần tử
+        let mut iter = tree.range(b"v", b"w");
+        assert_eq!(iter.next_key().await.unwrap(), Some((b"vcb".to_vec(), 6)));
+        assert_eq!(iter.next_key().await.unwrap(), Some((b"vib".to_vec(), 7)));
+
+        assert_eq!(tree.count_prefix(b"v").await.unwrap(), 3);
+        assert_eq!(tree.count_prefix(b"").await.unwrap(), 8);
+        assert_eq!(tree.count_prefix(b"q").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let mut tree = RadixTree::in_memory(4);
+        tree.insert(b"hello", 1).await.unwrap();
+        assert_eq!(tree.update(b"hello", 9).await.unwrap(), 1);
+        assert_eq!(tree.r#match(b"hello").await.unwrap(), 9);
+        assert!(tree.update(b"help", 2).await.is_err());
+        assert!(tree.update(b"hello", EMPTY).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remove_prunes_and_merges() {
+        let mut tree = RadixTree::in_memory(4);
+        tree.insert(b"hello", 1).await.unwrap();
+        tree.insert(b"help", 2).await.unwrap();
+        tree.insert(b"world", 3).await.unwrap();
+
+        assert_eq!(tree.remove(b"help").await.unwrap(), 2);
+        assert!(tree.r#match(b"help").await.is_err());
+        assert_eq!(tree.r#match(b"hello").await.unwrap(), 1);
+
+        // "hel" + "lo" phải được gộp lại thành một node
+        let root = tree.endpoints[shard_of(b'h', 4)];
+        assert_eq!(tree.get_node_prefix(root).await.unwrap(), b"hello");
+        assert!(tree.get_children_ids(root).await.unwrap().is_empty());
+
+        assert_eq!(tree.remove(b"hello").await.unwrap(), 1);
+        assert_eq!(tree.endpoints[shard_of(b'h', 4)], EMPTY);
+        assert!(tree.remove(b"hello").await.is_err());
+        assert_eq!(tree.r#match(b"world").await.unwrap(), 3);
+
+        // Tree vẫn dùng được sau khi shard trống
+        tree.insert(b"help", 4).await.unwrap();
+        assert_eq!(tree.r#match(b"help").await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_remove_prefix_key_keeps_longer_key() {
+        let mut tree = RadixTree::in_memory(4);
+        tree.insert(b"hello world", 1).await.unwrap();
+        tree.insert(b"hello", 2).await.unwrap();
+
+        assert_eq!(tree.remove(b"hello").await.unwrap(), 2);
+        assert_eq!(tree.r#match(b"hello world").await.unwrap(), 1);
+        assert_eq!(
+            tree.search_prefix(b"hel").await.unwrap(),
+            vec![(b"hello world".to_vec(), 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_open_from_file_storage() {
+        let path =
+            std::env::temp_dir().join(format!("algorithm-radix-open-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        {
+            let storage = storage::file::FileStorage::open(&path).unwrap();
+            let mut tree = RadixTree::new(4, storage);
+            tree.insert(b"hello", 1).await.unwrap();
+            tree.insert(b"help", 2).await.unwrap();
+            tree.insert(b"world", 3).await.unwrap();
+        }
+
+        let storage = storage::file::FileStorage::open(&path).unwrap();
+        let tree = RadixTree::open(4, storage).await.unwrap();
+        assert_eq!(tree.r#match(b"hello").await.unwrap(), 1);
+        assert_eq!(tree.r#match(b"help").await.unwrap(), 2);
+        assert_eq!(tree.r#match(b"world").await.unwrap(), 3);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
     #[tokio::test]
     async fn test_search_prefix_empty_tree() {
         let tree = RadixTree::in_memory(2);
@@ -19,10 +19,32 @@
 //! Shortcuts được cập nhật:
 //! - Khi **insert** node mới → `update_shortcuts()`
 //! - Khi **split** node → callback `OnSplitCallback` transfer entries từ parent sang leg
+//! - Khi **merge** node (sau remove) → callback `OnMergeCallback` chuyển entries từ child về node
+//! - Khi **prune** node (sau remove) → callback `OnPruneCallback` xoá node khỏi mọi shortcut
+//!
+//! ## Text search
+//! `insert_text` index cả dạng đã chuẩn hoá (xem `normalize`) lẫn từng từ của
+//! text, mỗi key trỏ tới một posting list các `entry_id`. `search` chuẩn hoá
+//! query theo cùng cách rồi giữ lại entry khớp **mọi** từ trong query.
+//!
+//! ## Ranking
+//! Kết quả `search_like` / `search` / `search_ranked` được xếp theo điểm:
+//! exact > prefix > word-start > substring > fuzzy (Levenshtein, tuỳ chọn).
+//!
+//! ## Persistence
+//! Tree nằm trong `Storage`; mỗi entry (tên, keys, text) được ghi thêm dưới
+//! metadata key `search_index:entry:{entry_id}` sau mỗi thao tác ghi. `open`
+//! dựng lại tree từ root đã lưu, rồi tính lại posting list và shortcuts từ
+//! entries + node trong storage (hai thứ này chỉ nằm trong bộ nhớ).
 
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
+use serde::{Deserialize, Serialize};
+
+use crate::heap::TopK;
+use crate::normalize;
 use crate::radixtree::{self, EMPTY, RadixTree};
 use crate::storage::Storage;
 
@@ -64,13 +86,43 @@ pub type Result<T> = std::result::Result<T, SearchError>;
 /// Vị trí được scan trực tiếp từ prefix khi search.
 type ShortcutData = Vec<HashMap<u8, HashSet<usize>>>;
 
+// ==================== Ranking ====================
+
+const SCORE_EXACT: f64 = 4.0;
+const SCORE_PREFIX: f64 = 3.0;
+const SCORE_WORD_START: f64 = 2.0;
+const SCORE_SUBSTRING: f64 = 1.0;
+
+// ==================== Persistence ====================
+
+const ENTRY_META_PREFIX: &str = "search_index:entry:";
+
 // ==================== SearchIndex ====================
 
+/// Entry hiển thị cùng các key đang trỏ tới nó — dùng khi remove theo `entry_id`.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    name: String,
+    keys: Vec<Vec<u8>>,
+    /// Text đã chuẩn hoá (từ `insert_text`) — dùng để chấm điểm thay cho `keys`,
+    /// vì token key luôn khớp exact với từ của query.
+    text: Option<Vec<u8>>,
+}
+
 /// SearchIndex — cho phép tìm kiếm substring (LIKE) trên RadixTree.
+///
+/// Record của mỗi key trong RadixTree trỏ tới một posting list (các `entry_id`
+/// cùng được index dưới key đó), nên nhiều entry có thể dùng chung một key —
+/// vd: token "vang" của mọi tiệm vàng.
 pub struct SearchIndex {
     tree: RadixTree,
     shortcuts: Arc<Mutex<ShortcutData>>,
-    entries: Vec<(i32, String)>,
+    /// postings[record - 1]; `None` là slot đã bị remove, chờ tái sử dụng qua `free`.
+    postings: Vec<Option<Vec<i32>>>,
+    free: Vec<usize>,
+    entries: HashMap<i32, Entry>,
+    /// Entry đã đổi trong bộ nhớ nhưng chưa ghi xuống storage (xem `flush_entries`).
+    dirty: HashSet<i32>,
 }
 
 impl SearchIndex {
@@ -78,15 +130,26 @@ impl SearchIndex {
 
     /// Tạo `SearchIndex` mới với `Storage` cụ thể.
     pub fn new<S: Storage + 'static>(sharding: usize, storage: S) -> Self {
-        let sharding = sharding.max(1);
+        Self::with_tree(RadixTree::new(sharding, storage))
+    }
+
+    /// Mở lại `SearchIndex` từ storage đã có dữ liệu (vd `FileStorage` sau restart):
+    /// tree lấy từ root đã lưu, entries từ metadata, còn posting list và
+    /// shortcuts được tính lại.
+    pub async fn open<S: Storage + 'static>(sharding: usize, storage: S) -> Result<Self> {
+        let mut index = Self::with_tree(RadixTree::open(sharding, storage).await?);
+        index.restore().await?;
+        Ok(index)
+    }
+
+    /// Gắn các callback giữ shortcuts đồng bộ với `tree`.
+    fn with_tree(mut tree: RadixTree) -> Self {
         let shortcuts = Arc::new(Mutex::new(
-            (0..sharding)
+            (0..tree.sharding_count())
                 .map(|_| HashMap::<u8, HashSet<usize>>::new())
                 .collect::<Vec<_>>(),
         ));
 
-        let mut tree = RadixTree::new(sharding, storage);
-
         // Register split callback
         // 1. Thêm shortcuts cho từng byte trong leg prefix
         // 2. Xoá parent khỏi byte nào không còn trong parent prefix sau split
@@ -122,10 +185,140 @@ impl SearchIndex {
             },
         ));
 
+        // Register merge callback: node nuốt prefix của child → node chứa thêm
+        // các byte đó, child không còn tồn tại
+        let cb_shortcuts = shortcuts.clone();
+        tree.with_merge_callback(Arc::new(move |node_id, child_id, child_prefix| {
+            let mut sc = match cb_shortcuts.lock() {
+                Ok(s) => s,
+                Err(_) => return Err(radixtree::RadixError::Callback),
+            };
+
+            let sharding = sc.len();
+            for byte in child_prefix {
+                let si = radixtree::shard_of(*byte, sharding);
+                let byte_set = sc[si].entry(*byte).or_default();
+                byte_set.remove(&child_id);
+                byte_set.insert(node_id);
+            }
+
+            Self::forget_node(&mut sc, child_id);
+            Ok(())
+        }));
+
+        // Register prune callback: node bị xoá → bỏ khỏi mọi shortcut
+        let cb_shortcuts = shortcuts.clone();
+        tree.with_prune_callback(Arc::new(move |node_id, _prefix| {
+            let mut sc = match cb_shortcuts.lock() {
+                Ok(s) => s,
+                Err(_) => return Err(radixtree::RadixError::Callback),
+            };
+
+            Self::forget_node(&mut sc, node_id);
+            Ok(())
+        }));
+
         Self {
             tree,
             shortcuts,
-            entries: Vec::new(),
+            postings: Vec::new(),
+            free: Vec::new(),
+            entries: HashMap::new(),
+            dirty: HashSet::new(),
+        }
+    }
+
+    /// Dựng lại shortcuts, posting list và entries từ storage — dùng trong `open`.
+    async fn restore(&mut self) -> Result<()> {
+        // 1. Duyệt toàn bộ node: shortcuts theo prefix, chừa slot posting cho
+        //    mọi record đang được tree tham chiếu
+        let mut stack: Vec<usize> = self
+            .tree
+            .root_ids()
+            .iter()
+            .copied()
+            .filter(|&id| id != EMPTY)
+            .collect();
+        while let Some(node_id) = stack.pop() {
+            let prefix = self.tree.get_node_prefix(node_id).await?;
+            self.update_shortcuts(&prefix, 0, node_id);
+
+            let record_idx = self.tree.get_node_record(node_id).await?;
+            if record_idx != EMPTY {
+                if self.postings.len() < record_idx {
+                    self.postings.resize(record_idx, None);
+                }
+                self.postings[record_idx - 1].get_or_insert_with(Vec::new);
+            }
+
+            stack.extend(self.tree.get_children_ids(node_id).await?);
+        }
+
+        // 2. Entries từ metadata, điền lại posting list theo record của từng key
+        let stored = self
+            .tree
+            .storage()
+            .scan_meta(ENTRY_META_PREFIX)
+            .await
+            .map_err(|e| SearchError::Storage(e.to_string()))?;
+
+        let mut entries = Vec::with_capacity(stored.len());
+        for (meta_key, value) in stored {
+            let entry_id = meta_key[ENTRY_META_PREFIX.len()..]
+                .parse::<i32>()
+                .map_err(|e| SearchError::Storage(format!("{meta_key}: {e}")))?;
+            let entry: Entry = serde_json::from_slice(&value)
+                .map_err(|e| SearchError::Storage(format!("{meta_key}: {e}")))?;
+            entries.push((entry_id, entry));
+        }
+        entries.sort_by_key(|(entry_id, _)| *entry_id);
+
+        for (entry_id, entry) in entries {
+            for key in &entry.keys {
+                let record_idx = self.tree.r#match(key).await?;
+                self.posting_mut(record_idx)?.push(entry_id);
+            }
+            self.entries.insert(entry_id, entry);
+        }
+
+        self.free = (1..=self.postings.len())
+            .filter(|&record_idx| self.postings[record_idx - 1].is_none())
+            .collect();
+        Ok(())
+    }
+
+    /// Ghi các entry trong `dirty` xuống storage; entry đã bị xoá thì xoá metadata.
+    async fn flush_entries(&mut self) -> Result<()> {
+        let mut dirty: Vec<i32> = self.dirty.drain().collect();
+        dirty.sort_unstable();
+
+        for (pos, &entry_id) in dirty.iter().enumerate() {
+            let meta_key = format!("{ENTRY_META_PREFIX}{entry_id}");
+            let written = match self.entries.get(&entry_id) {
+                Some(entry) => match serde_json::to_vec(entry) {
+                    Ok(value) => self.tree.storage_mut().put_meta(&meta_key, value).await,
+                    Err(e) => Err(crate::storage::StorageError::Internal(e.to_string())),
+                },
+                None => self.tree.storage_mut().delete_meta(&meta_key).await,
+            };
+
+            if let Err(e) = written {
+                // Giữ lại phần chưa ghi để lần ghi sau thử lại
+                self.dirty.extend(&dirty[pos..]);
+                return Err(SearchError::Storage(e.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Xoá `node_id` khỏi toàn bộ shortcuts. Quét hết vì shortcut của một node
+    /// có thể gồm cả byte không còn nằm trong prefix hiện tại của nó.
+    fn forget_node(sc: &mut ShortcutData, node_id: usize) {
+        for shard in sc.iter_mut() {
+            shard.retain(|_, byte_set| {
+                byte_set.remove(&node_id);
+                !byte_set.is_empty()
+            });
         }
     }
 
@@ -141,25 +334,217 @@ impl SearchIndex {
     /// - `key` — key để search (vd: tên cửa hàng dạng byte)
     /// - `entry_id` — ID của entry (vd: store_id)
     /// - `name` — tên hiển thị
+    ///
+    /// Key đã tồn tại → entry được thêm vào posting list của key đó.
     pub async fn insert(&mut self, key: &[u8], entry_id: i32, name: &str) -> Result<()> {
+        self.insert_key(key, entry_id, name).await?;
+        self.flush_entries().await
+    }
+
+    /// `insert` nhưng chưa ghi entry xuống storage.
+    async fn insert_key(&mut self, key: &[u8], entry_id: i32, name: &str) -> Result<()> {
         if key.is_empty() {
             return Err(SearchError::NotFound);
         }
 
-        // record trong RadixTree là 1-indexed (EMPTY = 0)
-        let record_idx = self.entries.len() + 1;
-        self.entries.push((entry_id, name.to_string()));
-
-        let (new_node_id, breakpoint) = self.tree.insert(key, record_idx).await?;
+        match self.tree.r#match(key).await {
+            Ok(record_idx) => {
+                let posting = self.posting_mut(record_idx)?;
+                if !posting.contains(&entry_id) {
+                    posting.push(entry_id);
+                }
+            }
+            Err(radixtree::RadixError::NotFound) => {
+                // record trong RadixTree là 1-indexed (EMPTY = 0)
+                let record_idx = self.allocate_posting(entry_id);
+
+                let (new_node_id, breakpoint) = match self.tree.insert(key, record_idx).await {
+                    Ok(inserted) => inserted,
+                    Err(e) => {
+                        self.release_posting(record_idx);
+                        return Err(e.into());
+                    }
+                };
 
-        // Nếu tạo node mới → cập nhật shortcuts
-        if new_node_id != EMPTY {
-            self.update_shortcuts(key, breakpoint, new_node_id);
+                // Nếu tạo node mới → cập nhật shortcuts
+                if new_node_id != EMPTY {
+                    self.update_shortcuts(key, breakpoint, new_node_id);
+                }
+            }
+            Err(e) => return Err(e.into()),
         }
 
+        self.attach(key, entry_id, name);
         Ok(())
     }
 
+    /// Index `text` cho entry: dạng đã chuẩn hoá đầy đủ (bỏ dấu, lowercase) và
+    /// từng từ của nó, để `search` khớp được cả cụm lẫn từng từ riêng lẻ.
+    pub async fn insert_text(&mut self, text: &str, entry_id: i32, name: &str) -> Result<()> {
+        let keys = Self::text_keys(text);
+        if keys.is_empty() {
+            return Err(SearchError::NotFound);
+        }
+
+        for key in &keys {
+            self.insert_key(key.as_bytes(), entry_id, name).await?;
+        }
+
+        if let Some(entry) = self.entries.get_mut(&entry_id) {
+            entry.text = Some(keys[0].as_bytes().to_vec());
+        }
+        self.flush_entries().await
+    }
+
+    /// Các key cần index cho `text`: cụm đã chuẩn hoá + từng token (không lặp).
+    fn text_keys(text: &str) -> Vec<String> {
+        let mut keys = vec![normalize::normalize(text)];
+        for token in normalize::tokenize(text) {
+            if !keys.contains(&token) {
+                keys.push(token);
+            }
+        }
+        keys.retain(|key| !key.is_empty());
+        keys
+    }
+
+    // ── Update / Remove ──
+
+    /// Gán lại entry cho `key`: nếu key đã có thì thay toàn bộ posting list bằng
+    /// `entry_id`, ngược lại insert mới.
+    ///
+    /// Đổi tên (key thay đổi) = `remove(old_key)` rồi `update(new_key, ...)`.
+    pub async fn update(&mut self, key: &[u8], entry_id: i32, name: &str) -> Result<()> {
+        match self.tree.r#match(key).await {
+            Ok(record_idx) => {
+                let previous = std::mem::replace(self.posting_mut(record_idx)?, vec![entry_id]);
+                for id in previous.into_iter().filter(|id| *id != entry_id) {
+                    self.detach(key, id);
+                }
+                self.attach(key, entry_id, name);
+                self.flush_entries().await
+            }
+            Err(radixtree::RadixError::NotFound) => self.insert(key, entry_id, name).await,
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Index lại entry theo `text` mới — dùng khi đổi tên entry đã `insert_text`.
+    pub async fn update_text(&mut self, text: &str, entry_id: i32, name: &str) -> Result<()> {
+        match self.remove_entry(entry_id).await {
+            Ok(_) | Err(SearchError::NotFound) => {}
+            Err(e) => return Err(e),
+        }
+        self.insert_text(text, entry_id, name).await
+    }
+
+    /// Xoá `key` khỏi index, trả về các entry đã gắn với nó.
+    pub async fn remove(&mut self, key: &[u8]) -> Result<Vec<(i32, String)>> {
+        let record_idx = self.tree.remove(key).await?;
+        let posting = self
+            .release_posting(record_idx)
+            .ok_or(SearchError::NotFound)?;
+
+        let mut removed = Vec::with_capacity(posting.len());
+        for id in posting {
+            if let Some(entry) = self.entries.get(&id) {
+                removed.push((id, entry.name.clone()));
+            }
+            self.detach(key, id);
+        }
+        self.flush_entries().await?;
+        Ok(removed)
+    }
+
+    /// Xoá entry khỏi mọi key trỏ tới nó; key không còn entry nào bị xoá khỏi tree.
+    ///
+    /// Trả về tên hiển thị của entry.
+    pub async fn remove_entry(&mut self, entry_id: i32) -> Result<String> {
+        let entry = self
+            .entries
+            .remove(&entry_id)
+            .ok_or(SearchError::NotFound)?;
+        self.dirty.insert(entry_id);
+
+        for key in &entry.keys {
+            let record_idx = self.tree.r#match(key).await?;
+            let posting = self.posting_mut(record_idx)?;
+            posting.retain(|id| *id != entry_id);
+
+            if posting.is_empty() {
+                self.tree.remove(key).await?;
+                self.release_posting(record_idx);
+            }
+        }
+
+        self.flush_entries().await?;
+        Ok(entry.name)
+    }
+
+    /// Số entry đang còn trong index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Cấp record index (1-indexed) cho posting list mới, ưu tiên slot đã được giải phóng.
+    fn allocate_posting(&mut self, entry_id: i32) -> usize {
+        let posting = Some(vec![entry_id]);
+        match self.free.pop() {
+            Some(record_idx) => {
+                self.postings[record_idx - 1] = posting;
+                record_idx
+            }
+            None => {
+                self.postings.push(posting);
+                self.postings.len()
+            }
+        }
+    }
+
+    fn release_posting(&mut self, record_idx: usize) -> Option<Vec<i32>> {
+        let posting = self.postings.get_mut(record_idx.checked_sub(1)?)?.take()?;
+        self.free.push(record_idx);
+        Some(posting)
+    }
+
+    fn posting_mut(&mut self, record_idx: usize) -> Result<&mut Vec<i32>> {
+        record_idx
+            .checked_sub(1)
+            .and_then(|idx| self.postings.get_mut(idx))
+            .and_then(Option::as_mut)
+            .ok_or(SearchError::NotFound)
+    }
+
+    /// Ghi nhận `key` trỏ tới entry, cập nhật tên hiển thị mới nhất.
+    fn attach(&mut self, key: &[u8], entry_id: i32, name: &str) {
+        let entry = self.entries.entry(entry_id).or_insert_with(|| Entry {
+            name: String::new(),
+            keys: Vec::new(),
+            text: None,
+        });
+
+        entry.name = name.to_string();
+        if !entry.keys.iter().any(|k| k == key) {
+            entry.keys.push(key.to_vec());
+        }
+        self.dirty.insert(entry_id);
+    }
+
+    /// Bỏ `key` khỏi entry; entry không còn key nào thì bị xoá hẳn.
+    fn detach(&mut self, key: &[u8], entry_id: i32) {
+        if let Some(entry) = self.entries.get_mut(&entry_id) {
+            entry.keys.retain(|k| k != key);
+            if entry.keys.is_empty() {
+                self.entries.remove(&entry_id);
+            }
+            self.dirty.insert(entry_id);
+        }
+    }
+
     /// Cập nhật shortcuts cho một node mới: thêm node_id vào set của từng byte.
     fn update_shortcuts(&self, key: &[u8], breakpoint: usize, node_id: usize) {
         if let Ok(mut shortcuts) = self.shortcuts.lock() {
@@ -173,12 +558,37 @@ impl SearchIndex {
 
     // ── Search LIKE ──
 
-    /// Tìm kiếm substring — entries có key chứa `pattern`.
+    /// Tìm kiếm substring — entries có key chứa `pattern`, xếp theo mức độ khớp
+    /// (exact > prefix > word-start > substring, xem `score_match`).
     ///
     /// Dùng KMP + DFS, với shortcut index để tìm candidate nodes.
     ///
     /// Trả về `Vec<(entry_id, name)>`.
     pub async fn search_like(&self, pattern: &[u8], limit: usize) -> Result<Vec<(i32, String)>> {
+        let mut ranked: Vec<(i32, String, f64)> = self
+            .collect_like(pattern)
+            .await?
+            .into_iter()
+            .map(|(id, name)| {
+                let score = self
+                    .entries
+                    .get(&id)
+                    .and_then(|entry| Self::score_entry(entry, pattern))
+                    .unwrap_or(SCORE_SUBSTRING);
+                (id, name, score)
+            })
+            .collect();
+
+        Self::sort_ranked(&mut ranked);
+        Ok(ranked
+            .into_iter()
+            .take(limit)
+            .map(|(id, name, _)| (id, name))
+            .collect())
+    }
+
+    /// Toàn bộ entries có key chứa `pattern`, theo thứ tự candidate node.
+    async fn collect_like(&self, pattern: &[u8]) -> Result<Vec<(i32, String)>> {
         if pattern.is_empty() {
             return Err(SearchError::NotFound);
         }
@@ -209,18 +619,13 @@ impl SearchIndex {
         let mut seen = HashSet::new();
 
         for &node_id in &candidates {
-            if results.len() >= limit {
-                break;
-            }
-
-            let found = self.dfs_search(node_id, pattern, &lps, 0, 0, limit).await?;
+            let found = self
+                .dfs_search(node_id, pattern, &lps, 0, 0, usize::MAX)
+                .await?;
 
             for entry in found {
                 if seen.insert(entry.0) {
                     results.push(entry);
-                    if results.len() >= limit {
-                        break;
-                    }
                 }
             }
         }
@@ -232,6 +637,189 @@ impl SearchIndex {
         }
     }
 
+    // ── Search text ──
+
+    /// Tìm kiếm không phân biệt dấu/hoa thường theo từng từ — dùng với `insert_text`.
+    ///
+    /// `query` được chuẩn hoá và tách từ giống lúc index; entry phải chứa **tất
+    /// cả** các từ (mỗi từ khớp substring, không quan tâm thứ tự), vd: "chau minh"
+    /// khớp "Bảo Tín Minh Châu". Kết quả xếp như `search_ranked`.
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<(i32, String)>> {
+        Ok(self
+            .search_ranked(query, limit, 0)
+            .await?
+            .into_iter()
+            .map(|(id, name, _)| (id, name))
+            .collect())
+    }
+
+    /// Như `search` nhưng trả kèm điểm: `Vec<(entry_id, name, score)>`, điểm giảm dần.
+    ///
+    /// - Điểm entry = max(điểm của cả cụm query, trung bình điểm từng từ).
+    /// - `max_distance > 0` bật fuzzy: từ không khớp substring vẫn được nhận nếu
+    ///   cách một key trong tree không quá `max_distance` phép sửa (Levenshtein),
+    ///   với điểm luôn thấp hơn mọi kết quả khớp substring.
+    pub async fn search_ranked(
+        &self,
+        query: &str,
+        limit: usize,
+        max_distance: usize,
+    ) -> Result<Vec<(i32, String, f64)>> {
+        let phrase = normalize::normalize(query);
+        let tokens = normalize::tokenize(query);
+        if tokens.is_empty() {
+            return Err(SearchError::NotFound);
+        }
+
+        // entry_id → tổng điểm các từ; từ sau chỉ giữ lại entry đã khớp các từ trước
+        let mut totals: Option<HashMap<i32, f64>> = None;
+        for token in &tokens {
+            let scores = self.score_token(token.as_bytes(), max_distance).await?;
+            totals = Some(match totals {
+                None => scores,
+                Some(prev) => prev
+                    .into_iter()
+                    .filter_map(|(id, total)| scores.get(&id).map(|score| (id, total + score)))
+                    .collect(),
+            });
+        }
+
+        // @NOTE: chỉ giữ `limit` kết quả tốt nhất thay vì sort toàn bộ
+        let count = tokens.len() as f64;
+        let mut found = false;
+        let mut ranked = TopK::new(limit, Self::compare_ranked);
+        for (id, total) in totals.unwrap_or_default() {
+            let Some(entry) = self.entries.get(&id) else {
+                continue;
+            };
+            let phrase_score = Self::score_entry(entry, phrase.as_bytes()).unwrap_or(0.0);
+
+            found = true;
+            ranked.push((id, entry.name.clone(), phrase_score.max(total / count)));
+        }
+
+        if !found {
+            return Err(SearchError::NotFound);
+        }
+        Ok(ranked.into_sorted_vec())
+    }
+
+    /// Điểm tốt nhất của từng entry cho một từ của query.
+    async fn score_token(&self, token: &[u8], max_distance: usize) -> Result<HashMap<i32, f64>> {
+        let mut scores: HashMap<i32, f64> = HashMap::new();
+
+        match self.collect_like(token).await {
+            Ok(found) => {
+                for (id, _) in found {
+                    let score = self
+                        .entries
+                        .get(&id)
+                        .and_then(|entry| Self::score_entry(entry, token))
+                        .unwrap_or(SCORE_SUBSTRING);
+                    scores.insert(id, score);
+                }
+            }
+            Err(SearchError::NotFound) => {}
+            Err(e) => return Err(e),
+        }
+
+        if max_distance > 0 {
+            match self.tree.search_fuzzy(token, max_distance).await {
+                Ok(found) => {
+                    for (_, record_idx, distance) in found {
+                        let score = Self::score_fuzzy(distance, max_distance);
+                        let Some(Some(posting)) = self.postings.get(record_idx - 1) else {
+                            continue;
+                        };
+
+                        for &id in posting {
+                            let best = scores.entry(id).or_insert(score);
+                            *best = best.max(score);
+                        }
+                    }
+                }
+                Err(radixtree::RadixError::NotFound) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if scores.is_empty() {
+            Err(SearchError::NotFound)
+        } else {
+            Ok(scores)
+        }
+    }
+
+    // ── Ranking ──
+
+    /// Điểm của `pattern` trên text/keys của entry (lấy key khớp tốt nhất).
+    fn score_entry(entry: &Entry, pattern: &[u8]) -> Option<f64> {
+        match &entry.text {
+            Some(text) => Self::score_match(pattern, text),
+            None => entry
+                .keys
+                .iter()
+                .filter_map(|key| Self::score_match(pattern, key))
+                .reduce(f64::max),
+        }
+    }
+
+    /// Chấm điểm `pattern` khớp trong `text`: hạng khớp + tối đa 0.5 theo tỉ lệ
+    /// độ dài (text càng sát pattern càng cao), nên các hạng không chồng lên nhau:
+    ///
+    /// ```text
+    /// exact      [4.0, 4.5]   text == pattern
+    /// prefix     [3.0, 3.5)   text bắt đầu bằng pattern
+    /// word-start [2.0, 2.5)   pattern bắt đầu ngay sau khoảng trắng/dấu câu
+    /// substring  [1.0, 1.5)   còn lại
+    /// fuzzy      (0.0, 1.0)   xem `score_fuzzy`
+    /// ```
+    fn score_match(pattern: &[u8], text: &[u8]) -> Option<f64> {
+        if pattern.is_empty() || pattern.len() > text.len() {
+            return None;
+        }
+
+        let tier = if text == pattern {
+            SCORE_EXACT
+        } else if text.starts_with(pattern) {
+            SCORE_PREFIX
+        } else {
+            let mut tier = None;
+            for (pos, window) in text.windows(pattern.len()).enumerate() {
+                if window != pattern {
+                    continue;
+                }
+
+                let prev = text[pos - 1];
+                if prev.is_ascii_whitespace() || prev.is_ascii_punctuation() {
+                    tier = Some(SCORE_WORD_START);
+                    break;
+                }
+                tier = Some(SCORE_SUBSTRING);
+            }
+            tier?
+        };
+
+        Some(tier + 0.5 * pattern.len() as f64 / text.len() as f64)
+    }
+
+    /// Điểm cho key cách `distance` phép sửa: luôn nằm trong (0, 1), dưới mọi
+    /// kết quả khớp substring.
+    fn score_fuzzy(distance: usize, max_distance: usize) -> f64 {
+        SCORE_SUBSTRING * (1.0 - distance as f64 / (max_distance + 1) as f64)
+    }
+
+    /// Điểm giảm dần; bằng điểm thì tên ngắn hơn, rồi `entry_id` nhỏ hơn đứng trước.
+    fn sort_ranked(ranked: &mut [(i32, String, f64)]) {
+        ranked.sort_by(Self::compare_ranked);
+    }
+
+    fn compare_ranked(a: &(i32, String, f64), b: &(i32, String, f64)) -> Ordering {
+        b.2.total_cmp(&a.2)
+            .then_with(|| a.1.len().cmp(&b.1.len()))
+            .then_with(|| a.0.cmp(&b.0))
+    }
+
     // ── KMP: LPS array ──
 
     /// Build Longest Proper Prefix which is also Suffix (LPS) array.
@@ -388,7 +976,7 @@ impl SearchIndex {
         Ok(records)
     }
 
-    /// Chuyển đổi record IDs (1-indexed) thành entries.
+    /// Chuyển đổi record IDs (1-indexed) thành entries qua posting list.
     fn resolve_records(&self, record_ids: &[usize], limit: usize) -> Vec<(i32, String)> {
         let mut results = Vec::new();
         let mut seen = HashSet::new();
@@ -397,10 +985,18 @@ impl SearchIndex {
                 continue;
             }
             let idx = rid - 1; // 1-indexed → 0-indexed
-            if idx < self.entries.len() && seen.insert(idx) {
-                results.push(self.entries[idx].clone());
-                if results.len() >= limit {
-                    break;
+            let Some(Some(posting)) = self.postings.get(idx) else {
+                continue;
+            };
+
+            for &id in posting {
+                if let Some(entry) = self.entries.get(&id)
+                    && seen.insert(id)
+                {
+                    results.push((id, entry.name.clone()));
+                    if results.len() >= limit {
+                        return results;
+                    }
                 }
             }
         }
@@ -521,17 +1117,17 @@ mod tests {
     async fn test_insert_duplicate_key() {
         let mut idx = SearchIndex::in_memory(4);
         idx.insert(b"hello", 1, "Hello").await.unwrap();
-        // Insert same key again — RadixTree trả về (EMPTY, tail)
-        // vì key đã tồn tại, không tạo node mới.
-        // SearchIndex vẫn append entries, nhưng tree node trỏ đến
-        // record cũ. Hành vi này OK vì duplicate key không phải use-case
-        // chính của SearchIndex.
+        // Insert same key again — key đã tồn tại nên không tạo node mới,
+        // entry mới được thêm vào posting list của record cũ.
         let res = idx.insert(b"hello", 2, "Hello Again").await;
         // Insert vẫn thành công (RadixError không xảy ra)
         assert!(res.is_ok());
 
         let results = idx.search_like(b"hello", 10).await.unwrap();
-        assert!(!results.is_empty());
+        assert_eq!(
+            results,
+            vec![(1, "Hello".into()), (2, "Hello Again".into())]
+        );
     }
 
     #[tokio::test]
@@ -565,6 +1161,357 @@ mod tests {
         assert_eq!(results[0].0, 2);
     }
 
+    #[tokio::test]
+    async fn test_remove_entry() {
+        let mut idx = SearchIndex::in_memory(4);
+        idx.insert(b"hello", 1, "Hello").await.unwrap();
+        idx.insert(b"help", 2, "Help").await.unwrap();
+        idx.insert(b"held", 3, "Held").await.unwrap();
+
+        assert_eq!(idx.remove(b"help").await.unwrap(), vec![(2, "Help".into())]);
+        assert_eq!(idx.len(), 2);
+        assert!(idx.remove(b"help").await.is_err());
+
+        let ids: Vec<i32> = idx
+            .search_like(b"hel", 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids.len(), 2);
+        assert!(!ids.contains(&2));
+        assert!(idx.search_like(b"lp", 10).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remove_merges_and_keeps_shortcuts() {
+        let mut idx = SearchIndex::in_memory(4);
+        idx.insert(b"test_abaadata_one", 1, "First").await.unwrap();
+        idx.insert(b"test_other_route", 2, "Second").await.unwrap();
+
+        // Xoá nhánh "other_route" → "test_" gộp lại với "abaadata_one"
+        idx.remove(b"test_other_route").await.unwrap();
+
+        let results = idx.search_like(b"st_ab", 10).await.unwrap();
+        assert_eq!(results, vec![(1, "First".into())]);
+        assert!(idx.search_like(b"route", 10).await.is_err());
+
+        // Không còn node nào bị xoá nằm trong shortcuts
+        let sc = idx.shortcuts.lock().unwrap();
+        for shard in sc.iter() {
+            for set in shard.values() {
+                assert!(!set.is_empty());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_then_reinsert() {
+        let mut idx = SearchIndex::in_memory(4);
+        idx.insert(b"ab", 1, "AB").await.unwrap();
+        idx.insert(b"abcde", 2, "ABCDE").await.unwrap();
+        idx.insert(b"abcdef", 3, "ABCDEF").await.unwrap();
+
+        // "abcde" là node trung gian có record → chỉ bỏ record, không prune
+        idx.remove(b"abcde").await.unwrap();
+        assert_eq!(
+            idx.search_like(b"bcde", 10).await.unwrap(),
+            vec![(3, "ABCDEF".into())]
+        );
+
+        idx.insert(b"abcde", 4, "ABCDE again").await.unwrap();
+        let ids: Vec<i32> = idx
+            .search_like(b"bcde", 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert!(ids.contains(&3));
+        assert!(ids.contains(&4));
+
+        // Slot cũ được tái sử dụng
+        assert_eq!(idx.postings.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_remove_last_key_empties_shard() {
+        let mut idx = SearchIndex::in_memory(1);
+        idx.insert(b"only", 1, "Only").await.unwrap();
+        idx.remove(b"only").await.unwrap();
+
+        assert!(idx.is_empty());
+        assert!(idx.search_like(b"on", 10).await.is_err());
+        assert!(idx.shortcuts.lock().unwrap()[0].is_empty());
+
+        idx.insert(b"other", 2, "Other").await.unwrap();
+        assert_eq!(
+            idx.search_like(b"th", 10).await.unwrap(),
+            vec![(2, "Other".into())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_entry() {
+        let mut idx = SearchIndex::in_memory(4);
+        idx.insert(b"tiem vang", 1, "Tiệm Vàng").await.unwrap();
+
+        // Key đã có → cập nhật tại chỗ
+        idx.update(b"tiem vang", 1, "Tiệm Vàng Mới").await.unwrap();
+        assert_eq!(
+            idx.search_like(b"vang", 10).await.unwrap(),
+            vec![(1, "Tiệm Vàng Mới".into())]
+        );
+
+        // Đổi tên: remove key cũ, update key mới
+        idx.remove(b"tiem vang").await.unwrap();
+        idx.update(b"tiem bac", 1, "Tiệm Bạc").await.unwrap();
+        assert!(idx.search_like(b"vang", 10).await.is_err());
+        assert_eq!(
+            idx.search_like(b"bac", 10).await.unwrap(),
+            vec![(1, "Tiệm Bạc".into())]
+        );
+        assert_eq!(idx.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_text_ignores_diacritics() {
+        let mut idx = SearchIndex::in_memory(26);
+        idx.insert_text("Bảo Tín Minh Châu", 1, "Bảo Tín Minh Châu")
+            .await
+            .unwrap();
+        idx.insert_text("Vàng Bạc Đá Quý Sài Gòn", 2, "Vàng Bạc Đá Quý Sài Gòn")
+            .await
+            .unwrap();
+
+        for query in ["bao tin minh chau", "Bảo Tín", "BAO TIN", "minh chau"] {
+            assert_eq!(
+                idx.search(query, 10).await.unwrap(),
+                vec![(1, "Bảo Tín Minh Châu".into())],
+                "query {query:?}"
+            );
+        }
+
+        assert_eq!(idx.search("da quy", 10).await.unwrap()[0].0, 2);
+        assert!(idx.search("ha noi", 10).await.is_err());
+        assert!(idx.search(" - ", 10).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_text_requires_all_tokens() {
+        let mut idx = SearchIndex::in_memory(26);
+        let stores = [
+            (1, "Tiệm Vàng Minh Châu"),
+            (2, "Bảo Tín Minh Châu"),
+            (3, "Tiệm Vàng Kim Thành"),
+        ];
+        for (id, name) in stores {
+            idx.insert_text(name, id, name).await.unwrap();
+        }
+
+        // Đảo thứ tự từ vẫn khớp
+        let ids =
+            |results: Vec<(i32, String)>| results.into_iter().map(|(id, _)| id).collect::<Vec<_>>();
+        assert_eq!(ids(idx.search("chau minh", 10).await.unwrap()).len(), 2);
+        assert_eq!(ids(idx.search("chau vang", 10).await.unwrap()), vec![1]);
+        assert_eq!(ids(idx.search("tiem thanh", 10).await.unwrap()), vec![3]);
+        assert!(idx.search("kim chau", 10).await.is_err());
+        assert_eq!(idx.search("tiem", 1).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_remove_entry_keeps_shared_tokens() {
+        let mut idx = SearchIndex::in_memory(26);
+        idx.insert_text("Tiệm Vàng An Phát", 1, "Tiệm Vàng An Phát")
+            .await
+            .unwrap();
+        idx.insert_text("Tiệm Vàng Hồng Phát", 2, "Tiệm Vàng Hồng Phát")
+            .await
+            .unwrap();
+
+        assert_eq!(idx.remove_entry(1).await.unwrap(), "Tiệm Vàng An Phát");
+        assert_eq!(idx.len(), 1);
+        assert!(idx.remove_entry(1).await.is_err());
+
+        // Token dùng chung vẫn còn cho entry 2, token riêng của entry 1 thì mất
+        assert_eq!(
+            idx.search("tiem vang phat", 10).await.unwrap(),
+            vec![(2, "Tiệm Vàng Hồng Phát".into())]
+        );
+        // "an" vẫn khớp substring của "vang" nhưng chỉ còn entry 2
+        assert_eq!(
+            idx.search("an phat", 10).await.unwrap(),
+            vec![(2, "Tiệm Vàng Hồng Phát".into())]
+        );
+
+        // Đổi tên qua update_text
+        idx.update_text("Tiệm Vàng Hồng Đức", 2, "Tiệm Vàng Hồng Đức")
+            .await
+            .unwrap();
+        assert!(idx.search("phat", 10).await.is_err());
+        assert_eq!(idx.search("hong duc", 10).await.unwrap()[0].0, 2);
+
+        idx.remove_entry(2).await.unwrap();
+        assert!(idx.is_empty());
+        assert!(idx.postings.iter().all(Option::is_none));
+    }
+
+    #[tokio::test]
+    async fn test_search_like_ranks_exact_prefix_word_substring() {
+        let mut idx = SearchIndex::in_memory(4);
+        // Insert theo thứ tự ngược với thứ hạng mong muốn
+        idx.insert(b"hoangvang", 1, "Substring").await.unwrap();
+        idx.insert(b"tiem vang", 2, "Word start").await.unwrap();
+        idx.insert(b"vang bac", 3, "Prefix").await.unwrap();
+        idx.insert(b"vang", 4, "Exact").await.unwrap();
+
+        let ids: Vec<i32> = idx
+            .search_like(b"vang", 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids, vec![4, 3, 2, 1]);
+
+        // limit cắt sau khi đã xếp hạng
+        assert_eq!(
+            idx.search_like(b"vang", 1).await.unwrap(),
+            vec![(4, "Exact".into())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_ranked_scores() {
+        let mut idx = SearchIndex::in_memory(26);
+        let stores = [
+            (1, "Tiệm Vàng Minh Châu"),
+            (2, "Vàng Bạc Minh Châu"),
+            (3, "Minh Châu"),
+        ];
+        for (id, name) in stores {
+            idx.insert_text(name, id, name).await.unwrap();
+        }
+
+        let ranked = idx.search_ranked("minh chau", 10, 0).await.unwrap();
+        let ids: Vec<i32> = ranked.iter().map(|(id, _, _)| *id).collect();
+        assert_eq!(ids, vec![3, 2, 1]);
+        assert!(ranked[0].2 >= SCORE_EXACT);
+        assert!(ranked[1].2 >= SCORE_WORD_START && ranked[1].2 < SCORE_PREFIX);
+
+        assert_eq!(
+            idx.search_ranked("vang minh", 10, 0).await.unwrap().len(),
+            2
+        );
+
+        // Đúng thứ tự từ xếp trên đảo thứ tự
+        let score_of =
+            |ranked: Vec<(i32, String, f64)>| ranked.iter().find(|r| r.0 == 3).unwrap().2;
+        let reversed = score_of(idx.search_ranked("chau minh", 10, 0).await.unwrap());
+        let in_order = score_of(idx.search_ranked("minh chau", 10, 0).await.unwrap());
+        assert!(reversed < in_order);
+
+        // `limit` không giới hạn vẫn chạy, không đặt trước bộ nhớ theo `limit`
+        assert_eq!(
+            idx.search_ranked("minh chau", usize::MAX, 0)
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_ranked_fuzzy() {
+        let mut idx = SearchIndex::in_memory(26);
+        idx.insert_text("Bảo Tín Minh Châu", 1, "Bảo Tín Minh Châu")
+            .await
+            .unwrap();
+        idx.insert_text("Tiệm Vàng Kim Thành", 2, "Tiệm Vàng Kim Thành")
+            .await
+            .unwrap();
+
+        // Gõ sai "minh" → "mihn": không khớp nếu tắt fuzzy
+        assert!(idx.search_ranked("mihn chau", 10, 0).await.is_err());
+
+        let ranked = idx.search_ranked("mihn chau", 10, 2).await.unwrap();
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].0, 1);
+
+        // Kết quả fuzzy luôn dưới kết quả khớp substring
+        let ranked = idx.search_ranked("thanh", 10, 1).await.unwrap();
+        assert_eq!(ranked[0].0, 2);
+        assert!(ranked[0].2 >= SCORE_SUBSTRING);
+        let typo = idx.search_ranked("thamh", 10, 1).await.unwrap();
+        assert_eq!(typo[0].0, 2);
+        assert!(typo[0].2 < SCORE_SUBSTRING);
+    }
+
+    #[tokio::test]
+    async fn test_open_restores_file_index() {
+        use crate::storage::file::FileStorage;
+
+        let path =
+            std::env::temp_dir().join(format!("search_index_reopen_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let stores = [
+            (1, "Tiệm Vàng An Phát"),
+            (2, "Tiệm Vàng Hồng Phát"),
+            (3, "Bảo Tín Minh Châu"),
+            (4, "Vàng Bạc Minh Châu"),
+        ];
+
+        async fn snapshot(idx: &SearchIndex) -> Vec<Vec<(i32, String)>> {
+            let mut out = Vec::new();
+            for query in ["vang", "phat", "minh chau", "an phat", "bao tin"] {
+                out.push(idx.search(query, 10).await.unwrap_or_default());
+            }
+            out.push(idx.search_like(b"minh", 10).await.unwrap_or_default());
+            out.push(
+                idx.search_ranked("mihn", 10, 1)
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(id, name, _)| (id, name))
+                    .collect(),
+            );
+            out
+        }
+
+        let before = {
+            let mut idx = SearchIndex::new(26, FileStorage::open(&path).unwrap());
+            for (id, name) in stores {
+                idx.insert_text(name, id, name).await.unwrap();
+            }
+            idx.remove_entry(2).await.unwrap();
+            idx.update_text("Bảo Tín Mạnh Hải", 3, "Bảo Tín Mạnh Hải")
+                .await
+                .unwrap();
+            snapshot(&idx).await
+        };
+
+        let mut idx = SearchIndex::open(26, FileStorage::open(&path).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(before[0].len(), 2, "fixture phải có kết quả để so sánh");
+        assert_eq!(idx.len(), 3);
+        assert_eq!(snapshot(&idx).await, before);
+        assert!(idx.search("hong phat", 10).await.is_err());
+
+        // Index mở lại vẫn ghi tiếp được: slot posting đã giải phóng được tái sử dụng
+        idx.insert_text("Tiệm Vàng Kim Thành", 5, "Tiệm Vàng Kim Thành")
+            .await
+            .unwrap();
+        let found = idx.search("kim thanh", 10).await.unwrap();
+        assert_eq!(found, vec![(5, "Tiệm Vàng Kim Thành".to_string())]);
+        assert_eq!(snapshot(&idx).await[1], before[1]);
+
+        drop(idx);
+        let _ = std::fs::remove_file(&path);
+    }
+
     // ==================== Benchmarks ====================
 
     #[tokio::test]
@@ -1,25 +1,100 @@
+//! Snowflake id 64-bit: `0 | timestamp(40) | sequence(12) | machine_id(10)`.
+//!
+//! `timestamp` tính bằng ms kể từ `start_time`. Mỗi replica cần một `machine_id`
+//! riêng — cấu hình tay hoặc xin lease qua `MachineLease` để các gateway chạy
+//! song song không bao giờ dùng chung id.
+
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use rand::Rng;
+use thiserror::Error;
+
+const MACHINE_BITS: u64 = 10;
+const SEQUENCE_BITS: u64 = 12;
+const TIMESTAMP_BITS: u64 = 40;
+
+const MACHINE_MASK: u64 = (1 << MACHINE_BITS) - 1;
+const SEQUENCE_MASK: u64 = (1 << SEQUENCE_BITS) - 1;
+const TIMESTAMP_MASK: u64 = (1 << TIMESTAMP_BITS) - 1;
+
+pub const MAX_MACHINE_ID: u16 = MACHINE_MASK as u16;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SnowflakeError {
+    #[error("machine_id {0} quá lớn, chỉ dùng 0..1023")]
+    InvalidMachineId(u16),
+    #[error("clock moved backwards by {0}ms")]
+    ClockMovedBackwards(u64),
+    #[error("lease of machine_id {0} is lost")]
+    LeaseLost(u16),
+    #[error("no machine_id available")]
+    NoMachineIdAvailable,
+    #[error("lease store error: {0}")]
+    Store(String),
+}
+
+/// Cách xử lý khi đồng hồ hệ thống lùi về trước timestamp đã cấp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClockBackwards {
+    /// Ngủ (chặn thread) cho tới khi đồng hồ đuổi kịp, lỗi nếu phải chờ quá lâu.
+    Wait(Duration),
+    /// Trả `SnowflakeError::ClockMovedBackwards` ngay.
+    Error,
+    /// Tiếp tục trên timestamp logic cũ, hết sequence thì mượn ms kế tiếp. Id vẫn
+    /// tăng dần nhưng có thể đi trước đồng hồ thật một chút.
+    #[default]
+    Borrow,
+}
 
 pub struct SnowflakeId {
     start_time: u64,
     machine_id: u16,
     state: AtomicU64,
+    clock_backwards: ClockBackwards,
+    lease: Option<Arc<MachineLease>>,
 }
 
 impl SnowflakeId {
     pub fn new(machine_id: u16, start_time: u64) -> Self {
-        if machine_id >= 1 << 10 {
-            panic!("machine_id quá lớn, chỉ dùng 0..1023");
+        Self::try_new(machine_id, start_time)
+            .unwrap_or_else(|_| panic!("machine_id quá lớn, chỉ dùng 0..1023"))
+    }
+
+    pub fn try_new(machine_id: u16, start_time: u64) -> Result<Self, SnowflakeError> {
+        if machine_id > MAX_MACHINE_ID {
+            return Err(SnowflakeError::InvalidMachineId(machine_id));
         }
 
-        Self {
+        Ok(Self {
             start_time,
             machine_id,
             state: AtomicU64::new(0),
+            clock_backwards: ClockBackwards::default(),
+            lease: None,
+        })
+    }
+
+    /// Dùng `machine_id` của lease; `try_generate` trả lỗi khi lease đã mất.
+    pub fn with_lease(lease: Arc<MachineLease>, start_time: u64) -> Self {
+        Self {
+            start_time,
+            machine_id: lease.machine_id(),
+            state: AtomicU64::new(0),
+            clock_backwards: ClockBackwards::default(),
+            lease: Some(lease),
         }
     }
 
+    pub fn with_clock_backwards(mut self, policy: ClockBackwards) -> Self {
+        self.clock_backwards = policy;
+        self
+    }
+
     pub fn get_machine_id(&self) -> u16 {
         self.machine_id
     }
@@ -28,43 +103,361 @@ impl SnowflakeId {
         self.start_time
     }
 
+    /// Tách id thành `(timestamp unix ms, machine_id, sequence)`.
+    pub fn decode(&self, id: i64) -> (u64, u16, u16) {
+        let id = id as u64;
+        (
+            self.start_time + ((id >> (SEQUENCE_BITS + MACHINE_BITS)) & TIMESTAMP_MASK),
+            (id & MACHINE_MASK) as u16,
+            ((id >> MACHINE_BITS) & SEQUENCE_MASK) as u16,
+        )
+    }
+
+    /// Như `try_generate`, panic khi lỗi (chỉ xảy ra với `ClockBackwards::Error`,
+    /// `ClockBackwards::Wait` hoặc khi dùng lease).
     pub fn generate(&self) -> i64 {
+        self.try_generate()
+            .unwrap_or_else(|error| panic!("Generate snowflake id failed: {error}"))
+    }
+
+    pub fn try_generate(&self) -> Result<i64, SnowflakeError> {
+        if let Some(lease) = &self.lease
+            && !lease.is_valid()
+        {
+            return Err(SnowflakeError::LeaseLost(self.machine_id));
+        }
+
         loop {
             let now_ms = SystemTime::now()
                 .duration_since(UNIX_EPOCH)
-                .expect("Clock moved backwards")
+                .unwrap_or_default()
                 .as_millis() as u64;
 
             let current_ts = now_ms.saturating_sub(self.start_time);
             let old_state = self.state.load(Ordering::Acquire);
 
-            let old_ts = old_state >> 12;
-            let old_seq = old_state & 0xFFF;
+            let old_ts = old_state >> SEQUENCE_BITS;
+            let old_seq = old_state & SEQUENCE_MASK;
 
             let (new_ts, new_seq) = if current_ts > old_ts {
                 (current_ts, 0)
             } else {
-                let next_seq = (old_seq + 1) & 0xFFF;
-                if next_seq == 0 {
+                let behind = old_ts - current_ts;
+                if behind > 0 {
+                    match self.clock_backwards {
+                        ClockBackwards::Error => {
+                            return Err(SnowflakeError::ClockMovedBackwards(behind));
+                        }
+                        ClockBackwards::Wait(max) if Duration::from_millis(behind) > max => {
+                            return Err(SnowflakeError::ClockMovedBackwards(behind));
+                        }
+                        ClockBackwards::Wait(_) => {
+                            std::thread::sleep(Duration::from_millis(behind));
+                            continue;
+                        }
+                        ClockBackwards::Borrow => {}
+                    }
+                }
+
+                let next_seq = (old_seq + 1) & SEQUENCE_MASK;
+                if next_seq != 0 {
+                    (old_ts, next_seq)
+                } else if behind > 0 {
+                    // @NOTE: đồng hồ đang lùi, chờ tới ms kế tiếp có thể rất lâu
+                    (old_ts + 1, 0)
+                } else {
                     continue;
                 }
-                (old_ts, next_seq)
             };
 
-            let new_state = (new_ts << 12) | new_seq;
+            let new_state = (new_ts << SEQUENCE_BITS) | new_seq;
 
             if self
                 .state
                 .compare_exchange_weak(old_state, new_state, Ordering::AcqRel, Ordering::Acquire)
                 .is_ok()
             {
-                let id = ((new_ts & 0xFFFFFFFFFF) << 22)
-                    | ((new_seq & 0xFFF) << 10)
-                    | (self.machine_id as u64 & 0x3FF);
+                let id = ((new_ts & TIMESTAMP_MASK) << (SEQUENCE_BITS + MACHINE_BITS))
+                    | ((new_seq & SEQUENCE_MASK) << MACHINE_BITS)
+                    | (self.machine_id as u64 & MACHINE_MASK);
+
+                return Ok(id as i64);
+            }
+        }
+    }
+}
+
+// ==================== Machine id lease ====================
+
+/// Nơi lưu lease machine id dùng chung giữa các replica.
+#[async_trait]
+pub trait LeaseStore: Send + Sync {
+    /// Giữ `machine_id` cho `owner` trong `ttl` nếu chưa ai giữ (hoặc đã là của
+    /// `owner`). Trả `false` nếu replica khác đang giữ.
+    async fn try_acquire(
+        &self,
+        machine_id: u16,
+        owner: &str,
+        ttl: Duration,
+    ) -> Result<bool, SnowflakeError>;
+
+    /// Gia hạn lease, `false` nếu `owner` không còn giữ `machine_id`.
+    async fn renew(
+        &self,
+        machine_id: u16,
+        owner: &str,
+        ttl: Duration,
+    ) -> Result<bool, SnowflakeError>;
+
+    /// Trả lại lease nếu `owner` vẫn đang giữ.
+    async fn release(&self, machine_id: u16, owner: &str) -> Result<(), SnowflakeError>;
+}
+
+/// Lease trong bộ nhớ, dùng cho test hoặc nhiều generator trong cùng process.
+#[derive(Default)]
+pub struct InMemoryLeaseStore {
+    leases: Mutex<HashMap<u16, (String, Instant)>>,
+}
+
+#[async_trait]
+impl LeaseStore for InMemoryLeaseStore {
+    async fn try_acquire(
+        &self,
+        machine_id: u16,
+        owner: &str,
+        ttl: Duration,
+    ) -> Result<bool, SnowflakeError> {
+        let mut leases = self.leases.lock();
+        let now = Instant::now();
+
+        match leases.get(&machine_id) {
+            Some((holder, deadline)) if holder != owner && *deadline > now => Ok(false),
+            _ => {
+                leases.insert(machine_id, (owner.to_string(), now + ttl));
+                Ok(true)
+            }
+        }
+    }
+
+    async fn renew(
+        &self,
+        machine_id: u16,
+        owner: &str,
+        ttl: Duration,
+    ) -> Result<bool, SnowflakeError> {
+        let now = Instant::now();
+
+        match self.leases.lock().get_mut(&machine_id) {
+            Some((holder, deadline)) if holder == owner && *deadline > now => {
+                *deadline = now + ttl;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release(&self, machine_id: u16, owner: &str) -> Result<(), SnowflakeError> {
+        let mut leases = self.leases.lock();
+        if leases
+            .get(&machine_id)
+            .is_some_and(|(holder, _)| holder == owner)
+        {
+            leases.remove(&machine_id);
+        }
+        Ok(())
+    }
+}
+
+/// Một machine id đang được giữ qua `LeaseStore`.
+///
+/// Hạn của lease được tính từ lúc *gửi* yêu cầu nên luôn hết sớm hơn phía store:
+/// sau khi hết hạn, `SnowflakeId` ngừng cấp id thay vì đụng id với replica
+/// vừa lấy lại machine id đó.
+pub struct MachineLease {
+    machine_id: u16,
+    owner: String,
+    ttl: Duration,
+    store: Arc<dyn LeaseStore>,
+    epoch: Instant,
+    /// Hạn lease (nanos tính từ `epoch`), `0` = đã mất.
+    deadline: AtomicU64,
+}
+
+impl MachineLease {
+    /// Xin machine id đầu tiên còn trống, bắt đầu từ một vị trí ngẫu nhiên để các
+    /// replica khởi động cùng lúc ít tranh nhau.
+    pub async fn acquire(
+        store: Arc<dyn LeaseStore>,
+        owner: &str,
+        ttl: Duration,
+    ) -> Result<Self, SnowflakeError> {
+        let epoch = Instant::now();
+        let offset = rand::thread_rng().gen_range(0..=MAX_MACHINE_ID);
+
+        for i in 0..=MAX_MACHINE_ID {
+            let machine_id = (offset + i) % (MAX_MACHINE_ID + 1);
+            let started = epoch.elapsed();
+
+            if store.try_acquire(machine_id, owner, ttl).await? {
+                return Ok(Self {
+                    machine_id,
+                    owner: owner.to_string(),
+                    ttl,
+                    store,
+                    epoch,
+                    deadline: AtomicU64::new((started + ttl).as_nanos() as u64),
+                });
+            }
+        }
+
+        Err(SnowflakeError::NoMachineIdAvailable)
+    }
+
+    pub fn machine_id(&self) -> u16 {
+        self.machine_id
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (self.epoch.elapsed().as_nanos() as u64) < self.deadline.load(Ordering::Acquire)
+    }
+
+    pub async fn renew(&self) -> Result<(), SnowflakeError> {
+        let started = self.epoch.elapsed();
+
+        if self
+            .store
+            .renew(self.machine_id, &self.owner, self.ttl)
+            .await?
+        {
+            self.deadline
+                .store((started + self.ttl).as_nanos() as u64, Ordering::Release);
+            Ok(())
+        } else {
+            self.deadline.store(0, Ordering::Release);
+            Err(SnowflakeError::LeaseLost(self.machine_id))
+        }
+    }
+
+    /// Gia hạn mỗi `ttl / 3` tới khi mất lease, trả về lỗi làm mất lease. Lỗi
+    /// tạm thời của store được thử lại miễn là lease còn hạn.
+    pub async fn keep_alive(&self) -> SnowflakeError {
+        loop {
+            tokio::time::sleep(self.ttl / 3).await;
+
+            match self.renew().await {
+                Ok(()) => {}
+                Err(SnowflakeError::Store(_)) if self.is_valid() => {}
+                Err(error) => return error,
+            }
+        }
+    }
+
+    pub async fn release(&self) -> Result<(), SnowflakeError> {
+        self.deadline.store(0, Ordering::Release);
+        self.store.release(self.machine_id, &self.owner).await
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use self::redis::RedisLeaseStore;
+
+#[cfg(feature = "redis")]
+mod redis {
+    //! Lease machine id trên Redis: mỗi machine id là một key `{prefix}:machine:{id}`
+    //! chứa owner, hết hạn bằng `PX`. Các bước kiểm tra owner chạy trong Lua
+    //! script để không bị replica khác chen giữa.
+
+    use std::time::Duration;
 
-                return id as i64;
+    use async_trait::async_trait;
+    use redis::Script;
+    use redis::aio::MultiplexedConnection;
+
+    use super::{LeaseStore, SnowflakeError};
+    use crate::storage::redis::KeyBuilder;
+
+    const ACQUIRE: &str = r"
+        local holder = redis.call('GET', KEYS[1])
+        if holder == false or holder == ARGV[1] then
+            redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2])
+            return 1
+        end
+        return 0
+    ";
+
+    const RENEW: &str = r"
+        if redis.call('GET', KEYS[1]) == ARGV[1] then
+            return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+        end
+        return 0
+    ";
+
+    const RELEASE: &str = r"
+        if redis.call('GET', KEYS[1]) == ARGV[1] then
+            return redis.call('DEL', KEYS[1])
+        end
+        return 0
+    ";
+
+    pub struct RedisLeaseStore {
+        conn: MultiplexedConnection,
+        kb: KeyBuilder,
+    }
+
+    impl RedisLeaseStore {
+        /// Dùng `MultiplexedConnection` có sẵn (vd từ `Resolver::cache()`).
+        pub fn new(conn: MultiplexedConnection, prefix: &str) -> Self {
+            Self {
+                conn,
+                kb: KeyBuilder::new(prefix),
             }
         }
+
+        async fn invoke(
+            &self,
+            script: &str,
+            machine_id: u16,
+            owner: &str,
+            ttl: Duration,
+        ) -> Result<bool, SnowflakeError> {
+            let mut conn = self.conn.clone();
+
+            Script::new(script)
+                .key(self.kb.indexed("machine", machine_id as usize))
+                .arg(owner)
+                .arg(ttl.as_millis() as u64)
+                .invoke_async::<i64>(&mut conn)
+                .await
+                .map(|done| done == 1)
+                .map_err(|error| SnowflakeError::Store(error.to_string()))
+        }
+    }
+
+    #[async_trait]
+    impl LeaseStore for RedisLeaseStore {
+        async fn try_acquire(
+            &self,
+            machine_id: u16,
+            owner: &str,
+            ttl: Duration,
+        ) -> Result<bool, SnowflakeError> {
+            self.invoke(ACQUIRE, machine_id, owner, ttl).await
+        }
+
+        async fn renew(
+            &self,
+            machine_id: u16,
+            owner: &str,
+            ttl: Duration,
+        ) -> Result<bool, SnowflakeError> {
+            self.invoke(RENEW, machine_id, owner, ttl).await
+        }
+
+        async fn release(&self, machine_id: u16, owner: &str) -> Result<(), SnowflakeError> {
+            self.invoke(RELEASE, machine_id, owner, Duration::ZERO)
+                .await
+                .map(|_| ())
+        }
     }
 }
 
@@ -202,6 +595,142 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decode_roundtrip() {
+        let generator = new_snowflake();
+        let before = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        let first = generator.generate();
+        let second = generator.generate();
+
+        let (timestamp, machine_id, sequence) = generator.decode(first);
+        assert_eq!(machine_id, 42);
+        assert!(timestamp >= before && timestamp <= before + 1000);
+
+        let (next_timestamp, _, next_sequence) = generator.decode(second);
+        assert!(
+            next_timestamp > timestamp
+                || (next_timestamp == timestamp && next_sequence == sequence + 1)
+        );
+    }
+
+    #[test]
+    fn test_try_new_rejects_invalid_machine_id() {
+        assert_eq!(
+            SnowflakeId::try_new(MAX_MACHINE_ID + 1, 0).err(),
+            Some(SnowflakeError::InvalidMachineId(MAX_MACHINE_ID + 1))
+        );
+        assert!(SnowflakeId::try_new(MAX_MACHINE_ID, 0).is_ok());
+    }
+
+    /// Giả lập đồng hồ lùi `ms` bằng cách đẩy timestamp đã cấp lên trước.
+    fn rewind_clock(generator: &SnowflakeId, ms: u64) -> u64 {
+        let now_ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+            - generator.get_start_time();
+        generator
+            .state
+            .store((now_ts + ms) << SEQUENCE_BITS, Ordering::Release);
+        now_ts + ms
+    }
+
+    #[test]
+    fn test_clock_backwards_policies() {
+        let generator = new_snowflake().with_clock_backwards(ClockBackwards::Error);
+        rewind_clock(&generator, 1000);
+        assert!(matches!(
+            generator.try_generate(),
+            Err(SnowflakeError::ClockMovedBackwards(drift)) if drift > 900
+        ));
+
+        let generator =
+            new_snowflake().with_clock_backwards(ClockBackwards::Wait(Duration::from_millis(50)));
+        rewind_clock(&generator, 1000);
+        assert!(generator.try_generate().is_err());
+
+        let logical_ts = rewind_clock(&generator, 20);
+        let id = generator.try_generate().unwrap();
+        assert!((id as u64 >> 22) >= logical_ts);
+
+        // Borrow: dùng tiếp timestamp logic, hết sequence thì sang ms kế tiếp
+        let generator = new_snowflake();
+        let logical_ts = rewind_clock(&generator, 60_000);
+        let ids = (0..=SEQUENCE_MASK)
+            .map(|_| generator.try_generate().unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(ids[0] as u64 >> 22, logical_ts);
+        assert_eq!(ids[SEQUENCE_MASK as usize] as u64 >> 22, logical_ts + 1);
+    }
+
+    #[tokio::test]
+    async fn test_machine_lease_is_exclusive() {
+        let store: Arc<dyn LeaseStore> = Arc::new(InMemoryLeaseStore::default());
+        let ttl = Duration::from_secs(60);
+
+        let mut leases = Vec::new();
+        for replica in 0..8 {
+            let lease = MachineLease::acquire(store.clone(), &format!("gateway-{replica}"), ttl)
+                .await
+                .unwrap();
+            leases.push(lease);
+        }
+
+        let machine_ids = leases
+            .iter()
+            .map(|lease| lease.machine_id())
+            .collect::<HashSet<_>>();
+        assert_eq!(machine_ids.len(), leases.len());
+
+        // Replica khác không lấy được id đang bị giữ, nhưng lấy lại được sau khi trả
+        let first = &leases[0];
+        assert!(
+            !store
+                .try_acquire(first.machine_id(), "intruder", ttl)
+                .await
+                .unwrap()
+        );
+        first.renew().await.unwrap();
+        first.release().await.unwrap();
+        assert!(!first.is_valid());
+        assert!(
+            store
+                .try_acquire(first.machine_id(), "intruder", ttl)
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            first.renew().await,
+            Err(SnowflakeError::LeaseLost(first.machine_id()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generator_stops_after_lease_expires() {
+        let store: Arc<dyn LeaseStore> = Arc::new(InMemoryLeaseStore::default());
+        let lease = Arc::new(
+            MachineLease::acquire(store, "gateway-0", Duration::from_millis(30))
+                .await
+                .unwrap(),
+        );
+
+        let generator = SnowflakeId::with_lease(lease.clone(), 0);
+        let id = generator.try_generate().unwrap();
+        assert_eq!(generator.decode(id).1, lease.machine_id());
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(
+            generator.try_generate(),
+            Err(SnowflakeError::LeaseLost(lease.machine_id()))
+        );
+    }
+
     #[test]
     #[should_panic(expected = "machine_id quá lớn")]
     fn test_invalid_machine_id_panics() {
@@ -1,9 +1,38 @@
+//! AES-256-GCM cho các secret lưu trong database (vd: `sys_token_map`).
+//!
+//! ## Format
+//! v0 — `encrypt`/`decrypt`: `nonce(12) | ciphertext`, mã hoá thẳng bằng master
+//! key, không biết blob thuộc key nào nên đổi key là mất hết dữ liệu cũ.
+//!
+//! v1 — `Keyring`: envelope encryption. Mỗi secret có data key ngẫu nhiên riêng,
+//! master key chỉ dùng để bọc data key đó:
+//!
+//! ```text
+//! "SOPS" | version | len(key_id) | key_id | wrap_nonce(12) | wrapped_dek(48) | nonce(12) | ciphertext
+//! └──────────────── header ───────────────┘
+//! ```
+//!
+//! Header nằm trong AAD của cả hai lớp nên không sửa được key id mà không làm
+//! hỏng blob. AAD của người gọi (vd: tenant id + tên token) chỉ gắn vào lớp dữ
+//! liệu: blob chép sang tenant khác sẽ không giải mã được.
+//!
+//! Đổi key: thêm key mới làm primary, giữ key cũ trong keyring để đọc, rồi chạy
+//! `Keyring::reencrypt` cho từng blob. Blob v0 vẫn đọc được (thử lần lượt từng
+//! key, bỏ qua AAD) và được nâng lên v1 khi re-encrypt.
+
 use std::io::{Error, ErrorKind};
 
-use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
 use aes_gcm::{Aes256Gcm, Key, Nonce};
 use rand::{RngCore, thread_rng};
 
+const MAGIC: &[u8; 4] = b"SOPS";
+const VERSION: u8 = 1;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+const WRAPPED_KEY_LEN: usize = KEY_LEN + TAG_LEN;
+
 pub fn decrypt(master_key: &[u8], encrypted_bytes: &[u8]) -> Result<String, Error> {
     let key = Key::<Aes256Gcm>::from_slice(master_key);
     let cipher = Aes256Gcm::new(key);
@@ -44,3 +73,367 @@ pub fn encrypt(master_key: &[u8], token_plain: &String) -> Result<Vec<u8>, Error
     final_blob.extend_from_slice(&ciphertext);
     Ok(final_blob)
 }
+
+// ==================== Keyring ====================
+
+/// Tập master key theo id. Key đầu tiên là primary — dùng để mã hoá, các key còn
+/// lại chỉ để giải mã blob cũ trong lúc chuyển key.
+pub struct Keyring {
+    keys: Vec<(String, Aes256Gcm)>,
+}
+
+impl Keyring {
+    pub fn new(id: &str, master_key: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            keys: vec![(validate_id(id)?.to_string(), new_cipher(master_key)?)],
+        })
+    }
+
+    /// Thêm một key cũ, chỉ dùng để giải mã.
+    pub fn with_key(mut self, id: &str, master_key: &[u8]) -> Result<Self, Error> {
+        let id = validate_id(id)?;
+        if self.contains(id) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Duplicated key id `{id}`"),
+            ));
+        }
+
+        self.keys.push((id.to_string(), new_cipher(master_key)?));
+        Ok(self)
+    }
+
+    /// Thêm nhiều key cũ dạng `id:key,id:key` (thường đọc từ biến môi trường).
+    pub fn with_keys(self, spec: &str) -> Result<Self, Error> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .try_fold(self, |keyring, entry| {
+                // @NOTE: không in `entry` vào lỗi vì có thể chứa key
+                let (id, key) = entry.split_once(':').ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidInput, "Key entry must look like `id:key`")
+                })?;
+                keyring.with_key(id, key.as_bytes())
+            })
+    }
+
+    pub fn primary_id(&self) -> &str {
+        &self.keys[0].0
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.keys.iter().any(|(key_id, _)| key_id == id)
+    }
+
+    /// Id của key đã mã hoá `blob`, `None` với blob v0.
+    pub fn key_id(blob: &[u8]) -> Option<&str> {
+        Header::parse(blob).map(|header| header.key_id)
+    }
+
+    /// `true` nếu blob chưa nằm dưới primary key (kể cả blob v0).
+    pub fn needs_rotation(&self, blob: &[u8]) -> bool {
+        Self::key_id(blob) != Some(self.primary_id())
+    }
+
+    pub fn encrypt(&self, plaintext: &str, aad: &[u8]) -> Result<Vec<u8>, Error> {
+        let (id, master) = &self.keys[0];
+
+        let mut blob = Vec::with_capacity(
+            MAGIC.len()
+                + 2
+                + id.len()
+                + 2 * NONCE_LEN
+                + WRAPPED_KEY_LEN
+                + plaintext.len()
+                + TAG_LEN,
+        );
+        blob.extend_from_slice(MAGIC);
+        blob.push(VERSION);
+        blob.push(id.len() as u8);
+        blob.extend_from_slice(id.as_bytes());
+        let header_len = blob.len();
+
+        let mut data_key = [0u8; KEY_LEN];
+        thread_rng().fill_bytes(&mut data_key);
+
+        let wrap_nonce = random_nonce();
+        let wrapped = master
+            .encrypt(
+                Nonce::from_slice(&wrap_nonce),
+                Payload {
+                    msg: &data_key,
+                    aad: &blob[..header_len],
+                },
+            )
+            .map_err(|error| Error::other(format!("Wrap data key failed: {error}")))?;
+
+        let nonce = random_nonce();
+        let ciphertext = new_cipher(&data_key)?
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: &data_aad(&blob[..header_len], aad),
+                },
+            )
+            .map_err(|error| Error::other(format!("Encrypt failed: {error}")))?;
+
+        blob.extend_from_slice(&wrap_nonce);
+        blob.extend_from_slice(&wrapped);
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&ciphertext);
+        Ok(blob)
+    }
+
+    /// Giải mã blob v1 bằng key ghi trong header, hoặc blob v0 bằng key đầu tiên
+    /// khớp (`aad` bị bỏ qua vì v0 không có AAD).
+    pub fn decrypt(&self, blob: &[u8], aad: &[u8]) -> Result<String, Error> {
+        let Some(header) = Header::parse(blob) else {
+            return self
+                .keys
+                .iter()
+                .find_map(|(_, master)| decrypt_legacy(master, blob))
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        "Decode failed: no key in keyring matches this blob",
+                    )
+                });
+        };
+
+        let master = self
+            .keys
+            .iter()
+            .find(|(id, _)| id == header.key_id)
+            .map(|(_, master)| master)
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("Key `{}` is not in keyring", header.key_id),
+                )
+            })?;
+
+        let body = &blob[header.len..];
+        if body.len() < 2 * NONCE_LEN + WRAPPED_KEY_LEN + TAG_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Data too short for decoding",
+            ));
+        }
+        let (wrap_nonce, body) = body.split_at(NONCE_LEN);
+        let (wrapped, body) = body.split_at(WRAPPED_KEY_LEN);
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+
+        let data_key = master
+            .decrypt(
+                Nonce::from_slice(wrap_nonce),
+                Payload {
+                    msg: wrapped,
+                    aad: &blob[..header.len],
+                },
+            )
+            .map_err(|error| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unwrap data key failed: {error}"),
+                )
+            })?;
+
+        let decrypted_bytes = new_cipher(&data_key)?
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &data_aad(&blob[..header.len], aad),
+                },
+            )
+            .map_err(|error| {
+                Error::new(ErrorKind::InvalidData, format!("Decode failed: {error}"))
+            })?;
+
+        String::from_utf8(decrypted_bytes).map_err(|error| {
+            Error::new(ErrorKind::InvalidData, format!("Validate failed: {error}"))
+        })
+    }
+
+    /// Mã hoá lại blob bằng primary key. Trả `None` nếu blob đã ở primary key.
+    pub fn reencrypt(&self, blob: &[u8], aad: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        if !self.needs_rotation(blob) {
+            return Ok(None);
+        }
+
+        self.encrypt(&self.decrypt(blob, aad)?, aad).map(Some)
+    }
+}
+
+struct Header<'a> {
+    key_id: &'a str,
+    len: usize,
+}
+
+impl<'a> Header<'a> {
+    /// `None` nếu không có magic/version — tức blob v0.
+    ///
+    /// @NOTE: blob v0 bắt đầu bằng nonce ngẫu nhiên, xác suất trùng `SOPS` +
+    /// version là 2^-40.
+    fn parse(blob: &'a [u8]) -> Option<Self> {
+        let rest = blob.strip_prefix(MAGIC.as_slice())?;
+        let (&version, rest) = rest.split_first()?;
+        let (&id_len, rest) = rest.split_first()?;
+        if version != VERSION || id_len == 0 || rest.len() < id_len as usize {
+            return None;
+        }
+
+        Some(Self {
+            key_id: std::str::from_utf8(&rest[..id_len as usize]).ok()?,
+            len: MAGIC.len() + 2 + id_len as usize,
+        })
+    }
+}
+
+fn validate_id(id: &str) -> Result<&str, Error> {
+    if id.is_empty() || id.len() > u8::MAX as usize || id.contains([',', ':']) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Key id must be 1-255 bytes without `,` or `:`, got `{id}`"),
+        ));
+    }
+    Ok(id)
+}
+
+fn new_cipher(key: &[u8]) -> Result<Aes256Gcm, Error> {
+    if key.len() != KEY_LEN {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Master key must be {KEY_LEN} bytes, current length: {}",
+                key.len()
+            ),
+        ));
+    }
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)))
+}
+
+fn random_nonce() -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    thread_rng().fill_bytes(&mut nonce);
+    nonce
+}
+
+fn data_aad(header: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(header.len() + aad.len());
+    out.extend_from_slice(header);
+    out.extend_from_slice(aad);
+    out
+}
+
+fn decrypt_legacy(cipher: &Aes256Gcm, blob: &[u8]) -> Option<String> {
+    if blob.len() < NONCE_LEN {
+        return None;
+    }
+
+    let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+    let decrypted_bytes = cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok()?;
+    String::from_utf8(decrypted_bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_KEY: &[u8] = b"old-master-key-32-bytes-for-aes!";
+    const NEW_KEY: &[u8] = b"new-master-key-32-bytes-for-aes!";
+
+    #[test]
+    fn test_roundtrip_with_aad() {
+        let keyring = Keyring::new("k1", NEW_KEY).unwrap();
+        let blob = keyring.encrypt("secret-token", b"1/github").unwrap();
+
+        assert_eq!(Keyring::key_id(&blob), Some("k1"));
+        assert_eq!(keyring.decrypt(&blob, b"1/github").unwrap(), "secret-token");
+
+        // Chép sang tenant/token khác → AAD không khớp
+        assert!(keyring.decrypt(&blob, b"2/github").is_err());
+
+        // Hai lần mã hoá cùng plaintext cho hai blob khác nhau
+        assert_ne!(blob, keyring.encrypt("secret-token", b"1/github").unwrap());
+    }
+
+    #[test]
+    fn test_tampered_header_is_rejected() {
+        let keyring = Keyring::new("k1", NEW_KEY)
+            .unwrap()
+            .with_key("k2", OLD_KEY)
+            .unwrap();
+        let mut blob = keyring.encrypt("secret-token", b"").unwrap();
+
+        // Đổi key id k1 → k2: key k2 có trong keyring nhưng header là AAD
+        blob[7] = b'2';
+        assert_eq!(Keyring::key_id(&blob), Some("k2"));
+        assert!(keyring.decrypt(&blob, b"").is_err());
+
+        let unknown = Keyring::new("k3", OLD_KEY).unwrap();
+        let blob = keyring.encrypt("secret-token", b"").unwrap();
+        assert_eq!(
+            unknown.decrypt(&blob, b"").unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn test_rotation_and_legacy_blobs() {
+        let legacy = encrypt(OLD_KEY, &"legacy-token".to_string()).unwrap();
+        let old = Keyring::new("old", OLD_KEY).unwrap();
+        let blob = old.encrypt("v1-token", b"1/github").unwrap();
+
+        let keyring = Keyring::new("new", NEW_KEY)
+            .unwrap()
+            .with_keys("old:old-master-key-32-bytes-for-aes!")
+            .unwrap();
+        assert_eq!(keyring.primary_id(), "new");
+
+        // Blob cũ vẫn đọc được trong lúc chuyển key
+        assert_eq!(
+            keyring.decrypt(&legacy, b"1/github").unwrap(),
+            "legacy-token"
+        );
+        assert_eq!(keyring.decrypt(&blob, b"1/github").unwrap(), "v1-token");
+
+        assert!(keyring.needs_rotation(&legacy));
+        assert!(keyring.needs_rotation(&blob));
+
+        let migrated = keyring.reencrypt(&legacy, b"1/github").unwrap().unwrap();
+        assert_eq!(Keyring::key_id(&migrated), Some("new"));
+        assert!(keyring.reencrypt(&migrated, b"1/github").unwrap().is_none());
+
+        let migrated = keyring.reencrypt(&blob, b"1/github").unwrap().unwrap();
+        assert!(!keyring.needs_rotation(&migrated));
+
+        // Bỏ key cũ khỏi keyring, blob đã migrate vẫn đọc được
+        let new_only = Keyring::new("new", NEW_KEY).unwrap();
+        assert_eq!(
+            new_only.decrypt(&migrated, b"1/github").unwrap(),
+            "v1-token"
+        );
+        assert!(new_only.decrypt(&legacy, b"1/github").is_err());
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        assert!(Keyring::new("k1", b"too-short").is_err());
+        assert!(Keyring::new("", NEW_KEY).is_err());
+        assert!(Keyring::new("a:b", NEW_KEY).is_err());
+        assert!(
+            Keyring::new("k1", NEW_KEY)
+                .unwrap()
+                .with_key("k1", OLD_KEY)
+                .is_err()
+        );
+        assert!(
+            Keyring::new("k1", NEW_KEY)
+                .unwrap()
+                .with_keys("k2")
+                .is_err()
+        );
+    }
+}
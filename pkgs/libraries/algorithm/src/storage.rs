@@ -37,6 +37,10 @@ pub trait Storage: Send + Sync {
         record: Option<usize>,
     ) -> Result<()>;
     async fn add_child(&mut self, parent_id: usize, child_id: usize) -> Result<()>;
+    async fn remove_child(&mut self, parent_id: usize, child_id: usize) -> Result<()>;
+    /// Xoá node: prefix rỗng, record `EMPTY`, không còn children.
+    /// ID không được tái sử dụng (tombstone) để các ID khác giữ nguyên.
+    async fn remove_node(&mut self, id: usize) -> Result<()>;
     async fn get_node(&self, id: usize) -> Result<(Vec<u8>, usize)>;
     async fn get_children(&self, id: usize) -> Result<Vec<usize>>;
     async fn set_root(&mut self, shard: usize, root_id: usize) -> Result<()>;
@@ -54,6 +58,12 @@ pub trait Storage: Send + Sync {
     async fn get_root_inputs(&self) -> Result<Vec<usize>>;
     async fn get_label(&self, state: usize) -> Result<String>;
     async fn num_states(&self) -> Result<usize>;
+
+    // ── Metadata: key/value tuỳ ý cho lớp phía trên (vd entry của SearchIndex) ──
+    async fn put_meta(&mut self, key: &str, value: Vec<u8>) -> Result<()>;
+    async fn delete_meta(&mut self, key: &str) -> Result<()>;
+    /// Mọi cặp key/value có key bắt đầu bằng `prefix`, theo thứ tự key.
+    async fn scan_meta(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>>;
 }
 
 // ==================== In-Memory Storage (Radix + Automaton) ====================
@@ -70,6 +80,9 @@ pub struct InMemoryStorage {
     failures: Vec<usize>,
     outputs: BTreeMap<usize, usize>,
     root_inputs: Vec<usize>,
+
+    // ── Metadata ──
+    meta: BTreeMap<String, Vec<u8>>,
 }
 
 impl Default for InMemoryStorage {
@@ -86,6 +99,8 @@ impl Default for InMemoryStorage {
             failures: vec![0],
             outputs: BTreeMap::new(),
             root_inputs: Vec::new(),
+
+            meta: BTreeMap::new(),
         }
     }
 }
@@ -121,6 +136,24 @@ impl Storage for InMemoryStorage {
         Ok(())
     }
 
+    async fn remove_child(&mut self, parent_id: usize, child_id: usize) -> Result<()> {
+        let children = self
+            .children
+            .get_mut(parent_id)
+            .ok_or(StorageError::BranchOutOfRange(parent_id))?;
+        children.retain(|&c| c != child_id);
+        Ok(())
+    }
+
+    async fn remove_node(&mut self, id: usize) -> Result<()> {
+        if id == EMPTY || id >= self.nodes.len() {
+            return Err(StorageError::BranchOutOfRange(id));
+        }
+        self.nodes[id] = (Vec::new(), EMPTY);
+        self.children[id].clear();
+        Ok(())
+    }
+
     async fn get_node(&self, id: usize) -> Result<(Vec<u8>, usize)> {
         if id >= self.nodes.len() {
             return Err(StorageError::BranchOutOfRange(id));
@@ -203,6 +236,974 @@ impl Storage for InMemoryStorage {
     async fn num_states(&self) -> Result<usize> {
         Ok(self.transitions.len())
     }
+
+    // ==================== Metadata Methods ====================
+
+    async fn put_meta(&mut self, key: &str, value: Vec<u8>) -> Result<()> {
+        self.meta.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn delete_meta(&mut self, key: &str) -> Result<()> {
+        self.meta.remove(key);
+        Ok(())
+    }
+
+    async fn scan_meta(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        Ok(self
+            .meta
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+// =========================================================================
+//  File Storage
+//  (append-only log trên local disk, replay khi mở lại)
+// =========================================================================
+
+pub mod file {
+    //! File-backed Storage implementation (Radix + Automaton).
+    //!
+    //! Mọi thao tác ghi được append vào một file log duy nhất, đồng thời áp
+    //! dụng lên một `InMemoryStorage` bên trong để phục vụ đọc. Khi mở lại,
+    //! log được replay từ đầu để dựng lại toàn bộ node/state.
+    //!
+    //! ## Định dạng file
+    //!
+    //! ```text
+    //! header : MAGIC (8 bytes) | VERSION (u32 LE)
+    //! record : len (u32 LE) | crc32(payload) (u32 LE) | payload (len bytes)
+    //! payload: tag (u8) | fields...
+    //! ```
+    //!
+    //! ## Crash safety
+    //!
+    //! Record bị ghi dở (thiếu byte) hoặc sai checksum ở cuối file được coi là
+    //! phần đuôi hỏng do crash: file bị cắt về record hợp lệ cuối cùng và các
+    //! thao tác trước đó vẫn được giữ nguyên. `compact()` ghi snapshot ra file
+    //! tạm rồi `rename` đè lên file cũ nên không bao giờ để lại file nửa vời.
+    //!
+    //! I/O là blocking (file local, ghi qua `BufWriter`); gọi `sync()` để chắc
+    //! chắn dữ liệu đã xuống đĩa.
+
+    use std::fs::{self, File, OpenOptions};
+    use std::io::{BufWriter, Read, Write};
+    use std::path::{Path, PathBuf};
+
+    use super::{InMemoryStorage, Result, Storage, StorageError};
+
+    const MAGIC: &[u8; 8] = b"ALGOSTG\0";
+    const VERSION: u32 = 1;
+    const HEADER_LEN: usize = MAGIC.len() + 4;
+    const RECORD_HEADER_LEN: usize = 8;
+
+    // ==================== Log operations ====================
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Op {
+        NewNode(Vec<u8>, usize),
+        UpdateNode(usize, Option<Vec<u8>>, Option<usize>),
+        AddChild(usize, usize),
+        SetRoot(usize, usize),
+        AddState(String),
+        SetTransition(usize, String, usize),
+        SetFailure(usize, usize),
+        SetOutput(usize, usize),
+        AddRootInput(usize),
+        RemoveChild(usize, usize),
+        RemoveNode(usize),
+        PutMeta(String, Vec<u8>),
+        DeleteMeta(String),
+    }
+
+    impl Op {
+        fn encode(&self) -> Vec<u8> {
+            let mut buf = Vec::new();
+            match self {
+                Op::NewNode(prefix, record) => {
+                    buf.push(1);
+                    put_bytes(&mut buf, prefix);
+                    put_usize(&mut buf, *record);
+                }
+                Op::UpdateNode(id, prefix, record) => {
+                    buf.push(2);
+                    put_usize(&mut buf, *id);
+                    match prefix {
+                        Some(p) => {
+                            buf.push(1);
+                            put_bytes(&mut buf, p);
+                        }
+                        None => buf.push(0),
+                    }
+                    match record {
+                        Some(r) => {
+                            buf.push(1);
+                            put_usize(&mut buf, *r);
+                        }
+                        None => buf.push(0),
+                    }
+                }
+                Op::AddChild(parent, child) => {
+                    buf.push(3);
+                    put_usize(&mut buf, *parent);
+                    put_usize(&mut buf, *child);
+                }
+                Op::SetRoot(shard, root) => {
+                    buf.push(4);
+                    put_usize(&mut buf, *shard);
+                    put_usize(&mut buf, *root);
+                }
+                Op::AddState(label) => {
+                    buf.push(5);
+                    put_bytes(&mut buf, label.as_bytes());
+                }
+                Op::SetTransition(from, label, to) => {
+                    buf.push(6);
+                    put_usize(&mut buf, *from);
+                    put_bytes(&mut buf, label.as_bytes());
+                    put_usize(&mut buf, *to);
+                }
+                Op::SetFailure(state, fail) => {
+                    buf.push(7);
+                    put_usize(&mut buf, *state);
+                    put_usize(&mut buf, *fail);
+                }
+                Op::SetOutput(state, pattern_idx) => {
+                    buf.push(8);
+                    put_usize(&mut buf, *state);
+                    put_usize(&mut buf, *pattern_idx);
+                }
+                Op::AddRootInput(state) => {
+                    buf.push(9);
+                    put_usize(&mut buf, *state);
+                }
+                Op::RemoveChild(parent, child) => {
+                    buf.push(10);
+                    put_usize(&mut buf, *parent);
+                    put_usize(&mut buf, *child);
+                }
+                Op::RemoveNode(id) => {
+                    buf.push(11);
+                    put_usize(&mut buf, *id);
+                }
+                Op::PutMeta(key, value) => {
+                    buf.push(12);
+                    put_bytes(&mut buf, key.as_bytes());
+                    put_bytes(&mut buf, value);
+                }
+                Op::DeleteMeta(key) => {
+                    buf.push(13);
+                    put_bytes(&mut buf, key.as_bytes());
+                }
+            }
+            buf
+        }
+
+        fn decode(payload: &[u8]) -> Result<Self> {
+            let mut r = Reader::new(payload);
+            let op = match r.u8()? {
+                1 => Op::NewNode(r.bytes()?, r.usize()?),
+                2 => {
+                    let id = r.usize()?;
+                    let prefix = if r.u8()? == 1 { Some(r.bytes()?) } else { None };
+                    let record = if r.u8()? == 1 { Some(r.usize()?) } else { None };
+                    Op::UpdateNode(id, prefix, record)
+                }
+                3 => Op::AddChild(r.usize()?, r.usize()?),
+                4 => Op::SetRoot(r.usize()?, r.usize()?),
+                5 => Op::AddState(r.string()?),
+                6 => Op::SetTransition(r.usize()?, r.string()?, r.usize()?),
+                7 => Op::SetFailure(r.usize()?, r.usize()?),
+                8 => Op::SetOutput(r.usize()?, r.usize()?),
+                9 => Op::AddRootInput(r.usize()?),
+                10 => Op::RemoveChild(r.usize()?, r.usize()?),
+                11 => Op::RemoveNode(r.usize()?),
+                12 => Op::PutMeta(r.string()?, r.bytes()?),
+                13 => Op::DeleteMeta(r.string()?),
+                tag => return Err(StorageError::Internal(format!("unknown log tag {tag}"))),
+            };
+            if !r.is_empty() {
+                return Err(StorageError::Internal(
+                    "trailing bytes in log record".into(),
+                ));
+            }
+            Ok(op)
+        }
+
+        /// Kiểm tra biên của op trên `InMemoryStorage` mà không thay đổi gì,
+        /// để record không hợp lệ trả về lỗi thay vì panic.
+        fn validate(&self, mem: &InMemoryStorage) -> Result<()> {
+            let check = |id: usize, len: usize| {
+                if id < len {
+                    Ok(())
+                } else {
+                    Err(StorageError::BranchOutOfRange(id))
+                }
+            };
+            match self {
+                Op::UpdateNode(id, _, _) => check(*id, mem.nodes.len()),
+                Op::AddChild(parent, _) | Op::RemoveChild(parent, _) => {
+                    check(*parent, mem.children.len())
+                }
+                Op::SetTransition(from, _, _) => check(*from, mem.transitions.len()),
+                Op::SetFailure(state, _) => check(*state, mem.failures.len()),
+                Op::RemoveNode(id) if *id == 0 => Err(StorageError::BranchOutOfRange(*id)),
+                Op::RemoveNode(id) => check(*id, mem.nodes.len()),
+                Op::NewNode(..)
+                | Op::SetRoot(..)
+                | Op::AddState(..)
+                | Op::SetOutput(..)
+                | Op::AddRootInput(..)
+                | Op::PutMeta(..)
+                | Op::DeleteMeta(..) => Ok(()),
+            }
+        }
+
+        /// Áp dụng op lên `InMemoryStorage` sau khi `validate`.
+        fn apply(&self, mem: &mut InMemoryStorage) -> Result<()> {
+            self.validate(mem)?;
+            match self {
+                Op::NewNode(prefix, record) => {
+                    mem.nodes.push((prefix.clone(), *record));
+                    mem.children.push(Vec::new());
+                }
+                Op::UpdateNode(id, prefix, record) => {
+                    let node = &mut mem.nodes[*id];
+                    if let Some(p) = prefix {
+                        node.0 = p.clone();
+                    }
+                    if let Some(r) = record {
+                        node.1 = *r;
+                    }
+                }
+                Op::AddChild(parent, child) => {
+                    mem.children[*parent].push(*child);
+                }
+                Op::SetRoot(shard, root) => {
+                    if *shard >= mem.roots.len() {
+                        mem.roots.resize(shard + 1, 0);
+                    }
+                    mem.roots[*shard] = *root;
+                }
+                Op::AddState(label) => {
+                    mem.labels.push(label.clone());
+                    mem.transitions.push(Default::default());
+                    mem.failures.push(0);
+                }
+                Op::SetTransition(from, label, to) => {
+                    mem.transitions[*from].insert(label.clone(), *to);
+                }
+                Op::SetFailure(state, fail) => {
+                    mem.failures[*state] = *fail;
+                }
+                Op::SetOutput(state, pattern_idx) => {
+                    mem.outputs.insert(*state, *pattern_idx);
+                }
+                Op::AddRootInput(state) => {
+                    mem.root_inputs.push(*state);
+                }
+                Op::RemoveChild(parent, child) => {
+                    mem.children[*parent].retain(|c| c != child);
+                }
+                Op::RemoveNode(id) => {
+                    mem.nodes[*id] = (Vec::new(), 0);
+                    mem.children[*id].clear();
+                }
+                Op::PutMeta(key, value) => {
+                    mem.meta.insert(key.clone(), value.clone());
+                }
+                Op::DeleteMeta(key) => {
+                    mem.meta.remove(key);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn put_usize(buf: &mut Vec<u8>, v: usize) {
+        buf.extend_from_slice(&(v as u64).to_le_bytes());
+    }
+
+    fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+        buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        buf.extend_from_slice(bytes);
+    }
+
+    struct Reader<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> Reader<'a> {
+        fn new(data: &'a [u8]) -> Self {
+            Self { data, pos: 0 }
+        }
+
+        fn is_empty(&self) -> bool {
+            self.pos >= self.data.len()
+        }
+
+        fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+            if self.pos + n > self.data.len() {
+                return Err(StorageError::Internal("truncated log record".into()));
+            }
+            let out = &self.data[self.pos..self.pos + n];
+            self.pos += n;
+            Ok(out)
+        }
+
+        fn u8(&mut self) -> Result<u8> {
+            Ok(self.take(1)?[0])
+        }
+
+        fn u32(&mut self) -> Result<u32> {
+            let mut b = [0u8; 4];
+            b.copy_from_slice(self.take(4)?);
+            Ok(u32::from_le_bytes(b))
+        }
+
+        fn usize(&mut self) -> Result<usize> {
+            let mut b = [0u8; 8];
+            b.copy_from_slice(self.take(8)?);
+            Ok(u64::from_le_bytes(b) as usize)
+        }
+
+        fn bytes(&mut self) -> Result<Vec<u8>> {
+            let len = self.u32()? as usize;
+            Ok(self.take(len)?.to_vec())
+        }
+
+        fn string(&mut self) -> Result<String> {
+            String::from_utf8(self.bytes()?).map_err(|e| StorageError::Internal(e.to_string()))
+        }
+    }
+
+    /// CRC-32 (IEEE) bitwise — đủ nhanh cho record nhỏ, không cần thêm dependency.
+    pub(crate) fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in data {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                let mask = (crc & 1).wrapping_neg();
+                crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+        !crc
+    }
+
+    fn io_err(e: std::io::Error) -> StorageError {
+        StorageError::Internal(e.to_string())
+    }
+
+    fn write_header(w: &mut impl Write) -> Result<()> {
+        w.write_all(MAGIC).map_err(io_err)?;
+        w.write_all(&VERSION.to_le_bytes()).map_err(io_err)
+    }
+
+    fn write_record(w: &mut impl Write, op: &Op) -> Result<()> {
+        let payload = op.encode();
+        let mut frame = Vec::with_capacity(RECORD_HEADER_LEN + payload.len());
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&crc32(&payload).to_le_bytes());
+        frame.extend_from_slice(&payload);
+        w.write_all(&frame).map_err(io_err)
+    }
+
+    /// Replay log vào `mem`. Trả về offset kết thúc record hợp lệ cuối cùng.
+    fn replay(data: &[u8], mem: &mut InMemoryStorage) -> Result<usize> {
+        if data.len() < HEADER_LEN || &data[..MAGIC.len()] != MAGIC {
+            return Err(StorageError::Internal("not a storage log file".into()));
+        }
+
+        let mut version = [0u8; 4];
+        version.copy_from_slice(&data[MAGIC.len()..HEADER_LEN]);
+        let version = u32::from_le_bytes(version);
+        if version != VERSION {
+            return Err(StorageError::Internal(format!(
+                "unsupported storage log version {version}"
+            )));
+        }
+
+        let mut pos = HEADER_LEN;
+        while pos + RECORD_HEADER_LEN <= data.len() {
+            let mut r = Reader::new(&data[pos..pos + RECORD_HEADER_LEN]);
+            let len = r.u32()? as usize;
+            let checksum = r.u32()?;
+
+            let start = pos + RECORD_HEADER_LEN;
+            let Some(payload) = data.get(start..start + len) else {
+                break;
+            };
+            if crc32(payload) != checksum {
+                // @NOTE: record hỏng nằm cuối file được xem như ghi dở (torn tail) và
+                // sẽ bị cắt; nếu phía sau còn dữ liệu thì đây là hỏng giữa file, báo
+                // lỗi thay vì âm thầm xoá các record hợp lệ phía sau
+                if start + len < data.len() {
+                    return Err(StorageError::Internal(format!(
+                        "corrupt log record at offset {pos}"
+                    )));
+                }
+                break;
+            }
+
+            Op::decode(payload)?.apply(mem)?;
+            pos = start + len;
+        }
+
+        Ok(pos)
+    }
+
+    // ==================== FileStorage ====================
+
+    pub struct FileStorage {
+        path: PathBuf,
+        writer: BufWriter<File>,
+        mem: InMemoryStorage,
+    }
+
+    impl FileStorage {
+        /// Mở (hoặc tạo mới) storage tại `path`.
+        ///
+        /// Nếu file đã tồn tại, log được replay để khôi phục trạng thái; phần
+        /// đuôi bị ghi dở do crash sẽ bị cắt bỏ.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+            let path = path.as_ref().to_path_buf();
+            let mut mem = InMemoryStorage::default();
+
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+                .map_err(io_err)?;
+
+            let mut data = Vec::new();
+            file.read_to_end(&mut data).map_err(io_err)?;
+
+            if data.is_empty() {
+                write_header(&mut file)
+                    .map_err(|e| StorageError::Internal(format!("init {}: {e}", path.display())))?;
+                file.sync_data().map_err(io_err)?;
+            } else {
+                let valid = replay(&data, &mut mem)?;
+                if valid < data.len() {
+                    log::warn!(
+                        "storage log {} has {} corrupted trailing bytes, truncating",
+                        path.display(),
+                        data.len() - valid
+                    );
+                    file.set_len(valid as u64).map_err(io_err)?;
+                    file.sync_data().map_err(io_err)?;
+                }
+            }
+
+            let file = OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .map_err(io_err)?;
+
+            Ok(Self {
+                path,
+                writer: BufWriter::new(file),
+                mem,
+            })
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        /// Flush buffer và `fsync` xuống đĩa.
+        pub fn sync(&mut self) -> Result<()> {
+            self.writer.flush().map_err(io_err)?;
+            self.writer.get_ref().sync_data().map_err(io_err)
+        }
+
+        /// Ghi lại log dưới dạng snapshot gọn nhất của trạng thái hiện tại.
+        ///
+        /// Snapshot được ghi ra `{path}.compact`, `fsync`, rồi `rename` đè lên
+        /// file gốc — crash giữa chừng vẫn giữ nguyên file cũ.
+        pub fn compact(&mut self) -> Result<()> {
+            self.writer.flush().map_err(io_err)?;
+
+            let mut tmp_path = self.path.clone().into_os_string();
+            tmp_path.push(".compact");
+            let tmp_path = PathBuf::from(tmp_path);
+
+            {
+                let mut w = BufWriter::new(File::create(&tmp_path).map_err(io_err)?);
+                write_header(&mut w)?;
+                for op in self.snapshot_ops() {
+                    write_record(&mut w, &op)?;
+                }
+                w.flush().map_err(io_err)?;
+                w.get_ref().sync_all().map_err(io_err)?;
+            }
+
+            fs::rename(&tmp_path, &self.path).map_err(io_err)?;
+
+            let file = OpenOptions::new()
+                .append(true)
+                .open(&self.path)
+                .map_err(io_err)?;
+            self.writer = BufWriter::new(file);
+            Ok(())
+        }
+
+        /// Chuỗi op tối thiểu để dựng lại `self.mem` từ một storage rỗng.
+        fn snapshot_ops(&self) -> Vec<Op> {
+            let mem = &self.mem;
+            let mut ops = Vec::new();
+
+            if mem.nodes[0] != (vec![], 0) {
+                ops.push(Op::UpdateNode(
+                    0,
+                    Some(mem.nodes[0].0.clone()),
+                    Some(mem.nodes[0].1),
+                ));
+            }
+            for (prefix, record) in mem.nodes.iter().skip(1) {
+                ops.push(Op::NewNode(prefix.clone(), *record));
+            }
+            for (parent, children) in mem.children.iter().enumerate() {
+                for &child in children {
+                    ops.push(Op::AddChild(parent, child));
+                }
+            }
+            for (shard, &root) in mem.roots.iter().enumerate() {
+                if root != 0 {
+                    ops.push(Op::SetRoot(shard, root));
+                }
+            }
+
+            for label in mem.labels.iter().skip(1) {
+                ops.push(Op::AddState(label.clone()));
+            }
+            for (from, transitions) in mem.transitions.iter().enumerate() {
+                for (label, &to) in transitions {
+                    ops.push(Op::SetTransition(from, label.clone(), to));
+                }
+            }
+            for (state, &fail) in mem.failures.iter().enumerate() {
+                if fail != 0 {
+                    ops.push(Op::SetFailure(state, fail));
+                }
+            }
+            for (&state, &pattern_idx) in &mem.outputs {
+                ops.push(Op::SetOutput(state, pattern_idx));
+            }
+            for &state in &mem.root_inputs {
+                ops.push(Op::AddRootInput(state));
+            }
+
+            for (key, value) in &mem.meta {
+                ops.push(Op::PutMeta(key.clone(), value.clone()));
+            }
+
+            ops
+        }
+
+        /// Kiểm tra op, append vào log rồi mới áp dụng lên bộ nhớ: op không hợp
+        /// lệ không bao giờ bị ghi xuống file, và ghi lỗi thì bộ nhớ không đi
+        /// trước log.
+        fn commit(&mut self, op: Op) -> Result<()> {
+            op.validate(&self.mem)?;
+            write_record(&mut self.writer, &op)?;
+            op.apply(&mut self.mem)
+        }
+    }
+
+    impl Drop for FileStorage {
+        fn drop(&mut self) {
+            let _ = self.writer.flush();
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Storage for FileStorage {
+        // ==================== Radix Methods ====================
+
+        async fn new_node(&mut self, prefix: Vec<u8>, record: usize) -> Result<usize> {
+            let id = self.mem.nodes.len();
+            self.commit(Op::NewNode(prefix, record))?;
+            Ok(id)
+        }
+
+        async fn update_node(
+            &mut self,
+            id: usize,
+            prefix: Option<Vec<u8>>,
+            record: Option<usize>,
+        ) -> Result<()> {
+            self.commit(Op::UpdateNode(id, prefix, record))
+        }
+
+        async fn add_child(&mut self, parent_id: usize, child_id: usize) -> Result<()> {
+            self.commit(Op::AddChild(parent_id, child_id))
+        }
+
+        async fn remove_child(&mut self, parent_id: usize, child_id: usize) -> Result<()> {
+            self.commit(Op::RemoveChild(parent_id, child_id))
+        }
+
+        async fn remove_node(&mut self, id: usize) -> Result<()> {
+            self.commit(Op::RemoveNode(id))
+        }
+
+        async fn get_node(&self, id: usize) -> Result<(Vec<u8>, usize)> {
+            self.mem.get_node(id).await
+        }
+
+        async fn get_children(&self, id: usize) -> Result<Vec<usize>> {
+            self.mem.get_children(id).await
+        }
+
+        async fn set_root(&mut self, shard: usize, root_id: usize) -> Result<()> {
+            self.commit(Op::SetRoot(shard, root_id))
+        }
+
+        async fn get_root(&self, shard: usize) -> Result<usize> {
+            self.mem.get_root(shard).await
+        }
+
+        // ==================== Automaton Methods ====================
+
+        async fn add_state(&mut self, label: &str) -> Result<usize> {
+            let id = self.mem.labels.len();
+            self.commit(Op::AddState(label.to_string()))?;
+            Ok(id)
+        }
+
+        async fn set_transition(&mut self, from: usize, label: &str, to: usize) -> Result<()> {
+            self.commit(Op::SetTransition(from, label.to_string(), to))
+        }
+
+        async fn get_transitions(&self, from: usize) -> Result<Vec<(String, usize)>> {
+            if from >= self.mem.transitions.len() {
+                return Err(StorageError::BranchOutOfRange(from));
+            }
+            self.mem.get_transitions(from).await
+        }
+
+        async fn set_failure(&mut self, state: usize, fail: usize) -> Result<()> {
+            self.commit(Op::SetFailure(state, fail))
+        }
+
+        async fn get_failure(&self, state: usize) -> Result<usize> {
+            if state >= self.mem.failures.len() {
+                return Err(StorageError::BranchOutOfRange(state));
+            }
+            self.mem.get_failure(state).await
+        }
+
+        async fn set_output(&mut self, state: usize, pattern_idx: usize) -> Result<()> {
+            self.commit(Op::SetOutput(state, pattern_idx))
+        }
+
+        async fn get_output(&self, state: usize) -> Result<Option<usize>> {
+            self.mem.get_output(state).await
+        }
+
+        async fn add_root_input(&mut self, state: usize) -> Result<()> {
+            self.commit(Op::AddRootInput(state))
+        }
+
+        async fn get_root_inputs(&self) -> Result<Vec<usize>> {
+            self.mem.get_root_inputs().await
+        }
+
+        async fn get_label(&self, state: usize) -> Result<String> {
+            self.mem.get_label(state).await
+        }
+
+        async fn num_states(&self) -> Result<usize> {
+            self.mem.num_states().await
+        }
+
+        // ==================== Metadata Methods ====================
+
+        async fn put_meta(&mut self, key: &str, value: Vec<u8>) -> Result<()> {
+            self.commit(Op::PutMeta(key.to_string(), value))
+        }
+
+        async fn delete_meta(&mut self, key: &str) -> Result<()> {
+            self.commit(Op::DeleteMeta(key.to_string()))
+        }
+
+        async fn scan_meta(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+            self.mem.scan_meta(prefix).await
+        }
+    }
+
+    // ── Tests ──────────────────────────────────────────────────────────
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::atomic::{AtomicU16, Ordering};
+
+        use super::*;
+
+        static COUNTER: AtomicU16 = AtomicU16::new(0);
+
+        /// Đường dẫn file log unique trong thư mục tạm.
+        fn temp_path() -> PathBuf {
+            let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let path = std::env::temp_dir().join(format!(
+                "algorithm-file-storage-{}-{n}.log",
+                std::process::id()
+            ));
+            let _ = fs::remove_file(&path);
+            path
+        }
+
+        #[tokio::test]
+        async fn test_reopen_restores_radix_and_automaton() {
+            let path = temp_path();
+            {
+                let mut s = FileStorage::open(&path).unwrap();
+                let parent = s.new_node(b"he".to_vec(), 0).await.unwrap();
+                let child = s.new_node(b"llo".to_vec(), 7).await.unwrap();
+                s.add_child(parent, child).await.unwrap();
+                s.update_node(parent, None, Some(3)).await.unwrap();
+                s.set_root(2, parent).await.unwrap();
+
+                let st = s.add_state("a").await.unwrap();
+                s.set_transition(0, "a", st).await.unwrap();
+                s.set_failure(st, 0).await.unwrap();
+                s.set_output(st, 4).await.unwrap();
+                s.add_root_input(st).await.unwrap();
+                s.sync().unwrap();
+            }
+
+            let s = FileStorage::open(&path).unwrap();
+            assert_eq!(s.get_node(1).await.unwrap(), (b"he".to_vec(), 3));
+            assert_eq!(s.get_node(2).await.unwrap(), (b"llo".to_vec(), 7));
+            assert_eq!(s.get_children(1).await.unwrap(), vec![2]);
+            assert_eq!(s.get_root(2).await.unwrap(), 1);
+
+            assert_eq!(s.num_states().await.unwrap(), 2);
+            assert_eq!(s.get_label(1).await.unwrap(), "a");
+            assert_eq!(s.get_transitions(0).await.unwrap(), vec![("a".into(), 1)]);
+            assert_eq!(s.get_output(1).await.unwrap(), Some(4));
+            assert_eq!(s.get_root_inputs().await.unwrap(), vec![1]);
+
+            let _ = fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_truncated_tail_is_discarded() {
+            let path = temp_path();
+            {
+                let mut s = FileStorage::open(&path).unwrap();
+                s.new_node(b"kept".to_vec(), 1).await.unwrap();
+                s.new_node(b"lost".to_vec(), 2).await.unwrap();
+                s.sync().unwrap();
+            }
+
+            // Giả lập crash: cắt mất vài byte cuối của record thứ hai
+            let len = fs::metadata(&path).unwrap().len();
+            OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_len(len - 3)
+                .unwrap();
+
+            let mut s = FileStorage::open(&path).unwrap();
+            assert_eq!(s.get_node(1).await.unwrap(), (b"kept".to_vec(), 1));
+            assert!(s.get_node(2).await.is_err());
+
+            // Ghi tiếp sau khi đã cắt đuôi vẫn replay được
+            let id = s.new_node(b"again".to_vec(), 3).await.unwrap();
+            assert_eq!(id, 2);
+            drop(s);
+
+            let s = FileStorage::open(&path).unwrap();
+            assert_eq!(s.get_node(2).await.unwrap(), (b"again".to_vec(), 3));
+
+            let _ = fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_corrupted_checksum_stops_replay() {
+            let path = temp_path();
+            {
+                let mut s = FileStorage::open(&path).unwrap();
+                s.new_node(b"first".to_vec(), 1).await.unwrap();
+                s.new_node(b"second".to_vec(), 2).await.unwrap();
+            }
+
+            let mut data = fs::read(&path).unwrap();
+            let last = data.len() - 1;
+            data[last] ^= 0xFF;
+            fs::write(&path, &data).unwrap();
+
+            let s = FileStorage::open(&path).unwrap();
+            assert_eq!(s.get_node(1).await.unwrap(), (b"first".to_vec(), 1));
+            assert!(s.get_node(2).await.is_err());
+
+            let _ = fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_corrupted_middle_record_is_rejected() {
+            let path = temp_path();
+            {
+                let mut s = FileStorage::open(&path).unwrap();
+                s.new_node(b"first".to_vec(), 1).await.unwrap();
+                s.new_node(b"second".to_vec(), 2).await.unwrap();
+            }
+
+            // Lật một byte trong payload của record đầu tiên
+            let mut data = fs::read(&path).unwrap();
+            data[HEADER_LEN + RECORD_HEADER_LEN] ^= 0xFF;
+            fs::write(&path, &data).unwrap();
+
+            match FileStorage::open(&path) {
+                Err(StorageError::Internal(msg)) => {
+                    assert!(msg.contains("corrupt log record"), "{msg}")
+                }
+                other => panic!("expected corrupt record error, got {:?}", other.err()),
+            }
+
+            // File phải được giữ nguyên để có thể khôi phục thủ công
+            assert_eq!(fs::read(&path).unwrap(), data);
+
+            let _ = fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_compact_keeps_state() {
+            let path = temp_path();
+            let mut s = FileStorage::open(&path).unwrap();
+            let id = s.new_node(b"a".to_vec(), 1).await.unwrap();
+            for i in 0..50 {
+                s.update_node(id, None, Some(i + 1)).await.unwrap();
+            }
+            s.sync().unwrap();
+            let before = fs::metadata(&path).unwrap().len();
+
+            s.compact().unwrap();
+            let after = fs::metadata(&path).unwrap().len();
+            assert!(after < before, "compact phải làm file nhỏ lại");
+
+            s.set_root(0, id).await.unwrap();
+            drop(s);
+
+            let s = FileStorage::open(&path).unwrap();
+            assert_eq!(s.get_node(id).await.unwrap(), (b"a".to_vec(), 50));
+            assert_eq!(s.get_root(0).await.unwrap(), id);
+
+            let _ = fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_remove_survives_reopen() {
+            let path = temp_path();
+            {
+                let mut s = FileStorage::open(&path).unwrap();
+                let parent = s.new_node(b"p".to_vec(), 0).await.unwrap();
+                let a = s.new_node(b"a".to_vec(), 1).await.unwrap();
+                let b = s.new_node(b"b".to_vec(), 2).await.unwrap();
+                s.add_child(parent, a).await.unwrap();
+                s.add_child(parent, b).await.unwrap();
+                s.remove_child(parent, a).await.unwrap();
+                s.remove_node(a).await.unwrap();
+                s.compact().unwrap();
+            }
+
+            let s = FileStorage::open(&path).unwrap();
+            assert_eq!(s.get_children(1).await.unwrap(), vec![3]);
+            assert_eq!(s.get_node(2).await.unwrap(), (vec![], 0));
+            assert_eq!(s.get_node(3).await.unwrap(), (b"b".to_vec(), 2));
+
+            let _ = fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_meta_survives_reopen_and_compact() {
+            let path = temp_path();
+            {
+                let mut s = FileStorage::open(&path).unwrap();
+                s.put_meta("entry:1", b"one".to_vec()).await.unwrap();
+                s.put_meta("entry:2", b"two".to_vec()).await.unwrap();
+                s.put_meta("other", b"x".to_vec()).await.unwrap();
+                s.put_meta("entry:1", b"uno".to_vec()).await.unwrap();
+                s.delete_meta("entry:2").await.unwrap();
+            }
+
+            let mut s = FileStorage::open(&path).unwrap();
+            assert_eq!(
+                s.scan_meta("entry:").await.unwrap(),
+                vec![("entry:1".to_string(), b"uno".to_vec())]
+            );
+
+            s.compact().unwrap();
+            drop(s);
+
+            let s = FileStorage::open(&path).unwrap();
+            assert_eq!(s.scan_meta("").await.unwrap().len(), 2);
+            assert_eq!(
+                s.scan_meta("other").await.unwrap(),
+                vec![("other".to_string(), b"x".to_vec())]
+            );
+
+            let _ = fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_invalid_op_is_not_logged() {
+            let path = temp_path();
+            {
+                let mut s = FileStorage::open(&path).unwrap();
+                assert!(s.add_child(99, 1).await.is_err());
+                s.new_node(b"ok".to_vec(), 1).await.unwrap();
+            }
+
+            let s = FileStorage::open(&path).unwrap();
+            assert_eq!(s.get_node(1).await.unwrap(), (b"ok".to_vec(), 1));
+
+            let _ = fs::remove_file(&path);
+        }
+
+        #[tokio::test]
+        async fn test_failed_write_is_not_applied() {
+            let path = temp_path();
+            let mut s = FileStorage::open(&path).unwrap();
+            s.new_node(b"ok".to_vec(), 1).await.unwrap();
+            s.sync().unwrap();
+
+            // Handle chỉ đọc và không buffer: mọi lần ghi record đều lỗi
+            s.writer = BufWriter::with_capacity(0, File::open(&path).unwrap());
+            assert!(s.new_node(b"lost".to_vec(), 2).await.is_err());
+            assert!(s.update_node(1, None, Some(9)).await.is_err());
+            assert!(s.get_node(2).await.is_err());
+            assert_eq!(s.get_node(1).await.unwrap(), (b"ok".to_vec(), 1));
+
+            // Ghi lại được thì id tiếp theo không bị nhảy
+            s.writer = BufWriter::new(OpenOptions::new().append(true).open(&path).unwrap());
+            assert_eq!(s.new_node(b"again".to_vec(), 3).await.unwrap(), 2);
+            drop(s);
+
+            let s = FileStorage::open(&path).unwrap();
+            assert_eq!(s.get_node(1).await.unwrap(), (b"ok".to_vec(), 1));
+            assert_eq!(s.get_node(2).await.unwrap(), (b"again".to_vec(), 3));
+
+            let _ = fs::remove_file(&path);
+        }
+
+        #[test]
+        fn test_rejects_foreign_file() {
+            let path = temp_path();
+            fs::write(&path, b"definitely not a log").unwrap();
+            assert!(FileStorage::open(&path).is_err());
+            let _ = fs::remove_file(&path);
+        }
+    }
 }
 
 // =========================================================================
@@ -231,6 +1232,7 @@ pub mod redis {
     //! | `{prefix}:failure`         | List  | failure link của state          |
     //! | `{prefix}:output`          | Hash  | output (pattern_idx) của state  |
     //! | `{prefix}:root_inputs`     | List  | danh sách root input states     |
+    //! | `{prefix}:meta`            | Hash  | metadata key/value              |
 
     use std::sync::Arc;
 
@@ -436,6 +1438,38 @@ pub mod redis {
             Ok(())
         }
 
+        async fn remove_child(&mut self, parent_id: usize, child_id: usize) -> Result<()> {
+            let mut conn = self.lock().await;
+
+            cmd("LREM")
+                .arg(self.kb.indexed("forward", parent_id))
+                .arg(0i64)
+                .arg(child_id as i64)
+                .query_async::<()>(&mut *conn)
+                .await
+                .map_err(|e: redis::RedisError| StorageError::Internal(e.to_string()))?;
+
+            Ok(())
+        }
+
+        async fn remove_node(&mut self, id: usize) -> Result<()> {
+            if id == 0 {
+                return Err(StorageError::BranchOutOfRange(id));
+            }
+
+            let mut conn = self.lock().await;
+
+            redis::pipe()
+                .lset(self.kb.key("branch"), id as isize, b"" as &[u8])
+                .lset(self.kb.key("record"), id as isize, 0i64)
+                .del(self.kb.indexed("forward", id))
+                .exec_async(&mut *conn)
+                .await
+                .map_err(|e: redis::RedisError| StorageError::Internal(e.to_string()))?;
+
+            Ok(())
+        }
+
         async fn get_node(&self, id: usize) -> Result<(Vec<u8>, usize)> {
             let mut conn = self.lock().await;
 
@@ -657,6 +1691,49 @@ pub mod redis {
 
             Ok(n)
         }
+
+        // ==================== Metadata Methods ====================
+
+        async fn put_meta(&mut self, key: &str, value: Vec<u8>) -> Result<()> {
+            let mut conn = self.lock().await;
+
+            cmd("HSET")
+                .arg(self.kb.key("meta"))
+                .arg(key)
+                .arg(&value[..])
+                .query_async::<()>(&mut *conn)
+                .await
+                .map_err(|e: redis::RedisError| StorageError::Internal(e.to_string()))?;
+
+            Ok(())
+        }
+
+        async fn delete_meta(&mut self, key: &str) -> Result<()> {
+            let mut conn = self.lock().await;
+
+            cmd("HDEL")
+                .arg(self.kb.key("meta"))
+                .arg(key)
+                .query_async::<()>(&mut *conn)
+                .await
+                .map_err(|e: redis::RedisError| StorageError::Internal(e.to_string()))?;
+
+            Ok(())
+        }
+
+        async fn scan_meta(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+            let mut conn = self.lock().await;
+
+            let mut pairs: Vec<(String, Vec<u8>)> = cmd("HGETALL")
+                .arg(self.kb.key("meta"))
+                .query_async(&mut *conn)
+                .await
+                .map_err(|e: redis::RedisError| StorageError::Internal(e.to_string()))?;
+
+            pairs.retain(|(key, _)| key.starts_with(prefix));
+            pairs.sort_by(|a, b| a.0.cmp(&b.0));
+            Ok(pairs)
+        }
     }
 
     // ── Tests ──────────────────────────────────────────────────────────
@@ -721,6 +1798,22 @@ pub mod redis {
             assert_eq!(children, vec![child1, child2]);
         }
 
+        #[tokio::test]
+        async fn test_remove_child_and_node() {
+            let mut s = new_test_storage().await;
+            let parent = s.new_node(b"parent".to_vec(), 0).await.unwrap();
+            let child1 = s.new_node(b"child1".to_vec(), 1).await.unwrap();
+            let child2 = s.new_node(b"child2".to_vec(), 2).await.unwrap();
+            s.add_child(parent, child1).await.unwrap();
+            s.add_child(parent, child2).await.unwrap();
+
+            s.remove_child(parent, child1).await.unwrap();
+            s.remove_node(child1).await.unwrap();
+
+            assert_eq!(s.get_children(parent).await.unwrap(), vec![child2]);
+            assert_eq!(s.get_node(child1).await.unwrap(), (vec![], 0));
+        }
+
         #[tokio::test]
         async fn test_root() {
             let mut s = new_test_storage().await;
@@ -0,0 +1,674 @@
+//! Backtest hướng sự kiện trên chuỗi `CandleStick` của một mã.
+//!
+//! ## Idea
+//! Engine phát lại từng nến theo thứ tự thời gian, mỗi nến đi qua 3 bước:
+//!
+//! ```text
+//! nến i:  khớp lệnh đang chờ  ->  chốt equity theo close  ->  strategy.on_bar(i)
+//!              ^                                                     |
+//!              +------------------ lệnh mới, khớp từ nến i+1 --------+
+//! ```
+//!
+//! Strategy chỉ thấy nến đã đóng (`Context::history`) và lệnh đặt ở nến `i`
+//! sớm nhất khớp ở nến `i + 1`, nên không có look-ahead. Strategy giữ chỉ báo
+//! của riêng nó và gọi `Indicator::next(context.candle())` mỗi nến.
+//!
+//! Luật khớp nằm trong `MarketRules`:
+//!
+//! - Biên độ: giá tham chiếu là close của ngày giao dịch trước, lệnh giới hạn
+//!   ngoài `[sàn, trần]` bị từ chối, giá khớp bị kẹp trong biên.
+//! - Lô chẵn: khối lượng làm tròn xuống theo lô, lệnh mua bị cắt theo sức mua.
+//! - T+n: cổ phiếu mua ở ngày `d` chỉ bán được từ ngày giao dịch thứ `n` sau
+//!   `d`, bán thì ăn vào lô mua cũ nhất trước (FIFO).
+//! - Phí mua/bán và thuế bán trừ thẳng vào tiền mặt.
+//! - Hiệu lực: lệnh `Day` chưa khớp bị huỷ khi sang ngày giao dịch mới (theo
+//!   `TradingSession`), tính từ phiên của nến đầu tiên lệnh được đưa vào khớp;
+//!   lệnh `Gtc` chờ tới khi khớp hoặc bị huỷ.
+//!
+//! Không có random hay HashMap, lệnh khớp theo thứ tự đặt, nên cùng nến và cùng
+//! strategy luôn ra cùng `Report`.
+
+mod order;
+mod report;
+mod rules;
+
+pub use order::*;
+pub use report::*;
+pub use rules::*;
+
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind};
+
+use schemas::CandleStick;
+
+/// Phần cổ phiếu còn lại nhỏ hơn mức này coi như đã bán hết
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+pub trait Strategy {
+    /// Gọi sau khi nến `context.index()` đóng cửa
+    fn on_bar(&mut self, context: &mut Context<'_>);
+}
+
+impl<F: FnMut(&mut Context<'_>)> Strategy for F {
+    fn on_bar(&mut self, context: &mut Context<'_>) {
+        self(context)
+    }
+}
+
+// ==================== Account ====================
+
+/// Một lô mua, `cost` là giá vốn mỗi cổ phiếu đã gồm phí mua
+#[derive(Debug, Clone)]
+struct Lot {
+    quantity: f64,
+    cost: f64,
+    t: i32,
+    settled_from: i64,
+}
+
+#[derive(Debug, Default)]
+struct Account {
+    cash: f64,
+    lots: VecDeque<Lot>,
+    orders: Vec<Order>,
+    next_order_id: usize,
+    day: i64,
+    last_close: f64,
+    fees: f64,
+    fills: Vec<Fill>,
+    trades: Vec<Trade>,
+    rejections: Vec<Rejection>,
+}
+
+impl Account {
+    fn position(&self) -> f64 {
+        self.lots.iter().map(|lot| lot.quantity).sum()
+    }
+
+    fn sellable(&self) -> f64 {
+        self.lots
+            .iter()
+            .filter(|lot| lot.settled_from <= self.day)
+            .map(|lot| lot.quantity)
+            .sum()
+    }
+
+    fn equity(&self) -> f64 {
+        self.cash + self.position() * self.last_close
+    }
+
+    fn reject(&mut self, order: Order, index: usize, reason: &'static str) {
+        self.rejections.push(Rejection {
+            order,
+            index,
+            reason,
+        });
+    }
+
+    fn execute(
+        &mut self,
+        rules: &MarketRules,
+        order: Order,
+        index: usize,
+        candle: &CandleStick,
+        price: f64,
+    ) {
+        let quantity = match order.side {
+            OrderSide::Buy => {
+                let affordable = rules.round_lot(self.cash / (price * (1.0 + rules.buy_fee)));
+                order.quantity.min(affordable)
+            }
+            OrderSide::Sell => order.quantity.min(self.sellable()),
+        };
+        if quantity <= QUANTITY_EPSILON {
+            let reason = match order.side {
+                OrderSide::Buy => "Insufficient cash",
+                OrderSide::Sell => "No settled shares to sell",
+            };
+            self.reject(order, index, reason);
+            return;
+        }
+
+        let value = price * quantity;
+        let fee = match order.side {
+            OrderSide::Buy => {
+                let fee = value * rules.buy_fee;
+                self.cash -= value + fee;
+                self.lots.push_back(Lot {
+                    quantity,
+                    cost: price * (1.0 + rules.buy_fee),
+                    t: candle.t,
+                    settled_from: rules.settlement_day(self.day),
+                });
+                fee
+            }
+            OrderSide::Sell => {
+                let fee = value * (rules.sell_fee + rules.sell_tax);
+                let exit_price = price * (1.0 - rules.sell_fee - rules.sell_tax);
+                self.cash += value - fee;
+
+                let mut remaining = quantity;
+                while remaining > QUANTITY_EPSILON {
+                    let Some(lot) = self.lots.front_mut() else {
+                        break;
+                    };
+                    let taken = lot.quantity.min(remaining);
+                    self.trades.push(Trade {
+                        entry_t: lot.t,
+                        exit_t: candle.t,
+                        entry_price: lot.cost,
+                        exit_price,
+                        quantity: taken,
+                        pnl: (exit_price - lot.cost) * taken,
+                    });
+
+                    lot.quantity -= taken;
+                    remaining -= taken;
+                    if lot.quantity <= QUANTITY_EPSILON {
+                        self.lots.pop_front();
+                    }
+                }
+                fee
+            }
+        };
+
+        self.fees += fee;
+        self.fills.push(Fill {
+            order_id: order.id,
+            index,
+            t: candle.t,
+            side: order.side,
+            price,
+            quantity,
+            fee,
+        });
+    }
+}
+
+// ==================== Context ====================
+
+/// Những gì strategy thấy và làm được sau mỗi nến
+pub struct Context<'a> {
+    index: usize,
+    candles: &'a [CandleStick],
+    rules: &'a MarketRules,
+    account: &'a mut Account,
+}
+
+impl Context<'_> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Nến vừa đóng
+    pub fn candle(&self) -> &CandleStick {
+        &self.candles[self.index]
+    }
+
+    /// Các nến đã đóng, tới và gồm nến hiện tại
+    pub fn history(&self) -> &[CandleStick] {
+        &self.candles[..=self.index]
+    }
+
+    pub fn rules(&self) -> &MarketRules {
+        self.rules
+    }
+
+    pub fn cash(&self) -> f64 {
+        self.account.cash
+    }
+
+    /// Tổng số cổ phiếu đang nắm, gồm cả phần chưa về
+    pub fn position(&self) -> f64 {
+        self.account.position()
+    }
+
+    /// Số cổ phiếu đã về, bán được ngay
+    pub fn sellable(&self) -> f64 {
+        self.account.sellable()
+    }
+
+    pub fn equity(&self) -> f64 {
+        self.account.equity()
+    }
+
+    /// Khối lượng tối đa mua được ở `price` với tiền mặt hiện có, sau phí và lô
+    pub fn affordable(&self, price: f64) -> f64 {
+        self.rules
+            .round_lot(self.account.cash / (price * (1.0 + self.rules.buy_fee)))
+    }
+
+    pub fn orders(&self) -> &[Order] {
+        &self.account.orders
+    }
+
+    /// Đặt lệnh với hiệu lực mặc định của `MarketRules`, khối lượng làm tròn
+    /// xuống theo lô. Trả id lệnh, hoặc `None` nếu lệnh bị từ chối ngay (khối
+    /// lượng hoặc giá không hợp lệ).
+    pub fn submit(&mut self, side: OrderSide, kind: OrderKind, quantity: f64) -> Option<usize> {
+        self.submit_with(side, kind, quantity, self.rules.time_in_force)
+    }
+
+    /// Như `submit` nhưng chỉ định hiệu lực của lệnh
+    pub fn submit_with(
+        &mut self,
+        side: OrderSide,
+        kind: OrderKind,
+        quantity: f64,
+        time_in_force: TimeInForce,
+    ) -> Option<usize> {
+        let id = self.account.next_order_id;
+        self.account.next_order_id += 1;
+
+        let order = Order {
+            id,
+            side,
+            kind,
+            quantity: self.rules.round_lot(quantity),
+            time_in_force,
+            submitted_at: self.index,
+        };
+        let valid_price = match kind {
+            OrderKind::Market => true,
+            OrderKind::Limit(price) | OrderKind::Stop(price) => price.is_finite() && price > 0.0,
+        };
+
+        if !valid_price {
+            self.account
+                .reject(order, self.index, "Invalid order price");
+            None
+        } else if order.quantity.is_nan() || order.quantity <= 0.0 {
+            self.account
+                .reject(order, self.index, "Quantity is less than a lot");
+            None
+        } else {
+            self.account.orders.push(order);
+            Some(id)
+        }
+    }
+
+    pub fn buy(&mut self, kind: OrderKind, quantity: f64) -> Option<usize> {
+        self.submit(OrderSide::Buy, kind, quantity)
+    }
+
+    pub fn sell(&mut self, kind: OrderKind, quantity: f64) -> Option<usize> {
+        self.submit(OrderSide::Sell, kind, quantity)
+    }
+
+    /// Huỷ lệnh đang chờ, trả `false` nếu lệnh đã khớp hoặc không tồn tại
+    pub fn cancel(&mut self, id: usize) -> bool {
+        let count = self.account.orders.len();
+        self.account.orders.retain(|order| order.id != id);
+        self.account.orders.len() < count
+    }
+
+    pub fn cancel_all(&mut self) {
+        self.account.orders.clear();
+    }
+}
+
+// ==================== Engine ====================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backtest {
+    rules: MarketRules,
+    initial_cash: f64,
+    risk_free_rate: f64,
+}
+
+impl Backtest {
+    pub fn new(rules: MarketRules, initial_cash: f64) -> Result<Self, Error> {
+        rules.validate()?;
+        if !(initial_cash.is_finite() && initial_cash > 0.0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Initial cash must be positive",
+            ));
+        }
+
+        Ok(Self {
+            rules,
+            initial_cash,
+            risk_free_rate: 0.0,
+        })
+    }
+
+    /// Lãi suất phi rủi ro theo năm dùng cho Sharpe/Sortino
+    pub fn with_risk_free_rate(mut self, risk_free_rate: f64) -> Self {
+        self.risk_free_rate = risk_free_rate;
+        self
+    }
+
+    /// Chạy `strategy` trên `candles` (tăng dần theo thời gian)
+    pub fn run<S: Strategy + ?Sized>(
+        &self,
+        candles: &[CandleStick],
+        strategy: &mut S,
+    ) -> Result<Report, Error> {
+        if candles.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "No candles to replay"));
+        }
+        if candles.windows(2).any(|pair| pair[0].t >= pair[1].t) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Candles must be strictly increasing in time",
+            ));
+        }
+
+        let mut account = Account {
+            cash: self.initial_cash,
+            ..Default::default()
+        };
+        let mut equity = Vec::with_capacity(candles.len());
+        let mut reference = None;
+        let mut previous_close = None;
+        let mut current_day = None;
+
+        for (index, candle) in candles.iter().enumerate() {
+            let day = self.rules.session.local_day(candle.t as i64);
+            if current_day != Some(day) {
+                reference = previous_close;
+                current_day = Some(day);
+            }
+            account.day = day;
+
+            // Lệnh khớp theo thứ tự đặt, lệnh chưa chạm giá thì tiếp tục chờ
+            let (floor, ceil) = self.rules.band(reference);
+            for order in std::mem::take(&mut account.orders) {
+                // Lệnh Day sống trong phiên của nến đầu tiên sau lúc đặt
+                if order.time_in_force == TimeInForce::Day
+                    && self
+                        .rules
+                        .session
+                        .local_day(candles[order.submitted_at + 1].t as i64)
+                        != day
+                {
+                    account.reject(order, index, "Day order expired at session end");
+                    continue;
+                }
+
+                if let OrderKind::Limit(price) = order.kind
+                    && !(floor <= price && price <= ceil)
+                {
+                    account.reject(order, index, "Limit price is outside the price band");
+                    continue;
+                }
+
+                match order.match_price(candle, floor, ceil) {
+                    Some(price) => account.execute(&self.rules, order, index, candle, price),
+                    None => account.orders.push(order),
+                }
+            }
+
+            previous_close = Some(candle.c);
+            account.last_close = candle.c;
+            equity.push((candle.t, account.equity()));
+
+            strategy.on_bar(&mut Context {
+                index,
+                candles,
+                rules: &self.rules,
+                account: &mut account,
+            });
+        }
+
+        let statistics = Statistics::compute(
+            self.initial_cash,
+            &equity,
+            &account.trades,
+            account.fees,
+            self.risk_free_rate,
+        );
+
+        Ok(Report {
+            fills: account.fills,
+            trades: account.trades,
+            rejections: account.rejections,
+            equity,
+            statistics,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::candles_from_closes;
+    use crate::{Indicator, SimpleMovingAverage};
+
+    // 2024-01-04 09:00 giờ Việt Nam, thứ 5
+    const THURSDAY: i32 = 1_704_333_600;
+    const DAY: i32 = 86400;
+
+    fn daily(days: &[i32], closes: &[f64]) -> Vec<CandleStick> {
+        days.iter()
+            .zip(closes)
+            .map(|(&day, &c)| CandleStick {
+                t: THURSDAY + day * DAY,
+                o: c,
+                h: c * 1.01,
+                l: c * 0.99,
+                c,
+                v: 1000.0,
+            })
+            .collect()
+    }
+
+    /// Cắt SMA nhanh/chậm, mua hết sức mua rồi bán toàn bộ
+    struct Crossover {
+        fast: SimpleMovingAverage,
+        slow: SimpleMovingAverage,
+        above: Option<bool>,
+    }
+
+    impl Strategy for Crossover {
+        fn on_bar(&mut self, context: &mut Context<'_>) {
+            let (Some(fast), Some(slow)) = (
+                self.fast.next(context.candle()),
+                self.slow.next(context.candle()),
+            ) else {
+                return;
+            };
+            let above = fast > slow;
+
+            if self.above == Some(!above) {
+                if above {
+                    let quantity = context.affordable(context.candle().c * 1.05);
+                    context.buy(OrderKind::Market, quantity);
+                } else if context.sellable() > 0.0 {
+                    context.sell(OrderKind::Market, context.sellable());
+                }
+            }
+            self.above = Some(above);
+        }
+    }
+
+    #[test]
+    fn test_crossover_strategy_is_deterministic() {
+        let closes = (0..90)
+            .map(|i| 50.0 + 5.0 * (i as f64 / 4.0).sin() + i as f64 * 0.05)
+            .collect::<Vec<_>>();
+        let candles = candles_from_closes(&closes);
+        let backtest = Backtest::new(MarketRules::crypto(), 10_000.0).unwrap();
+        let run = || {
+            backtest
+                .run(
+                    &candles,
+                    &mut Crossover {
+                        fast: SimpleMovingAverage::new(3),
+                        slow: SimpleMovingAverage::new(8),
+                        above: None,
+                    },
+                )
+                .unwrap()
+        };
+
+        let report = run();
+        assert!(report.statistics.trades >= 3);
+        assert_eq!(report.equity.len(), candles.len());
+        assert_eq!(report.statistics.trades, report.trades.len());
+
+        // Tiền mặt cuối = tiền đầu - mua + bán - phí
+        let flows = report
+            .fills
+            .iter()
+            .map(|fill| match fill.side {
+                OrderSide::Buy => -fill.price * fill.quantity - fill.fee,
+                OrderSide::Sell => fill.price * fill.quantity - fill.fee,
+            })
+            .sum::<f64>();
+        let position = report
+            .fills
+            .iter()
+            .map(|fill| match fill.side {
+                OrderSide::Buy => fill.quantity,
+                OrderSide::Sell => -fill.quantity,
+            })
+            .sum::<f64>();
+        let (_, last) = report.equity[candles.len() - 1];
+        let expected = 10_000.0 + flows + position * candles[candles.len() - 1].c;
+        assert!((last - expected).abs() < 1e-6);
+
+        let again = run();
+        assert_eq!(report.fills, again.fills);
+        assert_eq!(report.equity, again.equity);
+        assert_eq!(report.statistics, again.statistics);
+    }
+
+    #[test]
+    fn test_settlement_lot_size_and_fees() {
+        // Thứ 5, thứ 6, thứ 2, thứ 3, thứ 4
+        let candles = daily(&[0, 1, 4, 5, 6], &[10.0, 10.0, 10.2, 10.4, 10.6]);
+        let backtest = Backtest::new(MarketRules::hose(), 100_000.0).unwrap();
+
+        let report = backtest
+            .run(&candles, &mut |context: &mut Context<'_>| {
+                if context.index() == 0 {
+                    context.buy(OrderKind::Market, 250.0);
+                } else if context.position() > 0.0 && context.orders().is_empty() {
+                    context.sell(OrderKind::Market, context.position());
+                }
+            })
+            .unwrap();
+
+        // Lô 100: 250 -> 200, mua thứ 6 thì thứ 3 mới được bán
+        assert_eq!(report.fills.len(), 2);
+        let (buy, sell) = (&report.fills[0], &report.fills[1]);
+        assert_eq!((buy.index, buy.quantity), (1, 200.0));
+        assert_eq!((sell.index, sell.quantity, sell.price), (3, 200.0, 10.4));
+        assert_eq!(report.rejections.len(), 1);
+        assert_eq!(report.rejections[0].index, 2);
+
+        assert!((buy.fee - 2000.0 * 0.0015).abs() < 1e-9);
+        assert!((sell.fee - 2080.0 * 0.0025).abs() < 1e-9);
+
+        let trade = &report.trades[0];
+        assert!((trade.pnl - (2080.0 - sell.fee - 2000.0 - buy.fee)).abs() < 1e-9);
+        assert_eq!(report.statistics.win_rate, Some(1.0));
+        let (_, last) = report.equity[4];
+        assert!((last - (100_000.0 + trade.pnl)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_price_band_and_order_types() {
+        // Close thứ 5 = 10.0 nên biên độ thứ 6 là [9.3, 10.7]
+        let mut candles = daily(&[0, 1, 4], &[10.0, 10.0, 10.0]);
+        candles[1].h = 10.6;
+        candles[1].l = 9.5;
+
+        let backtest = Backtest::new(MarketRules::hose(), 100_000.0).unwrap();
+        let report = backtest
+            .run(&candles, &mut |context: &mut Context<'_>| {
+                if context.index() == 0 {
+                    context.buy(OrderKind::Limit(10.8), 100.0);
+                    context.buy(OrderKind::Limit(9.6), 100.0);
+                    context.buy(OrderKind::Stop(10.5), 100.0);
+                    context.buy(OrderKind::Limit(9.0), 100.0);
+                    context.buy(OrderKind::Market, 50.0);
+                }
+            })
+            .unwrap();
+
+        let reasons = report
+            .rejections
+            .iter()
+            .map(|rejection| (rejection.order.id, rejection.reason))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            vec![
+                (4, "Quantity is less than a lot"),
+                (0, "Limit price is outside the price band"),
+                (3, "Limit price is outside the price band"),
+            ]
+        );
+
+        let fills = report
+            .fills
+            .iter()
+            .map(|fill| (fill.order_id, fill.index, fill.price))
+            .collect::<Vec<_>>();
+        assert_eq!(fills, vec![(1, 1, 9.6), (2, 1, 10.5)]);
+    }
+
+    #[test]
+    fn test_day_orders_expire_at_session_end() {
+        assert_eq!(MarketRules::hnx().time_in_force, TimeInForce::Day);
+        assert_eq!(MarketRules::crypto().time_in_force, TimeInForce::Gtc);
+
+        // Thứ 6 giá không xuống tới 9.6, thứ 2 mới chạm
+        let mut candles = daily(&[0, 1, 4], &[10.0, 10.0, 10.0]);
+        candles[2].l = 9.5;
+
+        let backtest = Backtest::new(MarketRules::hose(), 100_000.0).unwrap();
+        let report = backtest
+            .run(&candles, &mut |context: &mut Context<'_>| {
+                if context.index() == 0 {
+                    context.buy(OrderKind::Limit(9.6), 100.0);
+                    context.submit_with(
+                        OrderSide::Buy,
+                        OrderKind::Limit(9.6),
+                        100.0,
+                        TimeInForce::Gtc,
+                    );
+                }
+            })
+            .unwrap();
+
+        // Lệnh Day đặt sau phiên thứ 5 sống hết phiên thứ 6 rồi bị huỷ
+        let rejections = report
+            .rejections
+            .iter()
+            .map(|rejection| (rejection.order.id, rejection.index, rejection.reason))
+            .collect::<Vec<_>>();
+        assert_eq!(rejections, vec![(0, 2, "Day order expired at session end")]);
+
+        let fills = report
+            .fills
+            .iter()
+            .map(|fill| (fill.order_id, fill.index, fill.price))
+            .collect::<Vec<_>>();
+        assert_eq!(fills, vec![(1, 2, 9.6)]);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let backtest = Backtest::new(MarketRules::hose(), 1.0).unwrap();
+        let mut noop = |_: &mut Context<'_>| {};
+        let unsorted = daily(&[1, 0], &[10.0, 10.0]);
+
+        assert!(backtest.run(&[], &mut noop).is_err());
+        assert!(backtest.run(&unsorted, &mut noop).is_err());
+        assert!(Backtest::new(MarketRules::hose(), 0.0).is_err());
+        assert!(
+            Backtest::new(
+                MarketRules {
+                    lot_size: Some(0.0),
+                    ..MarketRules::hose()
+                },
+                1.0
+            )
+            .is_err()
+        );
+    }
+}
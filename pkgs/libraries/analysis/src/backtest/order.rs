@@ -0,0 +1,118 @@
+use schemas::CandleStick;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderKind {
+    /// Khớp ở giá mở cửa của nến kế tiếp
+    Market,
+    /// Mua khi giá xuống tới, bán khi giá lên tới mức này
+    Limit(f64),
+    /// Lệnh điều kiện: mua khi giá lên tới, bán khi giá xuống tới mức này rồi khớp
+    /// như lệnh thị trường
+    Stop(f64),
+}
+
+/// Thời hạn hiệu lực của lệnh chưa khớp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeInForce {
+    /// Chỉ có hiệu lực trong phiên đầu tiên lệnh được đưa vào khớp, hết phiên
+    /// thì tự huỷ
+    Day,
+    /// Có hiệu lực tới khi khớp hoặc bị huỷ
+    Gtc,
+}
+
+/// Lệnh đang chờ khớp, có hiệu lực theo `time_in_force`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    pub id: usize,
+    pub side: OrderSide,
+    pub kind: OrderKind,
+    pub quantity: f64,
+    pub time_in_force: TimeInForce,
+    /// Index nến lúc đặt lệnh
+    pub submitted_at: usize,
+}
+
+/// Một lần khớp lệnh, `fee` gồm cả thuế bán
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub order_id: usize,
+    pub index: usize,
+    pub t: i32,
+    pub side: OrderSide,
+    pub price: f64,
+    pub quantity: f64,
+    pub fee: f64,
+}
+
+/// Lệnh bị từ chối lúc đặt, lúc khớp, hoặc hết hiệu lực khi sang phiên mới
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rejection {
+    pub order: Order,
+    pub index: usize,
+    pub reason: &'static str,
+}
+
+impl Order {
+    /// Giá khớp của lệnh trên `candle` khi giá bị kẹp trong `[floor, ceil]`,
+    /// `None` nếu giá chưa chạm điều kiện. Nến mở gap qua mức giá thì khớp ở
+    /// giá mở cửa.
+    pub(crate) fn match_price(&self, candle: &CandleStick, floor: f64, ceil: f64) -> Option<f64> {
+        let price = match (self.kind, self.side) {
+            (OrderKind::Market, _) => candle.o,
+            (OrderKind::Limit(price), OrderSide::Buy) if candle.l <= price => candle.o.min(price),
+            (OrderKind::Limit(price), OrderSide::Sell) if candle.h >= price => candle.o.max(price),
+            (OrderKind::Stop(price), OrderSide::Buy) if candle.h >= price => candle.o.max(price),
+            (OrderKind::Stop(price), OrderSide::Sell) if candle.l <= price => candle.o.min(price),
+            _ => return None,
+        };
+        Some(price.clamp(floor, ceil))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_price() {
+        let candle = CandleStick {
+            t: 0,
+            o: 10.0,
+            h: 11.0,
+            l: 9.0,
+            c: 10.5,
+            v: 0.0,
+        };
+        let order = |side, kind| Order {
+            id: 0,
+            side,
+            kind,
+            quantity: 100.0,
+            time_in_force: TimeInForce::Gtc,
+            submitted_at: 0,
+        };
+        let price = |side, kind| order(side, kind).match_price(&candle, 0.0, f64::INFINITY);
+
+        assert_eq!(price(OrderSide::Buy, OrderKind::Market), Some(10.0));
+        assert_eq!(price(OrderSide::Buy, OrderKind::Limit(9.5)), Some(9.5));
+        assert_eq!(price(OrderSide::Buy, OrderKind::Limit(10.5)), Some(10.0));
+        assert_eq!(price(OrderSide::Buy, OrderKind::Limit(8.5)), None);
+        assert_eq!(price(OrderSide::Sell, OrderKind::Limit(10.8)), Some(10.8));
+        assert_eq!(price(OrderSide::Sell, OrderKind::Limit(11.5)), None);
+        assert_eq!(price(OrderSide::Buy, OrderKind::Stop(10.8)), Some(10.8));
+        assert_eq!(price(OrderSide::Buy, OrderKind::Stop(9.5)), Some(10.0));
+        assert_eq!(price(OrderSide::Sell, OrderKind::Stop(9.5)), Some(9.5));
+        assert_eq!(price(OrderSide::Sell, OrderKind::Stop(8.0)), None);
+
+        // Giá khớp không vượt trần
+        let capped = order(OrderSide::Buy, OrderKind::Stop(10.8)).match_price(&candle, 9.5, 10.5);
+        assert_eq!(capped, Some(10.5));
+    }
+}
@@ -0,0 +1,172 @@
+use super::order::{Fill, Rejection};
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
+/// Một vòng mua - bán đã đóng. Giá vốn gồm phí mua, giá ra đã trừ phí và thuế
+/// bán; một lệnh bán ăn vào nhiều lô mua (FIFO) thì tách thành nhiều trade.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub entry_t: i32,
+    pub exit_t: i32,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub quantity: f64,
+    pub pnl: f64,
+}
+
+impl Trade {
+    pub fn return_rate(&self) -> f64 {
+        self.exit_price / self.entry_price - 1.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Statistics {
+    pub total_return: f64,
+    /// Tăng trưởng kép theo năm, `None` khi chuỗi không kéo dài theo thời gian
+    pub cagr: Option<f64>,
+    pub sharpe: Option<f64>,
+    pub sortino: Option<f64>,
+    /// Sụt giảm lớn nhất từ đỉnh của equity, dạng tỉ lệ dương
+    pub max_drawdown: f64,
+    /// Tỉ lệ trade có lãi, `None` khi chưa đóng trade nào
+    pub win_rate: Option<f64>,
+    pub trades: usize,
+    pub fees: f64,
+}
+
+impl Statistics {
+    /// Tính thống kê từ equity curve (theo từng nến) và các trade đã đóng.
+    /// Sharpe/Sortino được annualize theo số nến trung bình mỗi năm của chính
+    /// chuỗi, `risk_free_rate` là lãi suất phi rủi ro theo năm.
+    pub fn compute(
+        initial_cash: f64,
+        equity: &[(i32, f64)],
+        trades: &[Trade],
+        fees: f64,
+        risk_free_rate: f64,
+    ) -> Self {
+        let final_equity = equity.last().map_or(initial_cash, |&(_, value)| value);
+        let years = match (equity.first(), equity.last()) {
+            (Some(&(first, _)), Some(&(last, _))) => (last - first) as f64 / SECONDS_PER_YEAR,
+            _ => 0.0,
+        };
+
+        let mut previous = initial_cash;
+        let mut peak = initial_cash;
+        let mut max_drawdown: f64 = 0.0;
+        let mut returns = Vec::with_capacity(equity.len());
+
+        for &(_, value) in equity {
+            returns.push(value / previous - 1.0);
+            previous = value;
+            peak = peak.max(value);
+            max_drawdown = max_drawdown.max((peak - value) / peak);
+        }
+
+        let (sharpe, sortino) = if years > 0.0 && returns.len() > 1 {
+            let periods = (equity.len() - 1) as f64 / years;
+            let excess = returns
+                .iter()
+                .map(|value| value - risk_free_rate / periods)
+                .collect::<Vec<_>>();
+            let mean = excess.iter().sum::<f64>() / excess.len() as f64;
+            let deviation = (excess
+                .iter()
+                .map(|value| (value - mean).powi(2))
+                .sum::<f64>()
+                / (excess.len() - 1) as f64)
+                .sqrt();
+            let downside = (excess
+                .iter()
+                .map(|value| value.min(0.0).powi(2))
+                .sum::<f64>()
+                / excess.len() as f64)
+                .sqrt();
+            let annualize = |risk: f64| (risk > 0.0).then(|| mean / risk * periods.sqrt());
+
+            (annualize(deviation), annualize(downside))
+        } else {
+            (None, None)
+        };
+
+        let wins = trades.iter().filter(|trade| trade.pnl > 0.0).count();
+
+        Self {
+            total_return: final_equity / initial_cash - 1.0,
+            cagr: (years > 0.0).then(|| (final_equity / initial_cash).powf(1.0 / years) - 1.0),
+            sharpe,
+            sortino,
+            max_drawdown,
+            win_rate: (!trades.is_empty()).then(|| wins as f64 / trades.len() as f64),
+            trades: trades.len(),
+            fees,
+        }
+    }
+}
+
+/// Kết quả một lần backtest. Vị thế còn mở ở nến cuối được tính vào equity theo
+/// giá đóng cửa nhưng không nằm trong `trades`.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub fills: Vec<Fill>,
+    pub trades: Vec<Trade>,
+    pub rejections: Vec<Rejection>,
+    /// Equity sau mỗi nến: tiền mặt + cổ phiếu theo giá đóng cửa
+    pub equity: Vec<(i32, f64)>,
+    pub statistics: Statistics,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statistics() {
+        const DAY: i32 = 86400;
+        let equity = [100.0, 110.0, 99.0, 121.0, 115.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| (i as i32 * DAY, value))
+            .collect::<Vec<_>>();
+        let trade = |pnl: f64| Trade {
+            entry_t: 0,
+            exit_t: DAY,
+            entry_price: 10.0,
+            exit_price: 10.0 + pnl,
+            quantity: 1.0,
+            pnl,
+        };
+
+        let statistics = Statistics::compute(
+            100.0,
+            &equity,
+            &[trade(1.0), trade(-0.5), trade(2.0)],
+            3.0,
+            0.0,
+        );
+
+        assert!((statistics.total_return - 0.15).abs() < 1e-12);
+        assert!((statistics.max_drawdown - 0.1).abs() < 1e-12);
+        assert_eq!(statistics.win_rate, Some(2.0 / 3.0));
+        assert_eq!(statistics.trades, 3);
+        assert_eq!(statistics.fees, 3.0);
+
+        // 4 ngày, 365.25 nến mỗi năm
+        let years = 4.0 / 365.25;
+        let cagr = 1.15_f64.powf(1.0 / years) - 1.0;
+        assert!((statistics.cagr.unwrap() - cagr).abs() < 1e-9);
+
+        let returns = [0.0, 0.1, -0.1, 121.0 / 99.0 - 1.0, 115.0 / 121.0 - 1.0];
+        let mean = returns.iter().sum::<f64>() / 5.0;
+        let deviation = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / 4.0).sqrt();
+        let downside = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / 5.0).sqrt();
+        let periods = 365.25_f64.sqrt();
+
+        assert!((statistics.sharpe.unwrap() - mean / deviation * periods).abs() < 1e-9);
+        assert!((statistics.sortino.unwrap() - mean / downside * periods).abs() < 1e-9);
+
+        let flat = Statistics::compute(100.0, &[(0, 100.0)], &[], 0.0, 0.0);
+        assert_eq!((flat.cagr, flat.sharpe, flat.win_rate), (None, None, None));
+    }
+}
@@ -0,0 +1,119 @@
+use std::io::{Error, ErrorKind};
+
+use super::TimeInForce;
+use crate::TradingSession;
+
+/// Luật thị trường dùng khi khớp lệnh giả lập
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketRules {
+    /// Lịch giao dịch, xác định ngày giao dịch cho biên độ giá và T+n
+    pub session: TradingSession,
+    /// Biên độ so với giá tham chiếu (close của ngày giao dịch trước), `0.07` là
+    /// ±7%, `None` là không giới hạn
+    pub price_band: Option<f64>,
+    /// Lô chẵn, khối lượng đặt bị làm tròn xuống bội số của lô
+    pub lot_size: Option<f64>,
+    /// Số ngày giao dịch trước khi cổ phiếu mua về được phép bán
+    pub settlement_days: usize,
+    /// Phí môi giới theo giá trị khớp
+    pub buy_fee: f64,
+    pub sell_fee: f64,
+    /// Thuế thu nhập tính trên giá trị bán
+    pub sell_tax: f64,
+    /// Hiệu lực mặc định của lệnh đặt qua `Context::submit`
+    pub time_in_force: TimeInForce,
+}
+
+impl MarketRules {
+    /// HOSE: biên độ ±7%, lô 100, T+2, phí 0.15% mỗi chiều, thuế bán 0.1%, lệnh
+    /// chưa khớp hết hiệu lực cuối phiên
+    pub fn hose() -> Self {
+        Self {
+            session: TradingSession::hose(),
+            price_band: Some(0.07),
+            lot_size: Some(100.0),
+            settlement_days: 2,
+            buy_fee: 0.0015,
+            sell_fee: 0.0015,
+            sell_tax: 0.001,
+            time_in_force: TimeInForce::Day,
+        }
+    }
+
+    /// HNX: như HOSE nhưng biên độ ±10%
+    pub fn hnx() -> Self {
+        Self {
+            session: TradingSession::hnx(),
+            price_band: Some(0.1),
+            ..Self::hose()
+        }
+    }
+
+    /// Crypto: không biên độ, không lô, tiền và coin về ngay, phí taker 0.1%, lệnh
+    /// chờ tới khi khớp hoặc bị huỷ
+    pub fn crypto() -> Self {
+        Self {
+            session: TradingSession::crypto(),
+            price_band: None,
+            lot_size: None,
+            settlement_days: 0,
+            buy_fee: 0.001,
+            sell_fee: 0.001,
+            sell_tax: 0.0,
+            time_in_force: TimeInForce::Gtc,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        self.session.validate()?;
+        let rate = |value: f64| (0.0..1.0).contains(&value);
+
+        if !self.price_band.is_none_or(|band| 0.0 < band && band < 1.0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Price band must be within (0, 1)",
+            ));
+        }
+        if !self.lot_size.is_none_or(|lot| lot > 0.0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Lot size must be positive",
+            ));
+        }
+        if !(rate(self.buy_fee) && rate(self.sell_fee) && rate(self.sell_tax + self.sell_fee)) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Fees and tax must be within [0, 1)",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Làm tròn xuống theo lô chẵn
+    pub(crate) fn round_lot(&self, quantity: f64) -> f64 {
+        match self.lot_size {
+            Some(lot) => (quantity / lot).floor() * lot,
+            None => quantity,
+        }
+    }
+
+    /// Giá sàn và giá trần theo giá tham chiếu
+    pub(crate) fn band(&self, reference: Option<f64>) -> (f64, f64) {
+        match (self.price_band, reference) {
+            (Some(band), Some(reference)) => (reference * (1.0 - band), reference * (1.0 + band)),
+            _ => (0.0, f64::INFINITY),
+        }
+    }
+
+    /// Ngày đầu tiên cổ phiếu mua trong ngày `day` được phép bán
+    pub(crate) fn settlement_day(&self, day: i64) -> i64 {
+        let mut settled = day;
+        for _ in 0..self.settlement_days {
+            settled += 1;
+            while !self.session.is_trading_day(settled) {
+                settled += 1;
+            }
+        }
+        settled
+    }
+}
@@ -0,0 +1,150 @@
+//! Chỉ báo kỹ thuật dạng streaming: mỗi chỉ báo giữ state riêng và nhận từng nến
+//! một qua `Indicator::next`, hoặc cả chuỗi qua `Indicator::compute`.
+//!
+//! ## Idea
+//! Mọi chỉ báo đều cần một số nến đầu để "làm nóng" (SMA cần đủ `period` giá,
+//! ADX cần gần `2 * period`...). Trong giai đoạn đó `next` trả `None` thay vì một
+//! giá trị tính trên dữ liệu thiếu, và `warm_up()` cho biết cần bao nhiêu nến
+//! trước khi có giá trị đầu tiên — caller dùng nó để lấy thêm look-back:
+//!
+//! ```text
+//! nến:     c0    c1    c2    c3    c4    c5
+//! SMA(3):  None  None  2.0   3.0   4.0   5.0      warm_up() = 2
+//! ```
+//!
+//! `compute` reset state rồi chạy `next` trên cả slice, nên kết quả luôn cùng độ
+//! dài và thẳng hàng với input. Sau `compute` state nằm ở nến cuối, gọi tiếp
+//! `next` với nến mới là chạy streaming tiếp được.
+//!
+//! Các đường trung bình (`SimpleMovingAverage`, `ExponentialMovingAverage`,
+//! `WeightedMovingAverage`) còn có `update(f64)` để làm mượt chuỗi bất kỳ — RSI,
+//! MACD, Stochastic... dựng trên chúng, và `calculate_rrg` dùng WMA.
+//!
+//! `IndicatorSpec` là dạng chuỗi (`rsi:14`, `macd:12:26:9`) để API nhận danh
+//! sách chỉ báo từ query string và tính ra các đường thẳng hàng với nến.
+
+mod momentum;
+mod moving_average;
+mod spec;
+mod trend;
+mod volatility;
+mod volume;
+
+pub use momentum::*;
+pub use moving_average::*;
+pub use spec::*;
+pub use trend::*;
+pub use volatility::*;
+pub use volume::*;
+
+use schemas::CandleStick;
+
+pub trait Indicator {
+    type Output;
+
+    /// Nạp thêm một nến, trả `None` khi chưa đủ dữ liệu để tính
+    fn next(&mut self, candle: &CandleStick) -> Option<Self::Output>;
+
+    /// Xoá toàn bộ state, như vừa khởi tạo
+    fn reset(&mut self);
+
+    /// Số nến đầu tiên mà `next` trả `None`
+    fn warm_up(&self) -> usize;
+
+    /// Tính chỉ báo trên cả chuỗi nến, kết quả thẳng hàng với `candles`
+    fn compute(&mut self, candles: &[CandleStick]) -> Vec<Option<Self::Output>> {
+        self.reset();
+        candles.iter().map(|candle| self.next(candle)).collect()
+    }
+}
+
+/// True range của `candle` so với giá đóng cửa nến trước (nếu có)
+fn true_range(candle: &CandleStick, prev_close: Option<f64>) -> f64 {
+    match prev_close {
+        Some(prev_close) => (candle.h - candle.l)
+            .max((candle.h - prev_close).abs())
+            .max((candle.l - prev_close).abs()),
+        None => candle.h - candle.l,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use schemas::CandleStick;
+
+    pub fn candles_from_closes(closes: &[f64]) -> Vec<CandleStick> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| CandleStick {
+                t: i as i32 * 86400,
+                o: c,
+                h: c,
+                c,
+                l: c,
+                v: 0.0,
+            })
+            .collect()
+    }
+
+    /// 30 nến ngày có OHLCV đầy đủ, golden value của các test được tính độc lập
+    /// theo công thức gốc (Wilder, Appel, Lane, Granville, Bollinger)
+    pub fn candles() -> Vec<CandleStick> {
+        const OHLCV: [(f64, f64, f64, f64, f64); 30] = [
+            // (o, h, l, c, v)
+            (50.16, 50.97, 49.59, 49.72, 564900.0),
+            (49.71, 50.46, 49.40, 50.02, 436700.0),
+            (50.35, 50.71, 48.81, 49.58, 810600.0),
+            (50.07, 51.43, 49.42, 50.87, 662900.0),
+            (50.62, 52.04, 49.44, 51.18, 396100.0),
+            (51.13, 52.59, 50.92, 51.70, 114100.0),
+            (51.98, 52.70, 50.59, 50.88, 600800.0),
+            (50.90, 51.12, 49.91, 50.69, 744800.0),
+            (50.49, 52.72, 50.02, 51.72, 734300.0),
+            (51.35, 52.33, 50.51, 51.67, 913500.0),
+            (51.57, 53.19, 51.55, 52.45, 437400.0),
+            (52.06, 52.70, 51.88, 52.26, 823300.0),
+            (51.83, 52.07, 49.76, 50.84, 594200.0),
+            (50.83, 50.95, 50.03, 50.57, 377600.0),
+            (50.45, 51.58, 48.61, 49.56, 490800.0),
+            (49.63, 50.78, 48.29, 48.48, 414100.0),
+            (48.73, 50.33, 48.51, 49.33, 728600.0),
+            (48.88, 50.20, 48.69, 50.16, 995000.0),
+            (50.34, 50.63, 48.47, 49.24, 242100.0),
+            (49.65, 50.68, 48.95, 49.55, 247000.0),
+            (49.83, 50.79, 48.29, 48.64, 216900.0),
+            (48.36, 48.65, 47.39, 48.28, 742500.0),
+            (48.06, 48.87, 46.66, 47.38, 892400.0),
+            (47.09, 47.76, 45.03, 46.22, 488300.0),
+            (46.10, 46.44, 44.18, 45.05, 300000.0),
+            (44.90, 45.36, 43.81, 44.76, 921200.0),
+            (44.89, 45.57, 44.13, 44.88, 774000.0),
+            (45.28, 45.70, 45.03, 45.09, 493600.0),
+            (45.04, 45.83, 43.16, 44.27, 810800.0),
+            (44.72, 46.94, 43.93, 45.82, 548100.0),
+        ];
+
+        OHLCV
+            .iter()
+            .enumerate()
+            .map(|(i, &(o, h, l, c, v))| CandleStick {
+                t: 1_700_000_000 + i as i32 * 86400,
+                o,
+                h,
+                c,
+                l,
+                v,
+            })
+            .collect()
+    }
+
+    pub fn assert_series(actual: &[Option<f64>], expected: &[(usize, f64)], eps: f64) {
+        for &(i, value) in expected {
+            let got = actual[i].unwrap_or_else(|| panic!("index {i} vẫn đang warm-up"));
+            assert!(
+                (got - value).abs() < eps,
+                "index {i}: expected {value}, got {got}"
+            );
+        }
+    }
+}
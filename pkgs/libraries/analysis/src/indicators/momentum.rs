@@ -0,0 +1,308 @@
+use std::collections::VecDeque;
+
+use schemas::CandleStick;
+
+use super::{ExponentialMovingAverage, Indicator, SimpleMovingAverage};
+
+// ==================== RSI ====================
+
+/// RSI của Wilder: `100 - 100 / (1 + avg_gain / avg_loss)`, hai trung bình được
+/// làm mượt bằng EMA Wilder. Cần `period` lần thay đổi giá, tức `period + 1` nến.
+#[derive(Debug, Clone)]
+pub struct RelativeStrengthIndex {
+    gain: ExponentialMovingAverage,
+    loss: ExponentialMovingAverage,
+    prev_close: Option<f64>,
+}
+
+impl RelativeStrengthIndex {
+    pub fn new(period: usize) -> Self {
+        Self {
+            gain: ExponentialMovingAverage::wilder(period),
+            loss: ExponentialMovingAverage::wilder(period),
+            prev_close: None,
+        }
+    }
+}
+
+impl Default for RelativeStrengthIndex {
+    fn default() -> Self {
+        Self::new(14)
+    }
+}
+
+impl Indicator for RelativeStrengthIndex {
+    type Output = f64;
+
+    fn next(&mut self, candle: &CandleStick) -> Option<f64> {
+        let change = candle.c - self.prev_close.replace(candle.c)?;
+        let avg_gain = self.gain.update(change.max(0.0));
+        let avg_loss = self.loss.update((-change).max(0.0));
+
+        match (avg_gain?, avg_loss?) {
+            (0.0, 0.0) => Some(50.0),
+            (_, 0.0) => Some(100.0),
+            (gain, loss) => Some(100.0 - 100.0 / (1.0 + gain / loss)),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.gain.reset();
+        self.loss.reset();
+        self.prev_close = None;
+    }
+
+    fn warm_up(&self) -> usize {
+        self.gain.period()
+    }
+}
+
+// ==================== MACD ====================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdOutput {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+/// MACD của Appel: `EMA(fast) - EMA(slow)`, signal là EMA của chính đường MACD.
+///
+/// Chỉ trả giá trị khi cả ba đường đã có, nên warm-up là
+/// `(slow - 1) + (signal - 1)` nến.
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: ExponentialMovingAverage,
+    slow: ExponentialMovingAverage,
+    signal: ExponentialMovingAverage,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        assert!(fast < slow, "fast period phải nhỏ hơn slow period");
+
+        Self {
+            fast: ExponentialMovingAverage::new(fast),
+            slow: ExponentialMovingAverage::new(slow),
+            signal: ExponentialMovingAverage::new(signal),
+        }
+    }
+}
+
+impl Default for Macd {
+    fn default() -> Self {
+        Self::new(12, 26, 9)
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdOutput;
+
+    fn next(&mut self, candle: &CandleStick) -> Option<MacdOutput> {
+        let fast = self.fast.update(candle.c);
+        let slow = self.slow.update(candle.c);
+        let macd = fast? - slow?;
+        let signal = self.signal.update(macd)?;
+
+        Some(MacdOutput {
+            macd,
+            signal,
+            histogram: macd - signal,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.fast.reset();
+        self.slow.reset();
+        self.signal.reset();
+    }
+
+    fn warm_up(&self) -> usize {
+        self.slow.period() + self.signal.period() - 2
+    }
+}
+
+// ==================== Stochastic ====================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StochasticOutput {
+    pub k: f64,
+    pub d: f64,
+}
+
+/// Stochastic oscillator của Lane (bản "full"):
+/// - %K thô = `100 * (close - LL) / (HH - LL)` trên `period` nến gần nhất
+/// - %K = SMA(`smooth_k`) của %K thô, %D = SMA(`smooth_d`) của %K
+///
+/// `smooth_k = 1` là fast stochastic, `(14, 3, 3)` là slow stochastic quen thuộc.
+/// Khi `HH == LL` (giá đi ngang) %K thô lấy 50 thay vì chia cho 0.
+#[derive(Debug, Clone)]
+pub struct Stochastic {
+    period: usize,
+    window: VecDeque<(f64, f64)>,
+    smooth_k: SimpleMovingAverage,
+    smooth_d: SimpleMovingAverage,
+}
+
+impl Stochastic {
+    pub fn new(period: usize, smooth_k: usize, smooth_d: usize) -> Self {
+        assert!(period > 0, "period phải lớn hơn 0");
+
+        Self {
+            period,
+            window: VecDeque::with_capacity(period),
+            smooth_k: SimpleMovingAverage::new(smooth_k),
+            smooth_d: SimpleMovingAverage::new(smooth_d),
+        }
+    }
+}
+
+impl Default for Stochastic {
+    fn default() -> Self {
+        Self::new(14, 3, 3)
+    }
+}
+
+impl Indicator for Stochastic {
+    type Output = StochasticOutput;
+
+    fn next(&mut self, candle: &CandleStick) -> Option<StochasticOutput> {
+        if self.window.len() == self.period {
+            self.window.pop_front();
+        }
+        self.window.push_back((candle.h, candle.l));
+        if self.window.len() < self.period {
+            return None;
+        }
+
+        let (highest, lowest) = self
+            .window
+            .iter()
+            .fold((f64::MIN, f64::MAX), |(hh, ll), &(h, l)| {
+                (hh.max(h), ll.min(l))
+            });
+        let raw_k = if highest > lowest {
+            100.0 * (candle.c - lowest) / (highest - lowest)
+        } else {
+            50.0
+        };
+
+        let k = self.smooth_k.update(raw_k)?;
+        let d = self.smooth_d.update(k)?;
+        Some(StochasticOutput { k, d })
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.smooth_k.reset();
+        self.smooth_d.reset();
+    }
+
+    fn warm_up(&self) -> usize {
+        self.period + self.smooth_k.period() + self.smooth_d.period() - 3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::{assert_series, candles, candles_from_closes};
+
+    // Chuỗi giá ví dụ của StockCharts cho RSI 14 ngày
+    const CLOSES: [f64; 33] = [
+        44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61,
+        46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64, 46.21, 46.25, 45.71, 46.45, 45.78, 45.35,
+        44.03, 44.18, 44.22, 44.57, 43.42, 42.66, 43.13,
+    ];
+
+    #[test]
+    fn test_rsi_golden_values() {
+        let mut rsi = RelativeStrengthIndex::default();
+        let result = rsi.compute(&candles_from_closes(&CLOSES));
+
+        assert_eq!(rsi.warm_up(), 14);
+        assert!(result[..14].iter().all(Option::is_none));
+        assert_series(
+            &result,
+            &[
+                (14, 70.4641),
+                (15, 66.2496),
+                (20, 62.8807),
+                (26, 40.0194),
+                (32, 37.7888),
+            ],
+            1e-3,
+        );
+    }
+
+    #[test]
+    fn test_rsi_flat_and_one_sided_series() {
+        let mut rsi = RelativeStrengthIndex::new(3);
+
+        let flat = rsi.compute(&candles_from_closes(&[10.0; 6]));
+        assert_eq!(flat[5], Some(50.0));
+
+        let rising = rsi.compute(&candles_from_closes(&[1.0, 2.0, 3.0, 4.0, 5.0]));
+        assert_eq!(rising[4], Some(100.0));
+    }
+
+    #[test]
+    fn test_macd_golden_values() {
+        let mut macd = Macd::new(5, 10, 4);
+        let result = macd.compute(&candles());
+
+        assert_eq!(macd.warm_up(), 12);
+        assert!(result[..12].iter().all(Option::is_none));
+
+        let line: Vec<_> = result.iter().map(|o| o.map(|o| o.macd)).collect();
+        let signal: Vec<_> = result.iter().map(|o| o.map(|o| o.signal)).collect();
+        let histogram: Vec<_> = result.iter().map(|o| o.map(|o| o.histogram)).collect();
+        assert_series(
+            &line,
+            &[(12, 0.2963), (18, -0.3603), (24, -1.1005), (29, -0.7527)],
+            1e-3,
+        );
+        assert_series(
+            &signal,
+            &[(12, 0.4716), (18, -0.3141), (24, -0.8243), (29, -0.9357)],
+            1e-3,
+        );
+        assert_series(
+            &histogram,
+            &[(12, -0.1752), (18, -0.0462), (24, -0.2763), (29, 0.1830)],
+            1e-3,
+        );
+    }
+
+    #[test]
+    fn test_stochastic_golden_values() {
+        let mut stochastic = Stochastic::new(5, 3, 3);
+        let result = stochastic.compute(&candles());
+
+        assert_eq!(stochastic.warm_up(), 8);
+        assert!(result[..8].iter().all(Option::is_none));
+
+        let k: Vec<_> = result.iter().map(|o| o.map(|o| o.k)).collect();
+        let d: Vec<_> = result.iter().map(|o| o.map(|o| o.d)).collect();
+        assert_series(
+            &k,
+            &[(8, 53.8150), (15, 16.2220), (22, 19.2033), (29, 45.5390)],
+            1e-3,
+        );
+        assert_series(
+            &d,
+            &[(8, 59.2084), (15, 27.9176), (22, 26.8741), (29, 32.9262)],
+            1e-3,
+        );
+
+        let mut fast = Stochastic::new(5, 1, 1);
+        let raw_k = fast.compute(&candles());
+        assert!(raw_k[..4].iter().all(Option::is_none));
+        assert!(
+            raw_k[4..]
+                .iter()
+                .flatten()
+                .all(|o| o.k == o.d && (0.0..=100.0).contains(&o.k))
+        );
+    }
+}
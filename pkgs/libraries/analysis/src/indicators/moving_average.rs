@@ -0,0 +1,288 @@
+use std::collections::VecDeque;
+
+use schemas::CandleStick;
+
+use super::Indicator;
+
+// ==================== SMA ====================
+
+/// Trung bình cộng `period` giá đóng cửa gần nhất
+#[derive(Debug, Clone)]
+pub struct SimpleMovingAverage {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl SimpleMovingAverage {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "period phải lớn hơn 0");
+
+        Self {
+            period,
+            window: VecDeque::with_capacity(period),
+            sum: 0.0,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        if self.window.len() == self.period {
+            self.sum -= self.window.pop_front().unwrap_or_default();
+        }
+        self.window.push_back(value);
+        self.sum += value;
+
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+}
+
+impl Indicator for SimpleMovingAverage {
+    type Output = f64;
+
+    fn next(&mut self, candle: &CandleStick) -> Option<f64> {
+        self.update(candle.c)
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.sum = 0.0;
+    }
+
+    fn warm_up(&self) -> usize {
+        self.period - 1
+    }
+}
+
+// ==================== EMA ====================
+
+/// EMA với `alpha = 2 / (period + 1)`.
+///
+/// Giá trị đầu tiên là SMA của `period` giá đầu (giống TA-Lib/StockCharts), thay
+/// vì lấy luôn giá đầu tiên làm EMA — cách đó làm vài chục điểm đầu lệch hẳn
+/// về giá mở đầu chuỗi.
+#[derive(Debug, Clone)]
+pub struct ExponentialMovingAverage {
+    period: usize,
+    alpha: f64,
+    seed: SimpleMovingAverage,
+    current_ema: Option<f64>,
+}
+
+impl ExponentialMovingAverage {
+    pub fn new(period: usize) -> Self {
+        Self::with_alpha(period, 2.0 / (period as f64 + 1.0))
+    }
+
+    /// EMA của Wilder (`alpha = 1 / period`), dùng cho RSI/ATR/ADX
+    pub fn wilder(period: usize) -> Self {
+        Self::with_alpha(period, 1.0 / period as f64)
+    }
+
+    fn with_alpha(period: usize, alpha: f64) -> Self {
+        Self {
+            period,
+            alpha,
+            seed: SimpleMovingAverage::new(period),
+            current_ema: None,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.current_ema
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.current_ema = match self.current_ema {
+            Some(prev_ema) => Some(self.alpha * value + (1.0 - self.alpha) * prev_ema),
+            None => self.seed.update(value),
+        };
+        self.current_ema
+    }
+}
+
+impl Indicator for ExponentialMovingAverage {
+    type Output = f64;
+
+    fn next(&mut self, candle: &CandleStick) -> Option<f64> {
+        self.update(candle.c)
+    }
+
+    fn reset(&mut self) {
+        self.seed.reset();
+        self.current_ema = None;
+    }
+
+    fn warm_up(&self) -> usize {
+        self.period - 1
+    }
+}
+
+// ==================== WMA ====================
+
+/// Trung bình có trọng số tuyến tính: giá mới nhất nặng `period`, cũ nhất nặng 1.
+///
+/// Khi trượt cửa sổ, mọi trọng số cũ giảm đi 1 nên
+/// `numerator' = numerator - sum + period * value` — O(1) mỗi bước.
+#[derive(Debug, Clone)]
+pub struct WeightedMovingAverage {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+    numerator: f64,
+}
+
+impl WeightedMovingAverage {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "period phải lớn hơn 0");
+
+        Self {
+            period,
+            window: VecDeque::with_capacity(period),
+            sum: 0.0,
+            numerator: 0.0,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        if self.window.len() == self.period {
+            self.numerator += self.period as f64 * value - self.sum;
+            self.sum -= self.window.pop_front().unwrap_or_default();
+        } else {
+            self.numerator += (self.window.len() + 1) as f64 * value;
+        }
+        self.window.push_back(value);
+        self.sum += value;
+
+        (self.window.len() == self.period).then(|| {
+            let weight_sum = (self.period * (self.period + 1) / 2) as f64;
+            self.numerator / weight_sum
+        })
+    }
+
+    /// WMA trên cả slice, bỏ các điểm warm-up (`data.len() - period + 1` phần tử)
+    pub fn apply(data: &[f64], period: usize) -> Vec<f64> {
+        let mut wma = Self::new(period);
+        data.iter().filter_map(|&value| wma.update(value)).collect()
+    }
+}
+
+impl Indicator for WeightedMovingAverage {
+    type Output = f64;
+
+    fn next(&mut self, candle: &CandleStick) -> Option<f64> {
+        self.update(candle.c)
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.sum = 0.0;
+        self.numerator = 0.0;
+    }
+
+    fn warm_up(&self) -> usize {
+        self.period - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::{assert_series, candles_from_closes};
+
+    // Chuỗi giá ví dụ của StockCharts cho SMA/EMA 10 ngày
+    const CLOSES: [f64; 30] = [
+        22.27, 22.19, 22.08, 22.17, 22.18, 22.13, 22.23, 22.43, 22.24, 22.29, 22.15, 22.39, 22.38,
+        22.61, 23.36, 24.05, 23.75, 23.83, 23.95, 23.63, 23.82, 23.87, 23.65, 23.19, 23.10, 23.33,
+        22.68, 23.10, 22.40, 22.17,
+    ];
+
+    #[test]
+    fn test_sma_golden_values() {
+        let mut sma = SimpleMovingAverage::new(10);
+        let result = sma.compute(&candles_from_closes(&CLOSES));
+
+        assert_eq!(result.len(), CLOSES.len());
+        assert!(result[..9].iter().all(Option::is_none));
+        assert_series(
+            &result,
+            &[
+                (9, 22.2210),
+                (14, 22.4210),
+                (19, 23.2100),
+                (24, 23.6840),
+                (29, 23.1310),
+            ],
+            1e-3,
+        );
+    }
+
+    #[test]
+    fn test_ema_golden_values() {
+        let mut ema = ExponentialMovingAverage::new(10);
+        let result = ema.compute(&candles_from_closes(&CLOSES));
+
+        assert_eq!(ema.warm_up(), 9);
+        assert!(result[..9].iter().all(Option::is_none));
+        assert_series(
+            &result,
+            &[
+                (9, 22.2210),
+                (10, 22.2081),
+                (15, 22.7952),
+                (20, 23.4271),
+                (25, 23.3902),
+                (29, 22.9150),
+            ],
+            1e-3,
+        );
+    }
+
+    #[test]
+    fn test_wma_matches_direct_formula() {
+        let period = 5;
+        let expected: Vec<f64> = CLOSES
+            .windows(period)
+            .map(|w| {
+                let numerator: f64 = w.iter().enumerate().map(|(i, x)| (i + 1) as f64 * x).sum();
+                numerator / 15.0
+            })
+            .collect();
+
+        let actual = WeightedMovingAverage::apply(&CLOSES, period);
+        assert_eq!(actual.len(), CLOSES.len() - period + 1);
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-9, "{a} != {e}");
+        }
+
+        assert!(WeightedMovingAverage::apply(&CLOSES[..4], period).is_empty());
+    }
+
+    #[test]
+    fn test_compute_resets_and_streaming_continues() {
+        let candles = candles_from_closes(&CLOSES);
+        let mut streaming = ExponentialMovingAverage::new(10);
+        let expected: Vec<_> = candles.iter().map(|c| streaming.next(c)).collect();
+
+        let mut ema = ExponentialMovingAverage::new(10);
+        ema.compute(&candles[..20]);
+        // Gọi lại compute trên cả chuỗi phải cho kết quả như chạy mới
+        assert_eq!(ema.compute(&candles), expected);
+
+        let mut ema = ExponentialMovingAverage::new(10);
+        let mut result = ema.compute(&candles[..20]);
+        result.extend(candles[20..].iter().map(|c| ema.next(c)));
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,383 @@
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+
+use schemas::CandleStick;
+
+use super::{
+    AverageDirectionalIndex, AverageTrueRange, BollingerBands, ExponentialMovingAverage, Indicator,
+    Macd, OnBalanceVolume, RelativeStrengthIndex, SimpleMovingAverage, Stochastic,
+    VolumeWeightedAveragePrice, WeightedMovingAverage,
+};
+
+// ==================== Spec ====================
+
+/// Chu kỳ lớn nhất nhận khi parse, chặn buffer khổng lồ từ query string
+pub const MAX_INDICATOR_PERIOD: usize = 1000;
+
+/// Mô tả một chỉ báo dạng chuỗi `name:param:param...`, vd `rsi:14`,
+/// `macd:12:26:9`, `bb:20:2`. Bỏ tham số thì dùng giá trị mặc định (`rsi` =
+/// `rsi:14`). `Display` in lại dạng đầy đủ nên parse/in là round-trip.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndicatorSpec {
+    Sma(usize),
+    Ema(usize),
+    Wma(usize),
+    Rsi(usize),
+    Macd {
+        fast: usize,
+        slow: usize,
+        signal: usize,
+    },
+    Bollinger {
+        period: usize,
+        multiplier: f64,
+    },
+    Atr(usize),
+    Stochastic {
+        period: usize,
+        smooth_k: usize,
+        smooth_d: usize,
+    },
+    Obv,
+    Adx(usize),
+    /// VWAP reset theo phiên `length` giây, lệch `offset` giây; `length = 0` là
+    /// cộng dồn từ nến đầu tiên
+    Vwap {
+        length: i64,
+        offset: i64,
+    },
+}
+
+/// Một đường của chỉ báo sau khi tính, thẳng hàng với chuỗi nến đầu vào
+pub type IndicatorLine = (&'static str, Vec<Option<f64>>);
+
+impl IndicatorSpec {
+    /// Số nến đầu tiên chưa có giá trị
+    pub fn warm_up(&self) -> usize {
+        match *self {
+            Self::Sma(period) | Self::Ema(period) | Self::Wma(period) | Self::Atr(period) => {
+                period.saturating_sub(1)
+            }
+            Self::Rsi(period) => period,
+            Self::Macd { slow, signal, .. } => slow.saturating_add(signal).saturating_sub(2),
+            Self::Bollinger { period, .. } => period.saturating_sub(1),
+            Self::Stochastic {
+                period,
+                smooth_k,
+                smooth_d,
+            } => period
+                .saturating_add(smooth_k)
+                .saturating_add(smooth_d)
+                .saturating_sub(3),
+            Self::Adx(period) => period.saturating_mul(2).saturating_sub(1),
+            Self::Obv | Self::Vwap { .. } => 0,
+        }
+    }
+
+    /// Tính chỉ báo trên `candles`, chỉ báo nhiều đường (MACD, Bollinger...) trả
+    /// mỗi đường một phần tử
+    pub fn compute(&self, candles: &[CandleStick]) -> Vec<IndicatorLine> {
+        match *self {
+            Self::Sma(period) => single(SimpleMovingAverage::new(period), candles),
+            Self::Ema(period) => single(ExponentialMovingAverage::new(period), candles),
+            Self::Wma(period) => single(WeightedMovingAverage::new(period), candles),
+            Self::Rsi(period) => single(RelativeStrengthIndex::new(period), candles),
+            Self::Atr(period) => single(AverageTrueRange::new(period), candles),
+            Self::Obv => single(OnBalanceVolume::new(), candles),
+            Self::Vwap { length, offset } => {
+                let vwap = VolumeWeightedAveragePrice::new();
+                if length > 0 {
+                    single(vwap.with_session(length, offset), candles)
+                } else {
+                    single(vwap, candles)
+                }
+            }
+            Self::Macd { fast, slow, signal } => {
+                let result = Macd::new(fast, slow, signal).compute(candles);
+                vec![
+                    ("macd", result.iter().map(|o| o.map(|o| o.macd)).collect()),
+                    (
+                        "signal",
+                        result.iter().map(|o| o.map(|o| o.signal)).collect(),
+                    ),
+                    (
+                        "histogram",
+                        result.iter().map(|o| o.map(|o| o.histogram)).collect(),
+                    ),
+                ]
+            }
+            Self::Bollinger { period, multiplier } => {
+                let result = BollingerBands::new(period, multiplier).compute(candles);
+                vec![
+                    ("upper", result.iter().map(|o| o.map(|o| o.upper)).collect()),
+                    (
+                        "middle",
+                        result.iter().map(|o| o.map(|o| o.middle)).collect(),
+                    ),
+                    ("lower", result.iter().map(|o| o.map(|o| o.lower)).collect()),
+                ]
+            }
+            Self::Stochastic {
+                period,
+                smooth_k,
+                smooth_d,
+            } => {
+                let result = Stochastic::new(period, smooth_k, smooth_d).compute(candles);
+                vec![
+                    ("k", result.iter().map(|o| o.map(|o| o.k)).collect()),
+                    ("d", result.iter().map(|o| o.map(|o| o.d)).collect()),
+                ]
+            }
+            Self::Adx(period) => {
+                let result = AverageDirectionalIndex::new(period).compute(candles);
+                vec![
+                    ("adx", result.iter().map(|o| o.map(|o| o.adx)).collect()),
+                    (
+                        "plus_di",
+                        result.iter().map(|o| o.map(|o| o.plus_di)).collect(),
+                    ),
+                    (
+                        "minus_di",
+                        result.iter().map(|o| o.map(|o| o.minus_di)).collect(),
+                    ),
+                ]
+            }
+        }
+    }
+
+    /// Parse danh sách spec cách nhau bởi dấu phẩy, vd `rsi:14,macd,bb:20:2`
+    pub fn parse_list(specs: &str) -> Result<Vec<Self>, Error> {
+        specs
+            .split(',')
+            .map(str::trim)
+            .filter(|spec| !spec.is_empty())
+            .map(Self::from_str)
+            .collect()
+    }
+}
+
+fn single<I: Indicator<Output = f64>>(
+    mut indicator: I,
+    candles: &[CandleStick],
+) -> Vec<IndicatorLine> {
+    vec![("value", indicator.compute(candles))]
+}
+
+impl FromStr for IndicatorSpec {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self, Error> {
+        let mut parts = spec.trim().split(':');
+        let name = parts.next().unwrap_or_default().to_lowercase();
+        let params = parts.collect::<Vec<_>>();
+
+        let invalid = |reason: String| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid indicator `{spec}`: {reason}"),
+            )
+        };
+        let arity = |max: usize| {
+            if params.len() > max {
+                Err(invalid(format!("expected at most {max} parameter(s)")))
+            } else {
+                Ok(())
+            }
+        };
+        let period = |i: usize, default: usize| -> Result<usize, Error> {
+            match params.get(i) {
+                Some(value) => match value.parse::<usize>() {
+                    Ok(value) if value > MAX_INDICATOR_PERIOD => Err(invalid(format!(
+                        "`{value}` exceeds the maximum period {MAX_INDICATOR_PERIOD}"
+                    ))),
+                    Ok(value) if value > 0 => Ok(value),
+                    _ => Err(invalid(format!("`{value}` is not a positive integer"))),
+                },
+                None => Ok(default),
+            }
+        };
+        let number = |i: usize, default: f64| -> Result<f64, Error> {
+            match params.get(i) {
+                Some(value) => value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .ok_or_else(|| invalid(format!("`{value}` is not a number"))),
+                None => Ok(default),
+            }
+        };
+        let seconds = |i: usize| -> Result<i64, Error> {
+            match params.get(i) {
+                Some(value) => value
+                    .parse::<i64>()
+                    .map_err(|_| invalid(format!("`{value}` is not an integer"))),
+                None => Ok(0),
+            }
+        };
+
+        match name.as_str() {
+            "sma" => arity(1).and(period(0, 20).map(Self::Sma)),
+            "ema" => arity(1).and(period(0, 20).map(Self::Ema)),
+            "wma" => arity(1).and(period(0, 20).map(Self::Wma)),
+            "rsi" => arity(1).and(period(0, 14).map(Self::Rsi)),
+            "atr" => arity(1).and(period(0, 14).map(Self::Atr)),
+            "adx" => arity(1).and(period(0, 14).map(Self::Adx)),
+            "obv" => arity(0).map(|_| Self::Obv),
+            "macd" => {
+                arity(3)?;
+                let (fast, slow, signal) = (period(0, 12)?, period(1, 26)?, period(2, 9)?);
+                if fast >= slow {
+                    return Err(invalid("fast period must be less than slow period".into()));
+                }
+                Ok(Self::Macd { fast, slow, signal })
+            }
+            "bb" | "bollinger" => {
+                arity(2)?;
+                Ok(Self::Bollinger {
+                    period: period(0, 20)?,
+                    multiplier: number(1, 2.0)?,
+                })
+            }
+            "stoch" | "stochastic" => {
+                arity(3)?;
+                Ok(Self::Stochastic {
+                    period: period(0, 14)?,
+                    smooth_k: period(1, 3)?,
+                    smooth_d: period(2, 3)?,
+                })
+            }
+            "vwap" => {
+                arity(2)?;
+                let (length, offset) = (seconds(0)?, seconds(1)?);
+                if length < 0 {
+                    return Err(invalid("session length must not be negative".into()));
+                }
+                Ok(Self::Vwap { length, offset })
+            }
+            _ => Err(invalid("unknown indicator".into())),
+        }
+    }
+}
+
+impl fmt::Display for IndicatorSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sma(period) => write!(f, "sma:{period}"),
+            Self::Ema(period) => write!(f, "ema:{period}"),
+            Self::Wma(period) => write!(f, "wma:{period}"),
+            Self::Rsi(period) => write!(f, "rsi:{period}"),
+            Self::Atr(period) => write!(f, "atr:{period}"),
+            Self::Adx(period) => write!(f, "adx:{period}"),
+            Self::Obv => write!(f, "obv"),
+            Self::Macd { fast, slow, signal } => write!(f, "macd:{fast}:{slow}:{signal}"),
+            Self::Bollinger { period, multiplier } => write!(f, "bb:{period}:{multiplier}"),
+            Self::Stochastic {
+                period,
+                smooth_k,
+                smooth_d,
+            } => write!(f, "stoch:{period}:{smooth_k}:{smooth_d}"),
+            Self::Vwap { length, offset } => write!(f, "vwap:{length}:{offset}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::candles;
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        let specs =
+            IndicatorSpec::parse_list("rsi, macd:5:10:4,BB:10:2.5,stoch,obv,vwap:86400:25200")
+                .unwrap();
+
+        assert_eq!(
+            specs,
+            vec![
+                IndicatorSpec::Rsi(14),
+                IndicatorSpec::Macd {
+                    fast: 5,
+                    slow: 10,
+                    signal: 4
+                },
+                IndicatorSpec::Bollinger {
+                    period: 10,
+                    multiplier: 2.5
+                },
+                IndicatorSpec::Stochastic {
+                    period: 14,
+                    smooth_k: 3,
+                    smooth_d: 3
+                },
+                IndicatorSpec::Obv,
+                IndicatorSpec::Vwap {
+                    length: 86400,
+                    offset: 25200
+                },
+            ]
+        );
+        for spec in specs {
+            assert_eq!(spec.to_string().parse::<IndicatorSpec>().unwrap(), spec);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_specs() {
+        for spec in [
+            "foo",
+            "rsi:0",
+            "rsi:abc",
+            "sma:5:5",
+            "macd:26:12",
+            "bb:20:x",
+            "obv:1",
+            "vwap:-1",
+            "rsi:1001",
+            "macd:1:18446744073709551615:3",
+            "adx:9223372036854775808",
+            "stoch:18446744073709551615",
+        ] {
+            let error = spec.parse::<IndicatorSpec>().unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput, "{spec}");
+        }
+    }
+
+    #[test]
+    fn test_warm_up_saturates_on_huge_periods() {
+        let huge = usize::MAX;
+        for spec in [
+            IndicatorSpec::Macd {
+                fast: 1,
+                slow: huge,
+                signal: 3,
+            },
+            IndicatorSpec::Adx(huge),
+            IndicatorSpec::Stochastic {
+                period: huge,
+                smooth_k: huge,
+                smooth_d: huge,
+            },
+        ] {
+            assert!(spec.warm_up() > MAX_INDICATOR_PERIOD, "{spec}");
+        }
+    }
+
+    #[test]
+    fn test_warm_up_matches_indicators() {
+        let candles = candles();
+
+        for spec in IndicatorSpec::parse_list(
+            "sma:5,ema:5,wma:5,rsi:7,atr:7,adx:5,obv,macd:3:6:3,bb:5,stoch:5:3:2,vwap",
+        )
+        .unwrap()
+        {
+            for (name, values) in spec.compute(&candles) {
+                assert_eq!(values.len(), candles.len());
+                let first = values.iter().position(Option::is_some);
+                assert_eq!(first, Some(spec.warm_up()), "{spec} {name}");
+            }
+        }
+    }
+}
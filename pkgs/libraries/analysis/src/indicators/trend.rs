@@ -0,0 +1,126 @@
+use schemas::CandleStick;
+
+use super::{ExponentialMovingAverage, Indicator, true_range};
+
+// ==================== ADX ====================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdxOutput {
+    pub adx: f64,
+    pub plus_di: f64,
+    pub minus_di: f64,
+}
+
+/// Average Directional Index của Wilder.
+///
+/// Từ nến thứ hai: `+DM = h - prev_h` nếu lớn hơn `prev_l - l` và dương, ngược
+/// lại cho `-DM`. TR, +DM, -DM được làm mượt Wilder (`period` giá trị đầu lấy
+/// trung bình), `±DI = 100 * DM / TR`, `DX = 100 * |+DI - -DI| / (+DI + -DI)`,
+/// và ADX là Wilder của DX. Giá trị đầu tiên có ở nến thứ `2 * period`.
+///
+/// @NOTE: Wilder gốc dùng tổng trượt (`sum - sum / n + x`) thay vì trung bình;
+/// hai cách chỉ khác nhau hệ số `n` ở cả tử và mẫu nên DI/DX/ADX như nhau.
+#[derive(Debug, Clone)]
+pub struct AverageDirectionalIndex {
+    tr: ExponentialMovingAverage,
+    plus_dm: ExponentialMovingAverage,
+    minus_dm: ExponentialMovingAverage,
+    adx: ExponentialMovingAverage,
+    prev: Option<(f64, f64, f64)>,
+}
+
+impl AverageDirectionalIndex {
+    pub fn new(period: usize) -> Self {
+        Self {
+            tr: ExponentialMovingAverage::wilder(period),
+            plus_dm: ExponentialMovingAverage::wilder(period),
+            minus_dm: ExponentialMovingAverage::wilder(period),
+            adx: ExponentialMovingAverage::wilder(period),
+            prev: None,
+        }
+    }
+}
+
+impl Default for AverageDirectionalIndex {
+    fn default() -> Self {
+        Self::new(14)
+    }
+}
+
+impl Indicator for AverageDirectionalIndex {
+    type Output = AdxOutput;
+
+    fn next(&mut self, candle: &CandleStick) -> Option<AdxOutput> {
+        let (prev_h, prev_l, prev_c) = self.prev.replace((candle.h, candle.l, candle.c))?;
+
+        let up = candle.h - prev_h;
+        let down = prev_l - candle.l;
+        let plus_dm = if up > down && up > 0.0 { up } else { 0.0 };
+        let minus_dm = if down > up && down > 0.0 { down } else { 0.0 };
+
+        let tr = self.tr.update(true_range(candle, Some(prev_c)));
+        let plus_dm = self.plus_dm.update(plus_dm);
+        let minus_dm = self.minus_dm.update(minus_dm);
+        let (tr, plus_dm, minus_dm) = (tr?, plus_dm?, minus_dm?);
+
+        let (plus_di, minus_di) = if tr > 0.0 {
+            (100.0 * plus_dm / tr, 100.0 * minus_dm / tr)
+        } else {
+            (0.0, 0.0)
+        };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 {
+            100.0 * (plus_di - minus_di).abs() / di_sum
+        } else {
+            0.0
+        };
+
+        Some(AdxOutput {
+            adx: self.adx.update(dx)?,
+            plus_di,
+            minus_di,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.tr.reset();
+        self.plus_dm.reset();
+        self.minus_dm.reset();
+        self.adx.reset();
+        self.prev = None;
+    }
+
+    fn warm_up(&self) -> usize {
+        2 * self.tr.period() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::{assert_series, candles};
+
+    #[test]
+    fn test_adx_golden_values() {
+        let mut adx = AverageDirectionalIndex::new(7);
+        let result = adx.compute(&candles());
+
+        assert_eq!(adx.warm_up(), 13);
+        assert!(result[..13].iter().all(Option::is_none));
+
+        let line: Vec<_> = result.iter().map(|o| o.map(|o| o.adx)).collect();
+        let plus_di: Vec<_> = result.iter().map(|o| o.map(|o| o.plus_di)).collect();
+        let minus_di: Vec<_> = result.iter().map(|o| o.map(|o| o.minus_di)).collect();
+        assert_series(&line, &[(13, 27.3342), (20, 30.2483), (29, 56.8005)], 1e-3);
+        assert_series(
+            &plus_di,
+            &[(13, 17.0218), (20, 7.5402), (29, 11.1369)],
+            1e-3,
+        );
+        assert_series(
+            &minus_di,
+            &[(13, 21.2165), (20, 16.0469), (29, 27.2219)],
+            1e-3,
+        );
+    }
+}
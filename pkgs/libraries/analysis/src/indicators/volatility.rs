@@ -0,0 +1,175 @@
+use std::collections::VecDeque;
+
+use schemas::CandleStick;
+
+use super::{ExponentialMovingAverage, Indicator, true_range};
+
+// ==================== Bollinger Bands ====================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerOutput {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+/// Bollinger Bands: SMA(`period`) ± `multiplier` × độ lệch chuẩn (population)
+/// của cùng cửa sổ.
+///
+/// @NOTE: phương sai tính lại trên cửa sổ mỗi nến (O(period)) thay vì giữ tổng
+/// bình phương, vì `sum_sq / n - mean²` mất chính xác khi giá lớn và biên độ nhỏ.
+#[derive(Debug, Clone)]
+pub struct BollingerBands {
+    period: usize,
+    multiplier: f64,
+    window: VecDeque<f64>,
+}
+
+impl BollingerBands {
+    pub fn new(period: usize, multiplier: f64) -> Self {
+        assert!(period > 0, "period phải lớn hơn 0");
+
+        Self {
+            period,
+            multiplier,
+            window: VecDeque::with_capacity(period),
+        }
+    }
+}
+
+impl Default for BollingerBands {
+    fn default() -> Self {
+        Self::new(20, 2.0)
+    }
+}
+
+impl Indicator for BollingerBands {
+    type Output = BollingerOutput;
+
+    fn next(&mut self, candle: &CandleStick) -> Option<BollingerOutput> {
+        if self.window.len() == self.period {
+            self.window.pop_front();
+        }
+        self.window.push_back(candle.c);
+        if self.window.len() < self.period {
+            return None;
+        }
+
+        let n = self.period as f64;
+        let middle = self.window.iter().sum::<f64>() / n;
+        let variance = self
+            .window
+            .iter()
+            .map(|value| (value - middle).powi(2))
+            .sum::<f64>()
+            / n;
+        let width = self.multiplier * variance.sqrt();
+
+        Some(BollingerOutput {
+            upper: middle + width,
+            middle,
+            lower: middle - width,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+
+    fn warm_up(&self) -> usize {
+        self.period - 1
+    }
+}
+
+// ==================== ATR ====================
+
+/// Average True Range của Wilder. True range của nến đầu tiên là `h - l`, ATR
+/// đầu tiên là trung bình `period` true range đầu, sau đó làm mượt kiểu Wilder.
+#[derive(Debug, Clone)]
+pub struct AverageTrueRange {
+    average: ExponentialMovingAverage,
+    prev_close: Option<f64>,
+}
+
+impl AverageTrueRange {
+    pub fn new(period: usize) -> Self {
+        Self {
+            average: ExponentialMovingAverage::wilder(period),
+            prev_close: None,
+        }
+    }
+}
+
+impl Default for AverageTrueRange {
+    fn default() -> Self {
+        Self::new(14)
+    }
+}
+
+impl Indicator for AverageTrueRange {
+    type Output = f64;
+
+    fn next(&mut self, candle: &CandleStick) -> Option<f64> {
+        let tr = true_range(candle, self.prev_close.replace(candle.c));
+        self.average.update(tr)
+    }
+
+    fn reset(&mut self) {
+        self.average.reset();
+        self.prev_close = None;
+    }
+
+    fn warm_up(&self) -> usize {
+        self.average.period() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::{assert_series, candles, candles_from_closes};
+
+    #[test]
+    fn test_bollinger_golden_values() {
+        let mut bollinger = BollingerBands::new(10, 2.0);
+        let result = bollinger.compute(&candles());
+
+        assert!(result[..9].iter().all(Option::is_none));
+
+        let upper: Vec<_> = result.iter().map(|o| o.map(|o| o.upper)).collect();
+        let middle: Vec<_> = result.iter().map(|o| o.map(|o| o.middle)).collect();
+        let lower: Vec<_> = result.iter().map(|o| o.map(|o| o.lower)).collect();
+        assert_series(&upper, &[(9, 52.3319), (17, 53.2267), (29, 48.9820)], 1e-3);
+        assert_series(&middle, &[(9, 50.8030), (17, 50.7040), (29, 46.0390)], 1e-3);
+        assert_series(&lower, &[(9, 49.2741), (17, 48.1813), (29, 43.0960)], 1e-3);
+    }
+
+    #[test]
+    fn test_bollinger_flat_series_collapses() {
+        let mut bollinger = BollingerBands::new(3, 2.0);
+        let result = bollinger.compute(&candles_from_closes(&[5.0; 4]));
+
+        assert_eq!(
+            result[3],
+            Some(BollingerOutput {
+                upper: 5.0,
+                middle: 5.0,
+                lower: 5.0
+            })
+        );
+    }
+
+    #[test]
+    fn test_atr_golden_values() {
+        let mut atr = AverageTrueRange::new(7);
+        let result = atr.compute(&candles());
+
+        assert_eq!(atr.warm_up(), 6);
+        assert!(result[..6].iter().all(Option::is_none));
+        assert_series(
+            &result,
+            &[(6, 1.8186), (12, 1.8028), (20, 1.9769), (29, 2.0496)],
+            1e-3,
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use schemas::CandleStick;
+
+use super::Indicator;
+
+// ==================== OBV ====================
+
+/// On-Balance Volume của Granville: cộng volume khi giá đóng cửa tăng, trừ khi
+/// giảm, giữ nguyên khi đứng giá. Nến đầu tiên có OBV = 0.
+#[derive(Debug, Clone, Default)]
+pub struct OnBalanceVolume {
+    obv: f64,
+    prev_close: Option<f64>,
+}
+
+impl OnBalanceVolume {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Indicator for OnBalanceVolume {
+    type Output = f64;
+
+    fn next(&mut self, candle: &CandleStick) -> Option<f64> {
+        if let Some(prev_close) = self.prev_close.replace(candle.c) {
+            if candle.c > prev_close {
+                self.obv += candle.v;
+            } else if candle.c < prev_close {
+                self.obv -= candle.v;
+            }
+        }
+        Some(self.obv)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn warm_up(&self) -> usize {
+        0
+    }
+}
+
+// ==================== VWAP ====================
+
+/// VWAP luỹ kế theo giá điển hình `(h + l + c) / 3`.
+///
+/// Mặc định cộng dồn từ nến đầu tiên (anchored VWAP). Với `with_session(86400)`
+/// tổng được reset mỗi khi `t` sang phiên mới; `offset` dời mốc phiên theo múi
+/// giờ, vd `7 * 3600` để phiên bắt đầu lúc 00:00 giờ Việt Nam. Trả `None` khi
+/// phiên hiện tại chưa có volume.
+#[derive(Debug, Clone, Default)]
+pub struct VolumeWeightedAveragePrice {
+    session: Option<(i64, i64)>,
+    current_session: Option<i64>,
+    price_volume: f64,
+    volume: f64,
+}
+
+impl VolumeWeightedAveragePrice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_session(mut self, length: i64, offset: i64) -> Self {
+        assert!(length > 0, "độ dài phiên phải lớn hơn 0");
+
+        self.session = Some((length, offset));
+        self
+    }
+}
+
+impl Indicator for VolumeWeightedAveragePrice {
+    type Output = f64;
+
+    fn next(&mut self, candle: &CandleStick) -> Option<f64> {
+        if let Some((length, offset)) = self.session {
+            let session = (candle.t as i64 + offset).div_euclid(length);
+            if self.current_session.replace(session) != Some(session) {
+                self.price_volume = 0.0;
+                self.volume = 0.0;
+            }
+        }
+
+        let typical_price = (candle.h + candle.l + candle.c) / 3.0;
+        self.price_volume += typical_price * candle.v;
+        self.volume += candle.v;
+
+        (self.volume > 0.0).then(|| self.price_volume / self.volume)
+    }
+
+    fn reset(&mut self) {
+        self.current_session = None;
+        self.price_volume = 0.0;
+        self.volume = 0.0;
+    }
+
+    fn warm_up(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::fixtures::{assert_series, candles};
+
+    #[test]
+    fn test_obv_golden_values() {
+        let mut obv = OnBalanceVolume::new();
+        let result = obv.compute(&candles());
+
+        assert_eq!(result[0], Some(0.0));
+        assert_series(
+            &result,
+            &[
+                (1, 436700.0),
+                (10, -288200.0),
+                (20, -1476600.0),
+                (29, -3816100.0),
+            ],
+            1e-6,
+        );
+    }
+
+    #[test]
+    fn test_vwap_golden_values() {
+        let mut vwap = VolumeWeightedAveragePrice::new();
+        let result = vwap.compute(&candles());
+
+        assert_series(
+            &result,
+            &[(0, 50.0933), (10, 50.8567), (20, 50.5710), (29, 48.9527)],
+            1e-3,
+        );
+    }
+
+    #[test]
+    fn test_vwap_resets_each_session() {
+        let candles = candles();
+        // Mỗi nến của fixture là một ngày, phiên 1 ngày thì VWAP = giá điển hình
+        let mut daily = VolumeWeightedAveragePrice::new().with_session(86400, 0);
+        for (candle, value) in candles.iter().zip(daily.compute(&candles)) {
+            let typical_price = (candle.h + candle.l + candle.c) / 3.0;
+            assert!((value.unwrap() - typical_price).abs() < 1e-9);
+        }
+
+        let mut weekly = VolumeWeightedAveragePrice::new().with_session(7 * 86400, 0);
+        let result = weekly.compute(&candles);
+        let first_week = candles[0].t as i64 / (7 * 86400);
+        let boundary = candles
+            .iter()
+            .position(|c| c.t as i64 / (7 * 86400) != first_week)
+            .unwrap();
+        let first = &candles[boundary];
+        let typical_price = (first.h + first.l + first.c) / 3.0;
+        assert!((result[boundary].unwrap() - typical_price).abs() < 1e-9);
+    }
+}
@@ -1,7 +1,17 @@
+mod backtest;
 mod extract_features;
+mod indicators;
+mod patterns;
+mod pivot;
+mod resample;
 mod rrg;
 mod volume_profile;
 
+pub use backtest::*;
 pub use extract_features::*;
+pub use indicators::*;
+pub use patterns::*;
+pub use pivot::*;
+pub use resample::*;
 pub use rrg::*;
 pub use volume_profile::*;
@@ -0,0 +1,501 @@
+//! Nhận diện mô hình nến một, hai và ba cây trên chuỗi `CandleStick`.
+//!
+//! ## Idea
+//! Mọi mô hình đều được mô tả qua tỉ lệ của thân và bóng nến so với biên độ
+//! `h - l`, nên cùng một bộ ngưỡng dùng được cho mọi khung thời gian và mọi mức
+//! giá:
+//!
+//! ```text
+//!     |   <- upper = h - max(o, c)
+//!    [ ]  <- body  = |c - o|
+//!     |   <- lower = min(o, c) - l
+//! ```
+//!
+//! Các ngưỡng nằm trong `PatternConfig`. Mỗi kết quả là
+//! `(index, pattern, direction, strength)` với `index` là nến cuối của mô hình
+//! và `strength` trong `[0, 1]` đo mức "đẹp" của mô hình (thân doji càng nhỏ,
+//! bóng hammer càng dài... thì càng gần 1), để screener lọc hoặc xếp hạng.
+//!
+//! Hammer và shooting star chỉ có nghĩa sau một nhịp giảm/tăng, nên được lọc
+//! theo xu hướng của `trend_lookback` nến trước đó (0 là bỏ qua xu hướng).
+
+use std::fmt;
+
+use schemas::CandleStick;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandlePattern {
+    Doji,
+    Hammer,
+    ShootingStar,
+    Engulfing,
+    Harami,
+    MorningStar,
+    EveningStar,
+    ThreeWhiteSoldiers,
+    InsideBar,
+    OutsideBar,
+}
+
+impl CandlePattern {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Doji => "doji",
+            Self::Hammer => "hammer",
+            Self::ShootingStar => "shooting_star",
+            Self::Engulfing => "engulfing",
+            Self::Harami => "harami",
+            Self::MorningStar => "morning_star",
+            Self::EveningStar => "evening_star",
+            Self::ThreeWhiteSoldiers => "three_white_soldiers",
+            Self::InsideBar => "inside_bar",
+            Self::OutsideBar => "outside_bar",
+        }
+    }
+}
+
+impl fmt::Display for CandlePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternDirection {
+    Bullish,
+    Bearish,
+    Neutral,
+}
+
+/// `(index, pattern, direction, strength)`, `index` là nến cuối của mô hình
+pub type PatternMatch = (usize, CandlePattern, PatternDirection, f64);
+
+/// Ngưỡng nhận diện, các tỉ lệ đều tính theo biên độ `h - l` của nến đang xét
+#[derive(Debug, Clone, Copy)]
+pub struct PatternConfig {
+    /// Doji khi thân <= `doji_body` biên độ
+    pub doji_body: f64,
+    /// Thân nhỏ (hammer, shooting star, nến giữa của morning/evening star)
+    pub small_body: f64,
+    /// Thân lớn (nến đầu của morning/evening star, three white soldiers)
+    pub long_body: f64,
+    /// Bóng dài phải >= `long_shadow` lần thân
+    pub long_shadow: f64,
+    /// Bóng ngắn phải <= `short_shadow` biên độ
+    pub short_shadow: f64,
+    /// Sai số khi so hai mức giá "bằng nhau", tính theo biên độ
+    pub tolerance: f64,
+    /// Số nến trước mô hình dùng để xác định xu hướng, 0 là bỏ qua
+    pub trend_lookback: usize,
+}
+
+impl Default for PatternConfig {
+    fn default() -> Self {
+        Self {
+            doji_body: 0.1,
+            small_body: 0.35,
+            long_body: 0.6,
+            long_shadow: 2.0,
+            short_shadow: 0.1,
+            tolerance: 0.05,
+            trend_lookback: 3,
+        }
+    }
+}
+
+/// Hình dạng một nến, tính sẵn để các mô hình dùng chung
+#[derive(Debug, Clone, Copy)]
+struct Shape {
+    o: f64,
+    c: f64,
+    h: f64,
+    l: f64,
+    range: f64,
+    body: f64,
+    upper: f64,
+    lower: f64,
+}
+
+impl Shape {
+    fn new(candle: &CandleStick) -> Self {
+        let (top, bottom) = (candle.o.max(candle.c), candle.o.min(candle.c));
+
+        Self {
+            o: candle.o,
+            c: candle.c,
+            h: candle.h,
+            l: candle.l,
+            range: candle.h - candle.l,
+            body: top - bottom,
+            upper: candle.h - top,
+            lower: bottom - candle.l,
+        }
+    }
+
+    fn bullish(&self) -> bool {
+        self.c > self.o
+    }
+
+    fn bearish(&self) -> bool {
+        self.c < self.o
+    }
+
+    fn top(&self) -> f64 {
+        self.o.max(self.c)
+    }
+
+    fn bottom(&self) -> f64 {
+        self.o.min(self.c)
+    }
+}
+
+/// Tìm mọi mô hình trên `candles`, kết quả sắp theo `index` rồi theo thứ tự
+/// khai báo của `CandlePattern`
+pub fn detect_patterns(candles: &[CandleStick], config: &PatternConfig) -> Vec<PatternMatch> {
+    let shapes = candles.iter().map(Shape::new).collect::<Vec<_>>();
+    let mut matches = Vec::new();
+
+    for i in 0..shapes.len() {
+        // Nến không có biên độ (giá đứng yên) không mang thông tin hình dạng
+        if shapes[i].range <= 0.0 {
+            continue;
+        }
+
+        detect_single(&shapes, candles, i, config, &mut matches);
+        if i >= 1 && shapes[i - 1].range > 0.0 {
+            detect_double(&shapes, i, config, &mut matches);
+        }
+        if i >= 2 && shapes[i - 2..i].iter().all(|shape| shape.range > 0.0) {
+            detect_triple(&shapes, i, config, &mut matches);
+        }
+    }
+
+    matches.sort_by_key(|&(index, pattern, _, _)| (index, pattern as u8));
+    matches
+}
+
+/// Close của nến ngay trước `first` so với `trend_lookback` nến trước đó:
+/// `Some(true)` là đang giảm, `Some(false)` là đang tăng, `None` khi không đủ
+/// dữ liệu hoặc đi ngang
+fn prior_decline(candles: &[CandleStick], first: usize, config: &PatternConfig) -> Option<bool> {
+    let lookback = config.trend_lookback;
+    if first < lookback + 1 {
+        return None;
+    }
+
+    let (last, start) = (candles[first - 1].c, candles[first - 1 - lookback].c);
+    (last != start).then_some(last < start)
+}
+
+fn detect_single(
+    shapes: &[Shape],
+    candles: &[CandleStick],
+    i: usize,
+    config: &PatternConfig,
+    matches: &mut Vec<PatternMatch>,
+) {
+    let shape = &shapes[i];
+    let trend = prior_decline(candles, i, config);
+    let trend_allows = |decline: bool| config.trend_lookback == 0 || trend == Some(decline);
+
+    if shape.body <= config.doji_body * shape.range {
+        let strength = 1.0 - shape.body / (config.doji_body * shape.range);
+        matches.push((i, CandlePattern::Doji, PatternDirection::Neutral, strength));
+    }
+
+    if shape.body <= config.small_body * shape.range {
+        // Thân bằng 0 (doji chuồn chuồn/bia mộ) vẫn tính, miễn là có bóng dài một phía
+        if shape.lower > 0.0
+            && shape.lower >= config.long_shadow * shape.body
+            && shape.upper <= config.short_shadow * shape.range
+            && trend_allows(true)
+        {
+            matches.push((
+                i,
+                CandlePattern::Hammer,
+                PatternDirection::Bullish,
+                shape.lower / shape.range,
+            ));
+        }
+        if shape.upper > 0.0
+            && shape.upper >= config.long_shadow * shape.body
+            && shape.lower <= config.short_shadow * shape.range
+            && trend_allows(false)
+        {
+            matches.push((
+                i,
+                CandlePattern::ShootingStar,
+                PatternDirection::Bearish,
+                shape.upper / shape.range,
+            ));
+        }
+    }
+}
+
+fn detect_double(
+    shapes: &[Shape],
+    i: usize,
+    config: &PatternConfig,
+    matches: &mut Vec<PatternMatch>,
+) {
+    let (prev, curr) = (&shapes[i - 1], &shapes[i]);
+    let tolerance = config.tolerance * curr.range;
+    let opposite = (prev.bearish() && curr.bullish()) || (prev.bullish() && curr.bearish());
+    let direction = if curr.bullish() {
+        PatternDirection::Bullish
+    } else {
+        PatternDirection::Bearish
+    };
+
+    // Engulfing: thân nến sau bao trọn thân nến trước, ngược màu
+    if opposite
+        && curr.body > prev.body
+        && curr.top() + tolerance >= prev.top()
+        && curr.bottom() - tolerance <= prev.bottom()
+    {
+        matches.push((
+            i,
+            CandlePattern::Engulfing,
+            direction,
+            1.0 - prev.body / curr.body,
+        ));
+    }
+
+    // Harami: thân nến sau nằm trong thân nến trước, ngược màu
+    if opposite
+        && curr.body < prev.body
+        && curr.top() <= prev.top() + tolerance
+        && curr.bottom() >= prev.bottom() - tolerance
+    {
+        matches.push((
+            i,
+            CandlePattern::Harami,
+            direction,
+            1.0 - curr.body / prev.body,
+        ));
+    }
+
+    if curr.h <= prev.h && curr.l >= prev.l && curr.range < prev.range {
+        matches.push((
+            i,
+            CandlePattern::InsideBar,
+            PatternDirection::Neutral,
+            1.0 - curr.range / prev.range,
+        ));
+    }
+
+    if curr.h > prev.h && curr.l < prev.l {
+        let direction = match curr.c.partial_cmp(&curr.o) {
+            Some(std::cmp::Ordering::Greater) => PatternDirection::Bullish,
+            Some(std::cmp::Ordering::Less) => PatternDirection::Bearish,
+            _ => PatternDirection::Neutral,
+        };
+        matches.push((
+            i,
+            CandlePattern::OutsideBar,
+            direction,
+            1.0 - prev.range / curr.range,
+        ));
+    }
+}
+
+fn detect_triple(
+    shapes: &[Shape],
+    i: usize,
+    config: &PatternConfig,
+    matches: &mut Vec<PatternMatch>,
+) {
+    let (first, star, last) = (&shapes[i - 2], &shapes[i - 1], &shapes[i]);
+    let tolerance = config.tolerance * first.range;
+    let long_first = first.body >= config.long_body * first.range;
+    let small_star = star.body <= config.small_body * star.range;
+    let midpoint = (first.o + first.c) / 2.0;
+
+    // Morning star: nến giảm dài, nến thân nhỏ nằm dưới thân nến đầu, rồi nến
+    // tăng đóng cửa quá nửa thân nến đầu
+    if first.bearish()
+        && long_first
+        && small_star
+        && star.top() <= first.c + tolerance
+        && last.bullish()
+        && last.c > midpoint
+    {
+        let strength = ((last.c - first.c) / first.body).min(1.0);
+        matches.push((
+            i,
+            CandlePattern::MorningStar,
+            PatternDirection::Bullish,
+            strength,
+        ));
+    }
+
+    if first.bullish()
+        && long_first
+        && small_star
+        && star.bottom() >= first.c - tolerance
+        && last.bearish()
+        && last.c < midpoint
+    {
+        let strength = ((first.c - last.c) / first.body).min(1.0);
+        matches.push((
+            i,
+            CandlePattern::EveningStar,
+            PatternDirection::Bearish,
+            strength,
+        ));
+    }
+
+    // Three white soldiers: ba nến tăng thân dài, close cao dần và mỗi nến mở
+    // cửa bên trong thân nến trước
+    let soldiers = [first, star, last];
+    let all_long = soldiers
+        .iter()
+        .all(|shape| shape.bullish() && shape.body >= config.long_body * shape.range);
+    let stepping = soldiers.windows(2).all(|pair| {
+        let tolerance = config.tolerance * pair[1].range;
+        pair[1].c > pair[0].c
+            && pair[1].o >= pair[0].o - tolerance
+            && pair[1].o <= pair[0].c + tolerance
+    });
+    if all_long && stepping {
+        let strength = soldiers
+            .iter()
+            .map(|shape| shape.body / shape.range)
+            .sum::<f64>()
+            / 3.0;
+        matches.push((
+            i,
+            CandlePattern::ThreeWhiteSoldiers,
+            PatternDirection::Bullish,
+            strength,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(o: f64, h: f64, l: f64, c: f64) -> CandleStick {
+        CandleStick {
+            t: 0,
+            o,
+            h,
+            l,
+            c,
+            v: 1000.0,
+        }
+    }
+
+    fn find(
+        matches: &[PatternMatch],
+        index: usize,
+        pattern: CandlePattern,
+    ) -> Option<(PatternDirection, f64)> {
+        matches
+            .iter()
+            .find(|m| m.0 == index && m.1 == pattern)
+            .map(|m| (m.2, m.3))
+    }
+
+    #[test]
+    fn test_single_candle_patterns() {
+        let candles = vec![
+            candle(20.0, 20.5, 18.5, 19.0),
+            candle(19.0, 19.2, 17.8, 18.0),
+            candle(18.0, 18.1, 16.9, 17.0),
+            candle(17.0, 17.1, 15.9, 16.0),
+            // Sau 3 nến giảm: thân 0.2, bóng dưới 2.0, bóng trên 0.1
+            candle(16.0, 16.3, 14.0, 16.2),
+            // Doji: thân 0.02 trên biên độ 1.0
+            candle(16.0, 16.5, 15.5, 16.02),
+        ];
+        let matches = detect_patterns(&candles, &PatternConfig::default());
+
+        let (direction, strength) = find(&matches, 4, CandlePattern::Hammer).unwrap();
+        assert_eq!(direction, PatternDirection::Bullish);
+        assert!((strength - 2.0 / 2.3).abs() < 1e-9);
+        assert!(find(&matches, 4, CandlePattern::ShootingStar).is_none());
+
+        let (direction, strength) = find(&matches, 5, CandlePattern::Doji).unwrap();
+        assert_eq!(direction, PatternDirection::Neutral);
+        assert!((strength - 0.8).abs() < 1e-6);
+
+        // Cùng hình dạng nhưng không có nhịp giảm trước đó thì không phải hammer
+        let matches = detect_patterns(&candles[3..], &PatternConfig::default());
+        assert!(find(&matches, 1, CandlePattern::Hammer).is_none());
+
+        // Tắt lọc xu hướng thì nhận lại
+        let config = PatternConfig {
+            trend_lookback: 0,
+            ..Default::default()
+        };
+        let matches = detect_patterns(&candles[3..], &config);
+        assert!(find(&matches, 1, CandlePattern::Hammer).is_some());
+    }
+
+    #[test]
+    fn test_double_candle_patterns() {
+        let candles = vec![
+            candle(10.0, 10.2, 8.8, 9.0),
+            // Bullish engulfing và outside bar
+            candle(8.9, 10.6, 8.7, 10.5),
+            // Bearish harami và inside bar
+            candle(10.2, 10.4, 9.2, 9.5),
+        ];
+        let matches = detect_patterns(&candles, &PatternConfig::default());
+
+        let (direction, strength) = find(&matches, 1, CandlePattern::Engulfing).unwrap();
+        assert_eq!(direction, PatternDirection::Bullish);
+        assert!((strength - (1.0 - 1.0 / 1.6)).abs() < 1e-9);
+        let (direction, _) = find(&matches, 1, CandlePattern::OutsideBar).unwrap();
+        assert_eq!(direction, PatternDirection::Bullish);
+
+        let (direction, strength) = find(&matches, 2, CandlePattern::Harami).unwrap();
+        assert_eq!(direction, PatternDirection::Bearish);
+        assert!((strength - (1.0 - 0.7 / 1.6)).abs() < 1e-9);
+        let (direction, strength) = find(&matches, 2, CandlePattern::InsideBar).unwrap();
+        assert_eq!(direction, PatternDirection::Neutral);
+        assert!((strength - (1.0 - 1.2 / 1.9)).abs() < 1e-9);
+
+        assert!(find(&matches, 2, CandlePattern::Engulfing).is_none());
+    }
+
+    #[test]
+    fn test_triple_candle_patterns() {
+        let morning = vec![
+            candle(20.0, 20.2, 17.8, 18.0),
+            candle(17.8, 18.0, 17.2, 17.6),
+            candle(17.7, 19.7, 17.6, 19.5),
+        ];
+        let matches = detect_patterns(&morning, &PatternConfig::default());
+        let (direction, strength) = find(&matches, 2, CandlePattern::MorningStar).unwrap();
+        assert_eq!(direction, PatternDirection::Bullish);
+        assert!((strength - 0.75).abs() < 1e-9);
+
+        let evening = morning
+            .iter()
+            .map(|c| candle(40.0 - c.o, 40.0 - c.l, 40.0 - c.h, 40.0 - c.c))
+            .collect::<Vec<_>>();
+        let matches = detect_patterns(&evening, &PatternConfig::default());
+        let (direction, strength) = find(&matches, 2, CandlePattern::EveningStar).unwrap();
+        assert_eq!(direction, PatternDirection::Bearish);
+        assert!((strength - 0.75).abs() < 1e-9);
+        assert!(find(&matches, 2, CandlePattern::MorningStar).is_none());
+
+        let soldiers = vec![
+            candle(10.0, 11.1, 9.9, 11.0),
+            candle(10.5, 12.1, 10.4, 12.0),
+            candle(11.5, 13.2, 11.4, 13.0),
+        ];
+        let matches = detect_patterns(&soldiers, &PatternConfig::default());
+        let (direction, _) = find(&matches, 2, CandlePattern::ThreeWhiteSoldiers).unwrap();
+        assert_eq!(direction, PatternDirection::Bullish);
+
+        // Nến thứ ba mở cửa trên close nến trước (gap) thì không tính
+        let mut gapped = soldiers.clone();
+        gapped[2] = candle(12.5, 14.2, 12.4, 14.0);
+        let matches = detect_patterns(&gapped, &PatternConfig::default());
+        assert!(find(&matches, 2, CandlePattern::ThreeWhiteSoldiers).is_none());
+    }
+}
@@ -1,31 +1,80 @@
+//! Phân tích đỉnh/đáy (pivot) trên nhiều window size cùng lúc.
+//!
+//! ## Idea
+//! Với mỗi nến `i` và window `w`, đếm số nến trong `w` nến bên trái (`l`) và `w`
+//! nến bên phải (`r`) có giá đóng cửa lớn hơn `c[i]`:
+//!
+//! ```text
+//! high   = (w - l) * (w - r) / w^2      1 khi c[i] cao nhất cả hai phía
+//! low    = tương tự, đếm nến nhỏ hơn    1 khi c[i] thấp nhất cả hai phía
+//! degree = 1 - (w - l') * (w - r') / w^2   l', r' đếm nến có volume lớn hơn v[i]
+//! spread = (c[i] - o[i]) / (h[i] - l[i])
+//! ```
+//!
+//! `high`/`low` là phần bù của `peak = 1 - (w - l)(w - r)/w^2` trong ghi chú
+//! gốc, để 1 luôn là tín hiệu mạnh nhất. `degree ~ 1` nghĩa là volume cạn kiệt
+//! dần quanh nến, `|spread| ~ 1` là thân nến gần phủ hết biên độ — pivot đáng
+//! tin thường có cả hai.
+//!
+//! Khi một phía chưa đủ `w` nến (đầu/cuối chuỗi) thì dùng số nến đang có, phía
+//! không có nến nào coi như hệ số 1 — tức công thức một phía `(w - l) / w`.
+//! Các điểm số được lấy trung bình trên mọi `w`.
+//!
+//! Pivot mạnh nhất của một nến là `w` lớn nhất mà close vẫn là cực trị của
+//! `[i - w, i + w]` với đủ `w` nến mỗi phía. Số đếm dùng Fenwick tree trên rank
+//! của giá, mỗi window size một cây, nên cả chuỗi tốn `O(n * W * log n)`.
+
 use std::collections::{HashMap, VecDeque};
 use std::io::{Error, ErrorKind};
 
+use itertools::Itertools;
 use ordered_float::OrderedFloat;
+use rayon::prelude::*;
 use schemas::CandleStick;
 
-struct Pivot {
-    metrics: Vec<Vec<usize>>,
-    range: (usize, usize),
+/// Điểm số của một nến, lấy trung bình trên mọi window size
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PivotScore {
+    /// 1 khi close cao nhất trong `w` nến mỗi phía
+    pub high: f64,
+    /// 1 khi close thấp nhất trong `w` nến mỗi phía
+    pub low: f64,
+    /// 1 khi volume thấp nhất ít nhất một phía (volume cạn kiệt)
+    pub degree: f64,
+    /// `(c - o) / (h - l)` trong `[-1, 1]`, 0 khi `h == l`
+    pub spread: f64,
 }
 
-/*
-    l = count(x -> x > df.c[i], @view df.c[i-w:i-1])
-    r = count(x -> x > df.c[i], @view df.c[i+1:i+w])
-    peak = 1 - (w - l) * (w - r) / w^2
-    peak = 1 - (w - l) / w
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PivotKind {
+    High,
+    Low,
+}
 
-    l = count(x -> x > df.c[i], @view df.w[i-w:i-1])
-    r = count(x -> x > df.c[i], @view df.w[i+1:i+w])
-    degree = 1 - (w - l) * (w - r) / w^2
-    degree = 1 - (w - l) / w
+#[derive(Debug, Clone, PartialEq)]
+pub struct PivotPoint {
+    pub index: usize,
+    pub t: i32,
+    pub price: f64,
+    pub kind: PivotKind,
+    /// Window lớn nhất mà close vẫn là cực trị, càng lớn pivot càng mạnh
+    pub window: usize,
+}
 
-    spread = (df.c[i] - df.o[i]) / (df.h[i] - df.l[i])
-    vol cạn kiệt dần
+pub struct Pivot {
+    scores: Vec<PivotScore>,
+    pivots: Vec<PivotPoint>,
+    range: (usize, usize),
+}
 
-    lim(degree) ~= 1
-    lim(abs(spread)) ~= 1
-*/
+/// Số nến lớn hơn / nhỏ hơn nến hiện tại trong window, `total` là số nến
+/// hàng xóm thực có (không tính nến hiện tại)
+#[derive(Debug, Clone, Copy, Default)]
+struct RankCount {
+    greater: usize,
+    smaller: usize,
+    total: usize,
+}
 
 #[derive(Clone)]
 struct FenwickTree {
@@ -61,15 +110,142 @@ impl FenwickTree {
 }
 
 impl Pivot {
+    /// Window từ 2 tới `n / 3` nến mỗi phía
     pub fn new(candles: &[CandleStick]) -> Result<Self, Error> {
-        let range = (0, candles.len() / 3);
-        let metrics = Self::cumulate_pivot_in_multiple_windows(candles, range.0, range.1)?;
+        Self::with_windows(candles, 2, candles.len() / 3)
+    }
 
-        Ok(Self { metrics, range })
+    pub fn with_windows(
+        candles: &[CandleStick],
+        min_window_size: usize,
+        max_window_size: usize,
+    ) -> Result<Self, Error> {
+        if min_window_size == 0 || min_window_size > max_window_size {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Window sizes must satisfy 0 < min <= max",
+            ));
+        }
+        if 2 * max_window_size >= candles.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "max_window_size too large",
+            ));
+        }
+
+        let closes = candles.iter().map(|c| c.c).collect::<Vec<_>>();
+        let volumes = candles.iter().map(|c| c.v).collect::<Vec<_>>();
+        let reversed = |values: &[f64]| values.iter().rev().cloned().collect::<Vec<_>>();
+
+        // Window `w` hàng xóm tương ứng stream dài `w + 1` tính cả nến hiện tại,
+        // phía phải là phía trái của chuỗi đảo ngược
+        let (min_limit, max_limit) = (min_window_size + 1, max_window_size + 1);
+        let left_closes = Self::rank_counts(&closes, min_limit, max_limit);
+        let left_volumes = Self::rank_counts(&volumes, min_limit, max_limit);
+        let mut right_closes = Self::rank_counts(&reversed(&closes), min_limit, max_limit);
+        let mut right_volumes = Self::rank_counts(&reversed(&volumes), min_limit, max_limit);
+        right_closes.reverse();
+        right_volumes.reverse();
+
+        let side = |total: usize, count: usize| {
+            if total == 0 {
+                1.0
+            } else {
+                (total - count) as f64 / total as f64
+            }
+        };
+        let num_windows = (max_window_size - min_window_size + 1) as f64;
+
+        let (scores, strengths): (Vec<_>, Vec<_>) = (0..candles.len())
+            .into_par_iter()
+            .map(|i| {
+                let mut score = PivotScore::default();
+                let (mut high_window, mut low_window) = (0, 0);
+
+                for (k, (lc, rc)) in left_closes[i].iter().zip(&right_closes[i]).enumerate() {
+                    let (lv, rv) = (left_volumes[i][k], right_volumes[i][k]);
+                    let window = min_window_size + k;
+
+                    score.high += side(lc.total, lc.greater) * side(rc.total, rc.greater);
+                    score.low += side(lc.total, lc.smaller) * side(rc.total, rc.smaller);
+                    score.degree += 1.0 - side(lv.total, lv.greater) * side(rv.total, rv.greater);
+
+                    if lc.total == window && rc.total == window {
+                        if lc.greater == 0 && rc.greater == 0 {
+                            high_window = window;
+                        }
+                        if lc.smaller == 0 && rc.smaller == 0 {
+                            low_window = window;
+                        }
+                    }
+                }
+
+                let candle = &candles[i];
+                score.high /= num_windows;
+                score.low /= num_windows;
+                score.degree /= num_windows;
+                score.spread = if candle.h > candle.l {
+                    (candle.c - candle.o) / (candle.h - candle.l)
+                } else {
+                    0.0
+                };
+                (score, (high_window, low_window))
+            })
+            .unzip();
+
+        let pivots = strengths
+            .iter()
+            .enumerate()
+            .flat_map(|(index, &(high_window, low_window))| {
+                [(PivotKind::High, high_window), (PivotKind::Low, low_window)]
+                    .into_iter()
+                    .filter(|&(_, window)| window > 0)
+                    .map(move |(kind, window)| PivotPoint {
+                        index,
+                        t: candles[index].t,
+                        price: candles[index].c,
+                        kind,
+                        window,
+                    })
+            })
+            .sorted_by(|a, b| b.window.cmp(&a.window).then(a.index.cmp(&b.index)))
+            .collect();
+
+        Ok(Self {
+            scores,
+            pivots,
+            range: (min_window_size, max_window_size),
+        })
+    }
+
+    /// Điểm số từng nến, thẳng hàng với chuỗi nến đầu vào
+    pub fn scores(&self) -> &Vec<PivotScore> {
+        &self.scores
     }
 
+    /// Mọi pivot, sắp theo window giảm dần rồi theo thời gian
+    pub fn pivots(&self) -> &Vec<PivotPoint> {
+        &self.pivots
+    }
+
+    /// `(min_window_size, max_window_size)` đã dùng
+    pub fn windows(&self) -> (usize, usize) {
+        self.range
+    }
+
+    /// `limit` pivot mạnh nhất của một loại
+    pub fn strongest(&self, kind: PivotKind, limit: usize) -> Vec<&PivotPoint> {
+        self.pivots
+            .iter()
+            .filter(|pivot| pivot.kind == kind)
+            .take(limit)
+            .collect()
+    }
+
+    /// Số đếm thô: với mỗi nến và mỗi window `w` trong khoảng, số nến có close
+    /// lớn hơn nó trong `w` nến gần nhất (tính cả nó)
     #[inline]
-    fn cumulate_pivot_in_multiple_windows(
+    pub fn cumulate_pivot_in_multiple_windows(
         candles: &[CandleStick],
         min_window_size: usize,
         max_window_size: usize,
@@ -83,12 +259,24 @@ impl Pivot {
             ));
         }
 
+        let closes = candles.iter().map(|c| c.c).collect::<Vec<_>>();
+        Ok(Self::rank_counts(&closes, min_window_size, max_window_size)
+            .into_iter()
+            .map(|counts| counts.into_iter().map(|count| count.greater).collect())
+            .collect())
+    }
+
+    /// Với mỗi phần tử và mỗi `limit` trong `[min_window_size, max_window_size]`,
+    /// đếm trong `limit` phần tử gần nhất (tính cả nó) số phần tử lớn hơn/nhỏ hơn
+    fn rank_counts(
+        values: &[f64],
+        min_window_size: usize,
+        max_window_size: usize,
+    ) -> Vec<Vec<RankCount>> {
+        let n = values.len();
         let num_windows = max_window_size - min_window_size + 1;
 
-        let mut sorted_prices = candles
-            .iter()
-            .map(|c| OrderedFloat(c.c))
-            .collect::<Vec<_>>();
+        let mut sorted_prices = values.iter().map(|&v| OrderedFloat(v)).collect::<Vec<_>>();
 
         sorted_prices.sort_unstable();
         sorted_prices.dedup();
@@ -103,8 +291,8 @@ impl Pivot {
         let mut streams = vec![VecDeque::with_capacity(max_window_size); num_windows];
         let mut results = Vec::with_capacity(n);
 
-        for candle in candles {
-            let current_price = OrderedFloat(candle.c);
+        for &value in values {
+            let current_price = OrderedFloat(value);
             let current_rank = *rank_map.get(&current_price).unwrap();
             let mut current_candle_results = Vec::with_capacity(num_windows);
 
@@ -114,7 +302,7 @@ impl Pivot {
                 let stream = &mut streams[w_idx];
 
                 ft.add(current_rank, 1);
-                stream.push_back(candle.c);
+                stream.push_back(value);
 
                 if stream.len() > current_w_limit
                     && let Some(old_val) = stream.pop_front()
@@ -124,13 +312,22 @@ impl Pivot {
 
                 let total_in_window = stream.len();
                 let count_smaller_equal = ft.query(current_rank);
-                current_candle_results.push(total_in_window - count_smaller_equal);
+                let count_smaller = if current_rank > 0 {
+                    ft.query(current_rank - 1)
+                } else {
+                    0
+                };
+                current_candle_results.push(RankCount {
+                    greater: total_in_window - count_smaller_equal,
+                    smaller: count_smaller,
+                    total: total_in_window.saturating_sub(1),
+                });
             }
 
             results.push(current_candle_results);
         }
 
-        Ok(results)
+        results
     }
 }
 
@@ -202,6 +399,15 @@ mod tests {
                 c: 15.0,
                 ..Default::default()
             },
+            // Nến đệm để max_window_size <= n / 2, không ảnh hưởng nến đang xét
+            CandleStick {
+                c: 15.0,
+                ..Default::default()
+            },
+            CandleStick {
+                c: 15.0,
+                ..Default::default()
+            },
         ];
 
         let result = Pivot::cumulate_pivot_in_multiple_windows(&candles, 3, 3).unwrap();
@@ -231,6 +437,15 @@ mod tests {
                 c: 30.0,
                 ..Default::default()
             },
+            // Nến đệm để max_window_size <= n / 2, không ảnh hưởng nến đang xét
+            CandleStick {
+                c: 30.0,
+                ..Default::default()
+            },
+            CandleStick {
+                c: 30.0,
+                ..Default::default()
+            },
         ];
 
         // Test đồng thời window 2 và 3
@@ -269,6 +484,11 @@ mod tests {
                 c: 20.0,
                 ..Default::default()
             },
+            // Nến đệm để max_window_size <= n / 2
+            CandleStick {
+                c: 20.0,
+                ..Default::default()
+            },
         ];
         let result = Pivot::cumulate_pivot_in_multiple_windows(&candles, 2, 2).unwrap();
 
@@ -277,4 +497,74 @@ mod tests {
             assert_eq!(res[0], 0);
         }
     }
+
+    fn candles(closes: &[f64], volumes: &[f64]) -> Vec<CandleStick> {
+        closes
+            .iter()
+            .zip(volumes)
+            .enumerate()
+            .map(|(i, (&c, &v))| CandleStick {
+                t: 1_700_000_000 + i as i32 * 86400,
+                o: c,
+                h: c,
+                l: c,
+                c,
+                v,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pivot_scores() {
+        let mut candles = candles(
+            &[1.0, 2.0, 5.0, 2.0, 1.0, 0.5, 1.0, 4.0, 1.0],
+            &[10.0, 10.0, 3.0, 10.0, 10.0, 2.0, 10.0, 10.0, 10.0],
+        );
+        candles[3].o = 4.0;
+        candles[3].h = 4.5;
+        candles[3].l = 1.5;
+
+        let pivot = Pivot::with_windows(&candles, 1, 3).unwrap();
+        let scores = pivot.scores();
+        assert_eq!(scores.len(), candles.len());
+
+        // Nến 2 cao nhất ở mọi window, kể cả window 3 chỉ có 2 nến bên trái
+        assert!((scores[2].high - 1.0).abs() < 1e-9);
+        // Nến 4, low theo từng window: 1 * 0 = 0, 1 * 1/2, 1 * 2/3
+        assert!((scores[4].low - (0.0 + 0.5 + 2.0 / 3.0) / 3.0).abs() < 1e-9);
+
+        // Volume tại đỉnh/đáy thấp hơn mọi nến lân cận: cạn kiệt hoàn toàn
+        assert!((scores[2].degree - 1.0).abs() < 1e-9);
+        assert!((scores[5].degree - 1.0).abs() < 1e-9);
+        assert!(scores[0].degree.abs() < 1e-9);
+
+        assert!((scores[3].spread - (2.0 - 4.0) / 3.0).abs() < 1e-9);
+        assert_eq!(scores[2].spread, 0.0);
+    }
+
+    #[test]
+    fn test_strongest_pivots() {
+        let candles = candles(&[1.0, 2.0, 5.0, 2.0, 1.0, 0.5, 1.0, 4.0, 1.0], &[1.0; 9]);
+        let pivot = Pivot::with_windows(&candles, 1, 3).unwrap();
+
+        // Đỉnh ở nến 2 chỉ có 2 nến bên trái nên mạnh nhất ở window 2, đỉnh nến 7
+        // chỉ có 1 nến bên phải
+        let highs = pivot.strongest(PivotKind::High, 10);
+        assert_eq!(
+            highs
+                .iter()
+                .map(|pivot| (pivot.index, pivot.window))
+                .collect::<Vec<_>>(),
+            vec![(2, 2), (7, 1)]
+        );
+
+        let lows = pivot.strongest(PivotKind::Low, 1);
+        assert_eq!(lows.len(), 1);
+        assert_eq!((lows[0].index, lows[0].window), (5, 3));
+        assert_eq!(lows[0].t, candles[5].t);
+        assert_eq!(pivot.pivots()[0], *lows[0]);
+
+        assert!(Pivot::with_windows(&candles, 0, 3).is_err());
+        assert!(Pivot::with_windows(&candles, 2, 5).is_err());
+    }
 }
@@ -0,0 +1,651 @@
+//! Gộp nến độ phân giải thấp (hoặc tick) thành nến độ phân giải cao hơn.
+//!
+//! ## Idea
+//! Mỗi nến đầu vào được gán vào một "bucket" theo thời điểm mở `t` của nó, nến
+//! ra của bucket có `o` của nến đầu, `c` của nến cuối, `h`/`l` cực trị và `v`
+//! tổng. Thời điểm `t` của nến ra là thời điểm bắt đầu bucket:
+//!
+//! - Intraday (`1m` ... `4H`): bucket căn theo giờ mở cửa của từng phiên trong
+//!   ngày, không vắt qua giờ nghỉ trưa. Với HOSE, `1H` cho ra
+//!   `09:00, 10:00, 11:00 (tới 11:30), 13:00, 14:00 (tới 14:45)`.
+//! - `1D`/`1W`/`1M`: căn theo 0h giờ địa phương của ngày, của ngày đầu tuần
+//!   (`week_start`, mặc định thứ 2) và của ngày 1 trong tháng.
+//!
+//! Giờ giao dịch và múi giờ nằm trong `TradingSession`. Việt Nam không có giờ
+//! mùa hè nên Asia/Ho_Chi_Minh là một offset cố định `+07:00`.
+//!
+//! Khi bật gap filling, bucket giao dịch không có dữ liệu được lấp bằng nến
+//! phẳng tại close trước đó với `v = 0`; cuối tuần (nếu thị trường nghỉ) và ngày
+//! lễ không được lấp.
+//!
+//! `CandleBuilder` làm cùng việc đó theo kiểu streaming: nhận từng tick/nến và
+//! trả nến đã đóng mỗi khi dữ liệu mới rơi sang bucket kế tiếp.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, NaiveDate, Weekday};
+use schemas::{CandleStick, Tick};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// ==================== Timeframe ====================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timeframe {
+    /// Bucket trong ngày dài `n` giây, căn theo giờ mở cửa phiên
+    Intraday(i64),
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl FromStr for Timeframe {
+    type Err = Error;
+
+    /// Cùng ký hiệu resolution với API: `1m`, `15m`, `1H`, `4H`, `1D`, `1W`, `1M`
+    fn from_str(resolution: &str) -> Result<Self, Error> {
+        let invalid = || {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid resolution `{resolution}`"),
+            )
+        };
+        let split = resolution
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let (count, unit) = resolution.split_at(split);
+        let count = count
+            .parse::<i64>()
+            .ok()
+            .filter(|&count| count > 0)
+            .ok_or_else(invalid)?;
+
+        let timeframe = match (unit, count) {
+            ("m", _) => Self::Intraday(count.checked_mul(60).ok_or_else(invalid)?),
+            ("H", _) => Self::Intraday(count.checked_mul(60 * 60).ok_or_else(invalid)?),
+            ("D", 1) => Self::Daily,
+            ("W", 1) => Self::Weekly,
+            ("M", 1) => Self::Monthly,
+            _ => return Err(invalid()),
+        };
+        match timeframe {
+            Self::Intraday(seconds) if seconds > SECONDS_PER_DAY => Err(invalid()),
+            timeframe => Ok(timeframe),
+        }
+    }
+}
+
+impl fmt::Display for Timeframe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Intraday(seconds) if seconds % 3600 == 0 => write!(f, "{}H", seconds / 3600),
+            Self::Intraday(seconds) if seconds % 60 == 0 => write!(f, "{}m", seconds / 60),
+            Self::Intraday(seconds) => write!(f, "{seconds}s"),
+            Self::Daily => write!(f, "1D"),
+            Self::Weekly => write!(f, "1W"),
+            Self::Monthly => write!(f, "1M"),
+        }
+    }
+}
+
+// ==================== Trading session ====================
+
+/// Lịch giao dịch của một thị trường
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradingSession {
+    /// Lệch so với UTC, tính bằng giây
+    pub utc_offset: i32,
+    /// Các phiên trong ngày theo giờ địa phương, `(mở, đóng)` tính bằng giây kể
+    /// từ 0h, tăng dần và không chồng nhau
+    pub hours: Vec<(u32, u32)>,
+    /// Có giao dịch thứ 7 và chủ nhật không
+    pub weekends: bool,
+    /// Ngày nghỉ lễ theo giờ địa phương
+    pub holidays: BTreeSet<NaiveDate>,
+}
+
+impl TradingSession {
+    /// Crypto: 24/7, ngày tính theo UTC
+    pub fn crypto() -> Self {
+        Self {
+            utc_offset: 0,
+            hours: vec![(0, SECONDS_PER_DAY as u32)],
+            weekends: true,
+            holidays: BTreeSet::new(),
+        }
+    }
+
+    /// HOSE: 09:00 - 11:30 (gồm ATO) và 13:00 - 14:45 (gồm ATC), giờ Việt Nam
+    pub fn hose() -> Self {
+        Self {
+            utc_offset: 7 * 60 * 60,
+            hours: vec![(hms(9, 0), hms(11, 30)), (hms(13, 0), hms(14, 45))],
+            weekends: false,
+            holidays: BTreeSet::new(),
+        }
+    }
+
+    /// HNX: như HOSE nhưng phiên chiều kéo tới 15:00 (gồm phiên sau giờ PLO)
+    pub fn hnx() -> Self {
+        Self {
+            hours: vec![(hms(9, 0), hms(11, 30)), (hms(13, 0), hms(15, 0))],
+            ..Self::hose()
+        }
+    }
+
+    pub fn with_holidays(mut self, holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        self.holidays.extend(holidays);
+        self
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        let sorted = self.hours.windows(2).all(|pair| pair[0].1 <= pair[1].0);
+        let bounded = self
+            .hours
+            .iter()
+            .all(|&(open, close)| open < close && close <= SECONDS_PER_DAY as u32);
+
+        if self.hours.is_empty() || !sorted || !bounded {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Trading hours must be sorted, non-overlapping and within a day",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Ngày (kể từ 1970-01-01, theo giờ địa phương) chứa thời điểm `t`
+    pub(crate) fn local_day(&self, t: i64) -> i64 {
+        (t + self.utc_offset as i64).div_euclid(SECONDS_PER_DAY)
+    }
+
+    pub(crate) fn is_trading_day(&self, day: i64) -> bool {
+        let date = date_of(day);
+        let weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        (self.weekends || !weekend) && !self.holidays.contains(&date)
+    }
+}
+
+#[inline]
+fn hms(hour: u32, minute: u32) -> u32 {
+    hour * 60 * 60 + minute * 60
+}
+
+/// Ngày thứ `day` kể từ 1970-01-01
+#[inline]
+fn date_of(day: i64) -> NaiveDate {
+    DateTime::from_timestamp(day * SECONDS_PER_DAY, 0)
+        .map(|datetime| datetime.date_naive())
+        .unwrap_or_default()
+}
+
+#[inline]
+fn day_of(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0)
+        .map(|datetime| datetime.and_utc().timestamp() / SECONDS_PER_DAY)
+        .unwrap_or_default()
+}
+
+// ==================== Resampler ====================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resampler {
+    timeframe: Timeframe,
+    session: TradingSession,
+    week_start: Weekday,
+    fill_gaps: bool,
+}
+
+impl Resampler {
+    pub fn new(timeframe: Timeframe, session: TradingSession) -> Result<Self, Error> {
+        session.validate()?;
+        if let Timeframe::Intraday(seconds) = timeframe
+            && !(0 < seconds && seconds <= SECONDS_PER_DAY)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Intraday timeframe must be within a day",
+            ));
+        }
+
+        Ok(Self {
+            timeframe,
+            session,
+            week_start: Weekday::Mon,
+            fill_gaps: false,
+        })
+    }
+
+    pub fn with_week_start(mut self, week_start: Weekday) -> Self {
+        self.week_start = week_start;
+        self
+    }
+
+    pub fn with_gap_filling(mut self, fill_gaps: bool) -> Self {
+        self.fill_gaps = fill_gaps;
+        self
+    }
+
+    pub fn timeframe(&self) -> Timeframe {
+        self.timeframe
+    }
+
+    /// Thời điểm bắt đầu (UTC) của bucket chứa `t`. Với intraday, `t` nằm ngoài
+    /// giờ giao dịch (nghỉ trưa, trước giờ mở cửa...) trả `None`.
+    pub fn bucket_of(&self, t: i64) -> Option<i64> {
+        let offset = self.session.utc_offset as i64;
+        let local = t + offset;
+        let day = local.div_euclid(SECONDS_PER_DAY);
+
+        let start_day = match self.timeframe {
+            Timeframe::Intraday(length) => {
+                let seconds = local.rem_euclid(SECONDS_PER_DAY);
+                let &(open, _) = self
+                    .session
+                    .hours
+                    .iter()
+                    .find(|&&(open, close)| open as i64 <= seconds && seconds < close as i64)?;
+                let open = open as i64;
+                return Some(
+                    day * SECONDS_PER_DAY + open + (seconds - open) / length * length - offset,
+                );
+            }
+            Timeframe::Daily => day,
+            Timeframe::Weekly => {
+                let weekday = date_of(day).weekday();
+                day - weekday.days_since(self.week_start) as i64
+            }
+            Timeframe::Monthly => day_of(date_of(day).with_day(1)?),
+        };
+        Some(start_day * SECONDS_PER_DAY - offset)
+    }
+
+    /// Bucket ngay sau bucket bắt đầu ở `start`, bỏ qua giờ nghỉ và ngày nghỉ
+    fn next_bucket(&self, start: i64) -> i64 {
+        let offset = self.session.utc_offset as i64;
+        let local = start + offset;
+        let day = local.div_euclid(SECONDS_PER_DAY);
+
+        let next_day = match self.timeframe {
+            Timeframe::Intraday(length) => {
+                let seconds = local.rem_euclid(SECONDS_PER_DAY);
+                let segment = self
+                    .session
+                    .hours
+                    .iter()
+                    .position(|&(_, close)| seconds < close as i64)
+                    .unwrap_or(self.session.hours.len());
+
+                // Còn trong phiên hiện tại, hoặc sang phiên sau trong cùng ngày
+                let next = match self.session.hours.get(segment) {
+                    Some(&(_, close)) if seconds + length < close as i64 => Some(seconds + length),
+                    _ => self
+                        .session
+                        .hours
+                        .get(segment + 1)
+                        .map(|&(open, _)| open as i64),
+                };
+                if let Some(next) = next {
+                    return day * SECONDS_PER_DAY + next - offset;
+                }
+
+                let mut next_day = day + 1;
+                while !self.session.is_trading_day(next_day) {
+                    next_day += 1;
+                }
+                return next_day * SECONDS_PER_DAY + self.session.hours[0].0 as i64 - offset;
+            }
+            Timeframe::Daily => {
+                let mut next_day = day + 1;
+                while !self.session.is_trading_day(next_day) {
+                    next_day += 1;
+                }
+                next_day
+            }
+            Timeframe::Weekly => day + 7,
+            Timeframe::Monthly => {
+                let date = date_of(day);
+                let (year, month) = match date.month() {
+                    12 => (date.year() + 1, 1),
+                    month => (date.year(), month + 1),
+                };
+                NaiveDate::from_ymd_opt(year, month, 1)
+                    .map(day_of)
+                    .unwrap_or(day + 31)
+            }
+        };
+        next_day * SECONDS_PER_DAY - offset
+    }
+
+    /// Gộp `candles` (đã sắp theo thời gian) sang `timeframe`. Nến intraday nằm
+    /// ngoài giờ giao dịch bị bỏ qua.
+    pub fn resample(&self, candles: &[CandleStick]) -> Result<Vec<CandleStick>, Error> {
+        let mut builder = CandleBuilder::new(self.clone());
+        let mut resampled: Vec<CandleStick> = Vec::new();
+
+        for candle in candles {
+            if let Some(closed) = builder.push(candle)? {
+                self.push_with_gaps(&mut resampled, closed);
+            }
+        }
+        if let Some(last) = builder.finish() {
+            self.push_with_gaps(&mut resampled, last);
+        }
+        Ok(resampled)
+    }
+
+    /// Dựng nến `timeframe` trực tiếp từ tick khớp lệnh
+    pub fn resample_ticks(&self, ticks: &[Tick]) -> Result<Vec<CandleStick>, Error> {
+        self.resample(&ticks.iter().map(tick_to_candle).collect::<Vec<_>>())
+    }
+
+    fn push_with_gaps(&self, resampled: &mut Vec<CandleStick>, candle: CandleStick) {
+        if self.fill_gaps
+            && let Some(previous) = resampled.last().cloned()
+        {
+            let mut bucket = self.next_bucket(previous.t as i64);
+            while bucket < candle.t as i64 {
+                resampled.push(CandleStick {
+                    t: bucket as i32,
+                    o: previous.c,
+                    h: previous.c,
+                    l: previous.c,
+                    c: previous.c,
+                    v: 0.0,
+                });
+                bucket = self.next_bucket(bucket);
+            }
+        }
+        resampled.push(candle);
+    }
+}
+
+#[inline]
+fn tick_to_candle(tick: &Tick) -> CandleStick {
+    CandleStick {
+        t: (tick.timestamp / 1000) as i32,
+        o: tick.price,
+        h: tick.price,
+        l: tick.price,
+        c: tick.price,
+        v: tick.quantity,
+    }
+}
+
+// ==================== Streaming ====================
+
+/// Dựng nến theo `Resampler` từ luồng tick/nến, giữ nến đang chạy của bucket
+/// hiện tại
+#[derive(Debug, Clone)]
+pub struct CandleBuilder {
+    resampler: Resampler,
+    current: Option<CandleStick>,
+}
+
+impl CandleBuilder {
+    pub fn new(resampler: Resampler) -> Self {
+        Self {
+            resampler,
+            current: None,
+        }
+    }
+
+    /// Nến của bucket đang chạy, chưa đóng
+    pub fn current(&self) -> Option<&CandleStick> {
+        self.current.as_ref()
+    }
+
+    /// Cộng `candle` vào bucket của nó. Trả nến của bucket trước khi `candle`
+    /// mở bucket mới; dữ liệu thuộc bucket cũ hơn bucket hiện tại là lỗi.
+    pub fn push(&mut self, candle: &CandleStick) -> Result<Option<CandleStick>, Error> {
+        let Some(bucket) = self.resampler.bucket_of(candle.t as i64) else {
+            return Ok(None);
+        };
+        let bucket = bucket as i32;
+
+        match self.current.as_mut() {
+            Some(current) if current.t == bucket => {
+                current.h = current.h.max(candle.h);
+                current.l = current.l.min(candle.l);
+                current.c = candle.c;
+                current.v += candle.v;
+                Ok(None)
+            }
+            Some(current) if current.t > bucket => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Candles must be sorted by time",
+            )),
+            _ => Ok(self.current.replace(CandleStick {
+                t: bucket,
+                ..candle.clone()
+            })),
+        }
+    }
+
+    pub fn push_tick(&mut self, tick: &Tick) -> Result<Option<CandleStick>, Error> {
+        self.push(&tick_to_candle(tick))
+    }
+
+    /// Đóng và trả nến đang chạy
+    pub fn finish(&mut self) -> Option<CandleStick> {
+        self.current.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01 00:00 UTC, thứ 2
+    const JAN_1: i64 = 1_704_067_200;
+    const HOUR: i64 = 60 * 60;
+
+    fn candle(t: i64, price: f64, v: f64) -> CandleStick {
+        CandleStick {
+            t: t as i32,
+            o: price,
+            h: price + 0.5,
+            l: price - 0.5,
+            c: price + 0.1,
+            v,
+        }
+    }
+
+    #[test]
+    fn test_timeframe_parse_and_display() {
+        for (resolution, timeframe) in [
+            ("1m", Timeframe::Intraday(60)),
+            ("15m", Timeframe::Intraday(15 * 60)),
+            ("4H", Timeframe::Intraday(4 * HOUR)),
+            ("1D", Timeframe::Daily),
+            ("1W", Timeframe::Weekly),
+            ("1M", Timeframe::Monthly),
+        ] {
+            assert_eq!(resolution.parse::<Timeframe>().unwrap(), timeframe);
+            assert_eq!(timeframe.to_string(), resolution);
+        }
+        // Hai chuỗi cuối làm `count * 60` / `count * 3600` tràn i64
+        for resolution in [
+            "",
+            "m",
+            "0m",
+            "2D",
+            "25H",
+            "1x",
+            "153722867280912931m",
+            "2562047788015216H",
+        ] {
+            assert!(resolution.parse::<Timeframe>().is_err(), "{resolution}");
+        }
+    }
+
+    #[test]
+    fn test_hose_intraday_respects_lunch_break() {
+        // 2024-01-02, nến 1 phút suốt hai phiên theo giờ Việt Nam (UTC+7)
+        let day = JAN_1 + 24 * HOUR - 7 * HOUR;
+        let mut candles = Vec::new();
+        for minute in (9 * 60..11 * 60 + 30).chain(13 * 60..14 * 60 + 45) {
+            candles.push(candle(day + minute * 60, 100.0 + minute as f64, 1.0));
+        }
+        // Lệnh lẻ trong giờ nghỉ trưa bị bỏ qua
+        candles.insert(150, candle(day + 12 * HOUR, 500.0, 99.0));
+
+        let resampler = Resampler::new(Timeframe::Intraday(HOUR), TradingSession::hose()).unwrap();
+        let hourly = resampler.resample(&candles).unwrap();
+
+        let starts = hourly.iter().map(|c| c.t as i64 - day).collect::<Vec<_>>();
+        assert_eq!(
+            starts,
+            vec![9 * HOUR, 10 * HOUR, 11 * HOUR, 13 * HOUR, 14 * HOUR]
+        );
+        let volumes = hourly.iter().map(|c| c.v).collect::<Vec<_>>();
+        assert_eq!(volumes, vec![60.0, 60.0, 30.0, 60.0, 45.0]);
+
+        let last = &hourly[2];
+        assert_eq!(last.o, 100.0 + 660.0);
+        assert_eq!(last.c, 100.0 + 689.0 + 0.1);
+        assert_eq!(last.h, 100.0 + 689.0 + 0.5);
+        assert_eq!(last.l, 100.0 + 660.0 - 0.5);
+    }
+
+    #[test]
+    fn test_week_and_month_anchoring() {
+        // Nến ngày crypto từ 2024-01-29 (thứ 2) tới 2024-02-06
+        let candles = (28..37)
+            .map(|day| candle(JAN_1 + day * 24 * HOUR, day as f64, 1.0))
+            .collect::<Vec<_>>();
+
+        let weekly = Resampler::new(Timeframe::Weekly, TradingSession::crypto())
+            .unwrap()
+            .resample(&candles)
+            .unwrap();
+        assert_eq!(
+            weekly.iter().map(|c| (c.t as i64, c.v)).collect::<Vec<_>>(),
+            vec![(JAN_1 + 28 * 24 * HOUR, 7.0), (JAN_1 + 35 * 24 * HOUR, 2.0)]
+        );
+
+        // Tuần bắt đầu từ chủ nhật
+        let weekly = Resampler::new(Timeframe::Weekly, TradingSession::crypto())
+            .unwrap()
+            .with_week_start(Weekday::Sun)
+            .resample(&candles)
+            .unwrap();
+        assert_eq!(weekly[0].t as i64, JAN_1 + 27 * 24 * HOUR);
+        assert_eq!(weekly[1].t as i64, JAN_1 + 34 * 24 * HOUR);
+
+        let monthly = Resampler::new(Timeframe::Monthly, TradingSession::crypto())
+            .unwrap()
+            .resample(&candles)
+            .unwrap();
+        assert_eq!(
+            monthly
+                .iter()
+                .map(|c| (c.t as i64, c.v))
+                .collect::<Vec<_>>(),
+            vec![(JAN_1, 3.0), (JAN_1 + 31 * 24 * HOUR, 6.0)]
+        );
+        assert_eq!(monthly[1].o, 31.0);
+        assert_eq!(monthly[1].c, 36.1);
+
+        // Tháng theo giờ Việt Nam bắt đầu lúc 17:00 UTC ngày cuối tháng trước
+        let resampler = Resampler::new(Timeframe::Monthly, TradingSession::hose()).unwrap();
+        assert_eq!(
+            resampler.bucket_of(JAN_1 + 31 * 24 * HOUR - 6 * HOUR),
+            Some(JAN_1 + 31 * 24 * HOUR - 7 * HOUR)
+        );
+    }
+
+    #[test]
+    fn test_gap_filling_skips_weekends_and_holidays() {
+        // Thứ 6 2024-01-05 và thứ 4 2024-01-10, thứ 2 08/01 nghỉ lễ
+        let local = |day: i64| JAN_1 + day * 24 * HOUR - 7 * HOUR + 10 * HOUR;
+        let candles = vec![candle(local(4), 10.0, 5.0), candle(local(9), 12.0, 3.0)];
+        let holiday = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+
+        let daily = Resampler::new(
+            Timeframe::Daily,
+            TradingSession::hose().with_holidays([holiday]),
+        )
+        .unwrap()
+        .with_gap_filling(true)
+        .resample(&candles)
+        .unwrap();
+
+        let midnight = |day: i64| (JAN_1 + day * 24 * HOUR - 7 * HOUR) as i32;
+        assert_eq!(
+            daily.iter().map(|c| c.t).collect::<Vec<_>>(),
+            vec![midnight(4), midnight(8), midnight(9)]
+        );
+        assert_eq!(daily[1].v, 0.0);
+        assert_eq!(
+            (daily[1].o, daily[1].h, daily[1].l, daily[1].c),
+            (10.1, 10.1, 10.1, 10.1)
+        );
+
+        // Intraday HOSE: gap qua giờ nghỉ trưa và qua đêm chỉ lấp giờ giao dịch
+        let resampler = Resampler::new(Timeframe::Intraday(HOUR), TradingSession::hose())
+            .unwrap()
+            .with_gap_filling(true);
+        let day = JAN_1 + 24 * HOUR - 7 * HOUR;
+        let hourly = resampler
+            .resample(&[
+                candle(day + 10 * HOUR, 1.0, 1.0),
+                candle(day + 24 * HOUR + 9 * HOUR, 2.0, 1.0),
+            ])
+            .unwrap();
+        let starts = hourly.iter().map(|c| c.t as i64 - day).collect::<Vec<_>>();
+        assert_eq!(
+            starts,
+            vec![10 * HOUR, 11 * HOUR, 13 * HOUR, 14 * HOUR, 33 * HOUR]
+        );
+    }
+
+    #[test]
+    fn test_build_candles_from_ticks() {
+        let tick = |seconds: i64, price: f64, quantity: f64| Tick {
+            price,
+            quantity,
+            timestamp: (JAN_1 + seconds) * 1000,
+            ..Default::default()
+        };
+        let ticks = vec![
+            tick(5, 100.0, 1.0),
+            tick(30, 102.0, 2.0),
+            tick(59, 99.0, 1.0),
+            tick(61, 101.0, 4.0),
+        ];
+
+        let resampler = Resampler::new(Timeframe::Intraday(60), TradingSession::crypto()).unwrap();
+        let mut builder = CandleBuilder::new(resampler.clone());
+
+        assert!(builder.push_tick(&ticks[0]).unwrap().is_none());
+        assert!(builder.push_tick(&ticks[1]).unwrap().is_none());
+        assert!(builder.push_tick(&ticks[2]).unwrap().is_none());
+        assert_eq!(builder.current().unwrap().c, 99.0);
+
+        let closed = builder.push_tick(&ticks[3]).unwrap().unwrap();
+        assert_eq!(
+            (
+                closed.t as i64,
+                closed.o,
+                closed.h,
+                closed.l,
+                closed.c,
+                closed.v
+            ),
+            (JAN_1, 100.0, 102.0, 99.0, 99.0, 4.0)
+        );
+        assert_eq!(builder.current().unwrap().t as i64, JAN_1 + 60);
+
+        // Tick trễ thuộc bucket đã đóng là lỗi
+        assert!(builder.push_tick(&ticks[0]).is_err());
+
+        let candles = resampler.resample_ticks(&ticks).unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!((candles[0].t, candles[0].v), (closed.t, closed.v));
+        assert_eq!(candles[1].v, 4.0);
+    }
+}
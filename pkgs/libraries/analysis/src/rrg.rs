@@ -1,24 +1,7 @@
 use schemas::CandleStick;
 use std::io::{Error, ErrorKind};
 
-fn weighted_moving_average(data: &[f64], period: usize) -> Vec<f64> {
-    if data.len() < period {
-        return vec![];
-    }
-
-    let mut wma = Vec::with_capacity(data.len());
-    let weights: Vec<f64> = (1..=period).map(|x| x as f64).collect();
-    let weight_sum: f64 = weights.iter().sum();
-
-    for i in (period - 1)..data.len() {
-        let mut sum = 0.0;
-        for j in 0..period {
-            sum += data[i - j] * weights[period - 1 - j];
-        }
-        wma.push(sum / weight_sum);
-    }
-    wma
-}
+use crate::indicators::WeightedMovingAverage;
 
 pub fn calculate_rrg(
     target: &[CandleStick],
@@ -47,8 +30,8 @@ pub fn calculate_rrg(
         ));
     }
 
-    let rs_ratio = weighted_moving_average(
-        &weighted_moving_average(
+    let rs_ratio = WeightedMovingAverage::apply(
+        &WeightedMovingAverage::apply(
             &target
                 .iter()
                 .zip(reference.iter())
@@ -58,7 +41,7 @@ pub fn calculate_rrg(
         ),
         period,
     );
-    let rs_momentum = weighted_moving_average(
+    let rs_momentum = WeightedMovingAverage::apply(
         &rs_ratio
             .windows(period + 1)
             .map(|w| (w[period] / w[0] - 1.0) * 100.0 + 100.0)
@@ -1,10 +1,10 @@
 use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::io::{Error, ErrorKind};
 
 use itertools::Itertools;
 use rayon::prelude::*;
-use schemas::CandleStick;
+use schemas::{CandleStick, Tick};
 
 #[cfg(target_arch = "x86_64")]
 mod simd {
@@ -106,6 +106,82 @@ mod simd {
     }
 }
 
+/// Tham số cho phần phân tích trên mỗi window (value area, HVN/LVN)
+#[derive(Debug, Clone, Copy)]
+pub struct ProfileConfig {
+    /// Tỉ lệ volume nằm trong value area, mặc định 70%
+    pub value_area: f64,
+    /// Cửa sổ làm mượt profile trước khi tìm đỉnh/đáy, tính theo số level
+    pub node_smoothing: usize,
+    /// Đỉnh cục bộ có volume (đã làm mượt) >= `hvn_ratio * POC` mới là HVN
+    pub hvn_ratio: f64,
+    /// Đáy cục bộ có volume (đã làm mượt) <= `lvn_ratio * POC` mới là LVN
+    pub lvn_ratio: f64,
+}
+
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        Self {
+            value_area: 0.7,
+            node_smoothing: 3,
+            hvn_ratio: 0.6,
+            lvn_ratio: 0.3,
+        }
+    }
+}
+
+/// Kết quả phân tích một window của heatmap, mọi giá trị là index của level
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileSummary {
+    /// Point of control: level có volume lớn nhất
+    pub poc: usize,
+    pub value_area_low: usize,
+    pub value_area_high: usize,
+    pub high_volume_nodes: Vec<usize>,
+    pub low_volume_nodes: Vec<usize>,
+}
+
+/// POC của một phiên mà giá các phiên sau chưa quay lại chạm tới
+#[derive(Debug, Clone, PartialEq)]
+pub struct NakedPoc {
+    /// Timestamp nến đầu tiên của phiên
+    pub t: i32,
+    pub level: usize,
+    pub price: f64,
+    pub volume: f64,
+}
+
+/// State để `update` cộng dồn nến mới vào window cuối mà không tính lại heatmap
+#[derive(Debug, Clone, Default)]
+struct StreamState {
+    overlap: usize,
+    interval_in_hour: i32,
+    /// Cửa sổ làm mượt khi gom range, giữ nguyên dù grid được mở rộng
+    range_window: usize,
+    current_pin: Option<i32>,
+    /// Timestamp nến đầu tiên của phiên đang chạy
+    session_start: i32,
+    /// Profile của tối đa `max(overlap, 1)` phiên gần nhất, phần tử cuối là phiên đang chạy
+    sessions: VecDeque<Vec<f64>>,
+    /// Tổng volume mỗi cột (level) của heatmap
+    column_totals: Vec<f64>,
+    /// `column_totals` đã làm mượt với cửa sổ `range_window`
+    smoothed: Vec<f64>,
+    /// Các level xếp theo `smoothed` giảm dần (bằng nhau thì index nhỏ trước),
+    /// đúng thứ tự mà `ranges_from_totals` dùng để gom range
+    order: Vec<usize>,
+    /// Vị trí của mỗi level trong `order`
+    rank: Vec<usize>,
+    /// Index của range chứa mỗi level
+    range_of: Vec<usize>,
+    /// Tổng volume mỗi row trong từng range, thẳng hàng với `ranges`
+    range_row_totals: Vec<Vec<f64>>,
+    /// Tổng row lớn nhất của mỗi range, quyết định ngưỡng của timeline
+    range_peaks: Vec<f64>,
+    /// Summary của window đang chạy chưa được tính lại sau `update`
+    summary_stale: bool,
+}
+
 pub struct VolumeProfile {
     heatmap: Vec<Vec<f64>>,
     levels: Vec<f64>,
@@ -113,6 +189,12 @@ pub struct VolumeProfile {
     /// Mỗi phần tử là segments (start, end) của một range —
     /// thời điểm volume bắt đầu xuất hiện & tích luỹ trong vùng giá đó.
     timelines: Vec<Vec<(usize, usize)>>,
+    /// Thẳng hàng với `heatmap`, `None` khi window không có volume
+    summaries: Vec<Option<ProfileSummary>>,
+    naked_pocs: Vec<NakedPoc>,
+    price_step: f64,
+    config: ProfileConfig,
+    stream: Option<StreamState>,
 }
 type VolumeRange = (usize, usize);
 type VolumeTimeline = Vec<VolumeRange>;
@@ -130,26 +212,28 @@ impl VolumeProfile {
             levels: Vec::new(),
             ranges: Vec::new(),
             timelines: Vec::new(),
+            summaries: Vec::new(),
+            naked_pocs: Vec::new(),
+            price_step: 0.0,
+            config: ProfileConfig::default(),
+            stream: None,
         }
     }
 
+    pub fn with_config(mut self, config: ProfileConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     pub fn new_from_candles(
         candles: &[CandleStick],
         number_of_levels: usize,
         overlap: usize,
         interval_in_hour: i32,
     ) -> Result<Self, Error> {
-        let (heatmap, levels) =
-            Self::cumulate_volume_profile(candles, number_of_levels, overlap, interval_in_hour)?;
-        let ranges = Self::cumulate_volume_range(&heatmap, number_of_levels / 10)?;
-        let timelines = Self::calculate_cumulate_volume_timeline(&heatmap, &ranges)?;
-
-        Ok(Self {
-            heatmap,
-            levels,
-            ranges,
-            timelines,
-        })
+        let mut profile = Self::new();
+        profile.calculate(candles, number_of_levels, overlap, interval_in_hour)?;
+        Ok(profile)
     }
 
     pub fn calculate(
@@ -159,16 +243,526 @@ impl VolumeProfile {
         overlap: usize,
         interval_in_hour: i32,
     ) -> Result<(), Error> {
-        let (heatmap, levels) =
-            Self::cumulate_volume_profile(candles, number_of_levels, overlap, interval_in_hour)?;
+        if !(self.config.value_area > 0.0 && self.config.value_area <= 1.0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Value area must be in (0, 1]",
+            ));
+        }
+
+        // Profile từng phiên dùng cho cả heatmap (gộp `overlap` phiên) lẫn naked POC
+        let (sessions, levels) =
+            Self::cumulate_volume_profile(candles, number_of_levels, 1, interval_in_hour)?;
+        let heatmap = Self::windows_from_sessions(&sessions, overlap)?;
+        let starts = Self::session_starts(candles, interval_in_hour);
+
+        self.summaries = heatmap
+            .par_iter()
+            .map(|volumes| Self::summarize(volumes, &self.config))
+            .collect();
+        self.price_step = Self::price_step(candles, &levels);
+        self.naked_pocs =
+            Self::find_naked_pocs(candles, &sessions, &starts, &levels, self.price_step);
 
-        self.ranges = Self::cumulate_volume_range(&heatmap, number_of_levels / 10)?;
-        self.timelines = Self::calculate_cumulate_volume_timeline(&heatmap, &self.ranges)?;
+        let keep = sessions.len().saturating_sub(overlap.max(1));
+        let mut stream = StreamState {
+            overlap,
+            interval_in_hour,
+            range_window: number_of_levels / 10,
+            current_pin: candles
+                .last()
+                .map(|candle| Self::timestamp_to_pin(candle.t, interval_in_hour)),
+            session_start: candles[starts[starts.len() - 1]].t,
+            sessions: sessions.into_iter().skip(keep).collect(),
+            column_totals: Self::column_totals(&heatmap),
+            ..Default::default()
+        };
         self.levels = levels;
         self.heatmap = heatmap;
+        self.ranges.clear();
+        self.timelines.clear();
+        self.rebuild_ranges(&mut stream);
+        self.stream = Some(stream);
         Ok(())
     }
 
+    /// Profile rỗng để cộng dồn dần bằng `update`/`update_tick`, grid gồm
+    /// `number_of_levels` level cách nhau `price_step` và `reference_price` nằm
+    /// giữa. Grid tự mở rộng khi giá đi ra ngoài.
+    pub fn new_streaming(
+        reference_price: f64,
+        price_step: f64,
+        number_of_levels: usize,
+        overlap: usize,
+        interval_in_hour: i32,
+    ) -> Result<Self, Error> {
+        if !(price_step > 0.0 && reference_price.is_finite()) {
+            return Err(Error::new(ErrorKind::InvalidInput, "Invalid price grid"));
+        }
+        if number_of_levels == 0 || interval_in_hour <= 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Number of levels and interval must be positive",
+            ));
+        }
+
+        let min_price = reference_price - (number_of_levels / 2) as f64 * price_step;
+        let mut profile = Self::new();
+        profile.levels = (0..number_of_levels)
+            .map(|i| min_price + i as f64 * price_step)
+            .collect();
+        profile.price_step = price_step;
+
+        // overlap = 0 là một profile duy nhất cho toàn bộ dữ liệu, luôn có sẵn 1 row
+        if overlap == 0 {
+            profile.heatmap = vec![vec![0.0; number_of_levels]];
+            profile.summaries = vec![None];
+        }
+        let mut stream = StreamState {
+            overlap,
+            interval_in_hour,
+            range_window: number_of_levels / 10,
+            column_totals: vec![0.0; number_of_levels],
+            ..Default::default()
+        };
+        profile.rebuild_ranges(&mut stream);
+        profile.stream = Some(stream);
+        Ok(profile)
+    }
+
+    // ==================== Streaming ====================
+
+    /// Cộng dồn một nến mới vào window cuối mà không tính lại toàn bộ heatmap.
+    ///
+    /// Nến thuộc phiên mới sẽ chốt POC phiên cũ làm ứng viên naked POC và mở
+    /// window mới (overlap > 0). Giá vượt ra ngoài grid thì grid được nối thêm
+    /// level cùng bước giá.
+    ///
+    /// Mỗi lần update chỉ đụng tới các level nến chạm vào (cộng thêm nửa cửa sổ
+    /// làm mượt hai bên): thứ hạng level theo tổng volume đã làm mượt được giữ
+    /// sẵn, ranges chỉ gom lại khi thứ hạng đó thay đổi, timeline chỉ nối thêm
+    /// row cuối. Summary của window đang chạy (value area, HVN/LVN cần duyệt cả
+    /// profile) không tự tính lại, gọi `refresh` trước khi đọc `summaries`.
+    ///
+    /// Mở window mới, nối grid và `retain_windows` vẫn là một lượt trên toàn bộ
+    /// level, nhưng chỉ xảy ra mỗi phiên một lần hoặc khi giá ra khỏi grid.
+    ///
+    /// @NOTE: profile phải được tạo bằng `calculate`/`new_from_candles` hoặc
+    /// `new_streaming`, và nến không được thuộc phiên cũ hơn phiên đang chạy.
+    pub fn update(&mut self, candle: &CandleStick) -> Result<(), Error> {
+        let mut stream = self.stream.take().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "Profile must be calculated before streaming updates",
+            )
+        })?;
+        let result = self.fold_candle(&mut stream, candle);
+        self.stream = Some(stream);
+        result
+    }
+
+    /// Tính lại summary của window đang chạy sau các lần `update`/`update_tick`
+    pub fn refresh(&mut self) {
+        if let Some(mut stream) = self.stream.take() {
+            self.refresh_stale(&mut stream);
+            self.stream = Some(stream);
+        }
+    }
+
+    /// Cộng dồn một tick khớp lệnh, xem như nến có `o = h = l = c = price`
+    pub fn update_tick(&mut self, tick: &Tick) -> Result<(), Error> {
+        self.update(&CandleStick {
+            t: (tick.timestamp / 1000) as i32,
+            o: tick.price,
+            h: tick.price,
+            l: tick.price,
+            c: tick.price,
+            v: tick.quantity,
+        })
+    }
+
+    /// Chỉ giữ `keep` window gần nhất (tối thiểu 1) để profile chạy lâu không
+    /// phình mãi, naked POC của các phiên bị bỏ vẫn được giữ lại
+    pub fn retain_windows(&mut self, keep: usize) {
+        let drop = self.heatmap.len().saturating_sub(keep.max(1));
+        if drop == 0 {
+            return;
+        }
+
+        let removed = self.heatmap.drain(..drop).collect::<Vec<_>>();
+        self.summaries.drain(..drop);
+
+        if let Some(mut stream) = self.stream.take() {
+            for row in &removed {
+                for (total, volume) in stream.column_totals.iter_mut().zip(row) {
+                    *total = (*total - volume).max(0.0);
+                }
+            }
+            // Bỏ row có thể làm giảm peak nên timeline phải gom lại từ đầu
+            for ((row_totals, peak), timeline) in stream
+                .range_row_totals
+                .iter_mut()
+                .zip(&mut stream.range_peaks)
+                .zip(&mut self.timelines)
+            {
+                row_totals.drain(..drop);
+                *peak = Self::peak_of(row_totals);
+                *timeline = Self::timeline_segments(row_totals);
+            }
+            self.rebuild_ranges(&mut stream);
+            self.stream = Some(stream);
+        }
+    }
+
+    fn fold_candle(&mut self, stream: &mut StreamState, candle: &CandleStick) -> Result<(), Error> {
+        if !(candle.l.is_finite() && candle.h.is_finite() && candle.v.is_finite())
+            || candle.h < candle.l
+            || candle.v < 0.0
+        {
+            return Err(Error::new(ErrorKind::InvalidInput, "Invalid candle"));
+        }
+
+        let pin = Self::timestamp_to_pin(candle.t, stream.interval_in_hour);
+        if stream.current_pin.is_some_and(|current| pin < current) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Candle is older than the current session",
+            ));
+        }
+
+        if self.extend_grid(stream, candle.l, candle.h)? {
+            self.rebuild_ranges(stream);
+        }
+        if stream.current_pin != Some(pin) {
+            self.open_session(stream, candle.t);
+            stream.current_pin = Some(pin);
+        }
+
+        let (low_bin, high_bin, vol_per_bin) =
+            Self::candle_bins(candle, self.levels[0], self.price_step, self.levels.len());
+        if let Some(session) = stream.sessions.back_mut() {
+            unsafe {
+                simd::add_scalar(session, low_bin, high_bin, vol_per_bin);
+            }
+        }
+
+        // Khi chưa đủ `overlap` phiên thì chưa có window nào, volume chỉ nằm ở sessions
+        let row = match stream.overlap {
+            0 => Some(0),
+            overlap if stream.sessions.len() == overlap => Some(self.heatmap.len() - 1),
+            _ => None,
+        };
+        if let Some(row) = row {
+            unsafe {
+                simd::add_scalar(&mut self.heatmap[row], low_bin, high_bin, vol_per_bin);
+            }
+            stream.summary_stale = true;
+
+            self.add_to_range_rows(stream, low_bin, high_bin, vol_per_bin, row);
+            if Self::add_to_columns(stream, low_bin, high_bin, vol_per_bin) {
+                let ranges = Self::group_ranges(&stream.order);
+                if ranges != self.ranges {
+                    self.apply_ranges(stream, ranges);
+                }
+            }
+        }
+
+        self.naked_pocs
+            .retain(|poc| !(candle.l <= poc.price && poc.price <= candle.h));
+        Ok(())
+    }
+
+    /// Nối thêm level phía dưới/trên để grid chứa được `[low, high]`. Mỗi lần
+    /// chỉ cho nối tối đa bằng số level hiện có để một giá lỗi không làm grid
+    /// phình ra. Trả `true` nếu grid được nối thêm.
+    fn extend_grid(
+        &mut self,
+        stream: &mut StreamState,
+        low: f64,
+        high: f64,
+    ) -> Result<bool, Error> {
+        let step = self.price_step;
+        let count = self.levels.len();
+        let top = self.levels[count - 1] + step;
+
+        let below = if low < self.levels[0] {
+            ((self.levels[0] - low) / step).ceil() as usize
+        } else {
+            0
+        };
+        let above = if high > top {
+            ((high - top) / step).ceil() as usize
+        } else {
+            0
+        };
+        if below == 0 && above == 0 {
+            return Ok(false);
+        }
+        if below + above > count {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Price moves too far outside the profile grid",
+            ));
+        }
+
+        let bottom = self.levels[0];
+        self.levels
+            .splice(0..0, (1..=below).rev().map(|i| bottom - i as f64 * step));
+        self.levels
+            .extend((0..above).map(|i| top + i as f64 * step));
+
+        for volumes in self
+            .heatmap
+            .iter_mut()
+            .chain(stream.sessions.iter_mut())
+            .chain(std::iter::once(&mut stream.column_totals))
+        {
+            volumes.splice(0..0, std::iter::repeat_n(0.0, below));
+            volumes.resize(volumes.len() + above, 0.0);
+        }
+
+        // Nối phía dưới làm dịch index mọi level, range_row_totals giữ nguyên vì
+        // range vẫn bao đúng các cột cũ
+        if below > 0 {
+            for (begin, center, end) in &mut self.ranges {
+                *begin += below;
+                *center += below;
+                *end += below;
+            }
+            for summary in self.summaries.iter_mut().flatten() {
+                summary.poc += below;
+                summary.value_area_low += below;
+                summary.value_area_high += below;
+                summary
+                    .high_volume_nodes
+                    .iter_mut()
+                    .chain(summary.low_volume_nodes.iter_mut())
+                    .for_each(|level| *level += below);
+            }
+            for poc in &mut self.naked_pocs {
+                poc.level += below;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Chốt POC phiên vừa xong làm ứng viên naked POC và mở phiên mới bắt đầu ở `t`
+    fn open_session(&mut self, stream: &mut StreamState, t: i32) {
+        // Window sắp đóng phải có summary đúng trước khi thành lịch sử
+        self.refresh_stale(stream);
+
+        if let Some(volumes) = stream.sessions.back() {
+            let level = Self::poc_of(volumes);
+            if volumes[level] > 0.0 {
+                self.naked_pocs.push(NakedPoc {
+                    t: stream.session_start,
+                    level,
+                    price: self.price_of(level),
+                    volume: volumes[level],
+                });
+            }
+        }
+
+        stream.sessions.push_back(vec![0.0; self.levels.len()]);
+        if stream.sessions.len() > stream.overlap.max(1) {
+            stream.sessions.pop_front();
+        }
+        stream.session_start = t;
+
+        // Window mới gồm `overlap` phiên gần nhất, phiên mới còn rỗng
+        if stream.overlap > 0 && stream.sessions.len() == stream.overlap {
+            let mut row = vec![0.0; self.levels.len()];
+            for session in &stream.sessions {
+                for (volume, value) in row.iter_mut().zip(session) {
+                    *volume += value;
+                }
+            }
+            for (total, volume) in stream.column_totals.iter_mut().zip(&row) {
+                *total += volume;
+            }
+            for ((&(_, l_beg, l_end), row_totals), (peak, timeline)) in self
+                .ranges
+                .iter()
+                .zip(&mut stream.range_row_totals)
+                .zip(stream.range_peaks.iter_mut().zip(&mut self.timelines))
+            {
+                row_totals.push(row[l_beg..=l_end].iter().sum());
+                Self::extend_timeline(timeline, row_totals, peak);
+            }
+            self.summaries.push(Self::summarize(&row, &self.config));
+            self.heatmap.push(row);
+            self.rebuild_ranges(stream);
+        }
+    }
+
+    fn refresh_stale(&mut self, stream: &mut StreamState) {
+        if stream.summary_stale {
+            stream.summary_stale = false;
+            if let (Some(volumes), Some(summary)) = (self.heatmap.last(), self.summaries.last_mut())
+            {
+                *summary = Self::summarize(volumes, &self.config);
+            }
+        }
+    }
+
+    /// Làm mượt lại toàn bộ tổng theo cột, xếp hạng lại các level rồi gom range
+    fn rebuild_ranges(&mut self, stream: &mut StreamState) {
+        stream.smoothed = Self::smooth_column_totals(&stream.column_totals, stream.range_window);
+        stream.order = Self::rank_levels(&stream.smoothed);
+        stream.rank = vec![0; stream.order.len()];
+        for (position, &level) in stream.order.iter().enumerate() {
+            stream.rank[level] = position;
+        }
+        self.apply_ranges(stream, Self::group_ranges(&stream.order));
+    }
+
+    /// Thay `ranges`, range nào không đổi `[center, end]` (phần được cộng vào
+    /// tổng theo row) thì dùng lại tổng, peak và timeline đã có, chỉ range mới
+    /// phải cộng lại trên heatmap
+    fn apply_ranges(&mut self, stream: &mut StreamState, ranges: Vec<(usize, usize, usize)>) {
+        let mut previous = self
+            .ranges
+            .iter()
+            .zip(
+                stream
+                    .range_row_totals
+                    .drain(..)
+                    .zip(stream.range_peaks.drain(..))
+                    .zip(self.timelines.drain(..)),
+            )
+            .map(|(&(_, center, end), ((row_totals, peak), timeline))| {
+                ((center, end), (row_totals, peak, timeline))
+            })
+            .collect::<HashMap<_, _>>();
+
+        stream.range_of = vec![0; stream.column_totals.len()];
+        for (index, range) in ranges.iter().enumerate() {
+            stream.range_of[range.0..=range.2].fill(index);
+
+            let (row_totals, peak, timeline) =
+                previous.remove(&(range.1, range.2)).unwrap_or_else(|| {
+                    let row_totals = Self::range_row_totals(&self.heatmap, range);
+                    let peak = Self::peak_of(&row_totals);
+                    let timeline = Self::timeline_segments(&row_totals);
+                    (row_totals, peak, timeline)
+                });
+            stream.range_row_totals.push(row_totals);
+            stream.range_peaks.push(peak);
+            self.timelines.push(timeline);
+        }
+        self.ranges = ranges;
+    }
+
+    /// Cộng volume của `[low_bin, high_bin]` vào tổng row `row` của các range
+    /// chứa chúng rồi nối timeline của các range đó. Giống `range_row_totals`,
+    /// chỉ phần `[center, end]` của range được tính.
+    fn add_to_range_rows(
+        &mut self,
+        stream: &mut StreamState,
+        low_bin: usize,
+        high_bin: usize,
+        vol_per_bin: f64,
+        row: usize,
+    ) {
+        let mut level = low_bin;
+        while level <= high_bin {
+            let index = stream.range_of[level];
+            let (_, center, end) = self.ranges[index];
+            let (first, last) = (level.max(center), end.min(high_bin));
+            level = end + 1;
+            if first > last {
+                continue;
+            }
+
+            let row_totals = &mut stream.range_row_totals[index];
+            row_totals[row] += (last - first + 1) as f64 * vol_per_bin;
+            Self::extend_timeline(
+                &mut self.timelines[index],
+                row_totals,
+                &mut stream.range_peaks[index],
+            );
+        }
+    }
+
+    /// Cộng volume vào tổng theo cột, cập nhật phần `smoothed` có cửa sổ chạm
+    /// `[low_bin, high_bin]` và đẩy các level đó lên đúng hạng. Volume chỉ tăng
+    /// nên level chỉ có thể lên hạng. Trả `true` nếu thứ hạng thay đổi.
+    fn add_to_columns(
+        stream: &mut StreamState,
+        low_bin: usize,
+        high_bin: usize,
+        vol_per_bin: f64,
+    ) -> bool {
+        unsafe {
+            simd::add_scalar(&mut stream.column_totals, low_bin, high_bin, vol_per_bin);
+        }
+
+        let count = stream.column_totals.len();
+        let half = stream.range_window / 2;
+        let first = low_bin.saturating_sub(half);
+        let last = (high_bin + half).min(count - 1);
+        for level in first..=last {
+            // Cửa sổ làm mượt của `level` giống `smooth_column_totals`
+            let start = level.saturating_sub(half);
+            let end = usize::min(level + half + 1, count);
+            let covered = (end - 1).min(high_bin) + 1 - start.max(low_bin);
+            stream.smoothed[level] += covered as f64 * vol_per_bin / (end - start) as f64;
+        }
+
+        // Đẩy level tăng nhiều nhất trước thì mỗi level chỉ cần so với level đứng trước
+        let smoothed = &stream.smoothed;
+        let ahead =
+            |a: usize, b: usize| smoothed[a] > smoothed[b] || (smoothed[a] == smoothed[b] && a < b);
+        let mut changed = (first..=last).collect::<Vec<_>>();
+        changed.sort_by(|&a, &b| {
+            smoothed[b]
+                .partial_cmp(&smoothed[a])
+                .unwrap_or(Ordering::Equal)
+                .then(a.cmp(&b))
+        });
+
+        let mut reordered = false;
+        for level in changed {
+            let mut position = stream.rank[level];
+            while position > 0 && ahead(level, stream.order[position - 1]) {
+                let previous = stream.order[position - 1];
+                stream.order.swap(position - 1, position);
+                stream.rank[previous] = position;
+                position -= 1;
+                reordered = true;
+            }
+            stream.rank[level] = position;
+        }
+        reordered
+    }
+
+    /// Cập nhật timeline khi chỉ row cuối của `row_totals` vừa tăng hoặc vừa được
+    /// nối thêm. Peak mới làm đổi ngưỡng thì phải gom lại cả timeline.
+    fn extend_timeline(timeline: &mut VolumeTimeline, row_totals: &[f64], peak: &mut f64) {
+        let Some(last) = row_totals.len().checked_sub(1) else {
+            return;
+        };
+
+        let before = Self::timeline_threshold(*peak);
+        *peak = peak.max(row_totals[last]);
+        let threshold = Self::timeline_threshold(*peak);
+        if threshold != before {
+            *timeline = Self::timeline_segments(row_totals);
+            return;
+        }
+        if row_totals[last] <= threshold {
+            return;
+        }
+
+        match timeline.last_mut() {
+            Some(segment) if segment.1 >= last => segment.1 = last + 1,
+            _ if last > 0 && row_totals[last - 1] > threshold => {
+                timeline.push((last - 1, last + 1));
+            }
+            _ => {}
+        }
+    }
+
     // --- Getters ---
     pub fn heatmap(&self) -> &Vec<Vec<f64>> {
         &self.heatmap
@@ -182,12 +776,174 @@ impl VolumeProfile {
     pub fn timelines(&self) -> &Vec<Vec<(usize, usize)>> {
         &self.timelines
     }
+    pub fn summaries(&self) -> &Vec<Option<ProfileSummary>> {
+        &self.summaries
+    }
+    pub fn naked_pocs(&self) -> &Vec<NakedPoc> {
+        &self.naked_pocs
+    }
+
+    /// Giá giữa của level thứ `level`
+    pub fn price_of(&self, level: usize) -> f64 {
+        self.levels[level] + self.price_step / 2.0
+    }
+
+    /// Khoảng giá `(low, high)` của value area, tính theo biên của các level
+    pub fn value_area_prices(&self, summary: &ProfileSummary) -> (f64, f64) {
+        (
+            self.levels[summary.value_area_low],
+            self.levels[summary.value_area_high] + self.price_step,
+        )
+    }
 
     #[inline]
     fn timestamp_to_pin(t: i32, interval_in_hour: i32) -> i32 {
         t / (interval_in_hour * 60 * 60)
     }
 
+    /// Bước giá giữa hai level, tính y như `cumulate_volume_profile` để nến
+    /// cộng dồn bằng `update` rơi vào đúng bin như khi tính cả chuỗi
+    #[inline]
+    fn price_step(candles: &[CandleStick], levels: &[f64]) -> f64 {
+        if levels.is_empty() {
+            return 0.0;
+        }
+
+        let max_price = candles
+            .iter()
+            .map(|c| c.h)
+            .fold(f64::NEG_INFINITY, f64::max);
+        (max_price - levels[0]) / levels.len() as f64
+    }
+
+    /// Index nến đầu tiên của mỗi phiên `interval_in_hour`
+    fn session_starts(candles: &[CandleStick], interval_in_hour: i32) -> Vec<usize> {
+        let mut pin_start_indices = vec![0];
+        let mut current_pin = Self::timestamp_to_pin(candles[0].t, interval_in_hour);
+
+        for (i, candle) in candles.iter().enumerate().skip(1) {
+            let candle_pin = Self::timestamp_to_pin(candle.t, interval_in_hour);
+            if candle_pin > current_pin {
+                pin_start_indices.push(i);
+                current_pin = candle_pin;
+            }
+        }
+        pin_start_indices
+    }
+
+    // ==================== Value area & volume nodes ====================
+
+    /// Phân tích một window: POC, value area và HVN/LVN.
+    ///
+    /// Value area theo cách của CBOT: bắt đầu từ POC, mỗi bước so tổng volume
+    /// của 2 level kế tiếp phía trên với 2 level phía dưới, nhận cặp lớn hơn
+    /// (hoà thì lấy phía trên) cho tới khi đủ `config.value_area` tổng volume.
+    ///
+    /// HVN/LVN là đỉnh/đáy cục bộ của profile đã làm mượt; LVN chỉ xét bên trong
+    /// vùng có giao dịch vì hai đuôi profile luôn thấp.
+    pub fn summarize(volumes: &[f64], config: &ProfileConfig) -> Option<ProfileSummary> {
+        let total = volumes.iter().sum::<f64>();
+        if volumes.is_empty() || total <= 0.0 {
+            return None;
+        }
+
+        let poc = Self::poc_of(volumes);
+
+        let target = total * config.value_area;
+        let (mut low, mut high, mut covered) = (poc, poc, volumes[poc]);
+        while covered < target && (low > 0 || high + 1 < volumes.len()) {
+            let above_end = (high + 3).min(volumes.len());
+            let below_start = low.saturating_sub(2);
+            let above = volumes[high + 1..above_end].iter().sum::<f64>();
+            let below = volumes[below_start..low].iter().sum::<f64>();
+
+            if high + 1 < volumes.len() && (above >= below || low == 0) {
+                covered += above;
+                high = above_end - 1;
+            } else {
+                covered += below;
+                low = below_start;
+            }
+        }
+
+        let smoothed = Self::smooth_column_totals(volumes, config.node_smoothing.max(1));
+        let peak = smoothed.iter().cloned().fold(0.0, f64::max);
+        let first_traded = volumes.iter().position(|&v| v > 0.0).unwrap_or(0);
+        let last_traded = volumes.iter().rposition(|&v| v > 0.0).unwrap_or(0);
+
+        let mut high_volume_nodes = Vec::new();
+        let mut low_volume_nodes = Vec::new();
+        for i in 0..smoothed.len() {
+            let left = if i > 0 {
+                smoothed[i - 1]
+            } else {
+                f64::NEG_INFINITY
+            };
+            let right = smoothed.get(i + 1).cloned().unwrap_or(f64::NEG_INFINITY);
+
+            // Cạnh trái chặt, phải không chặt: vùng bằng phẳng chỉ lấy level đầu
+            if smoothed[i] > left && smoothed[i] >= right && smoothed[i] >= config.hvn_ratio * peak
+            {
+                high_volume_nodes.push(i);
+            }
+            if i > first_traded
+                && i < last_traded
+                && smoothed[i] < left
+                && smoothed[i] <= right
+                && smoothed[i] <= config.lvn_ratio * peak
+            {
+                low_volume_nodes.push(i);
+            }
+        }
+
+        Some(ProfileSummary {
+            poc,
+            value_area_low: low,
+            value_area_high: high,
+            high_volume_nodes,
+            low_volume_nodes,
+        })
+    }
+
+    #[inline]
+    fn poc_of(volumes: &[f64]) -> usize {
+        volumes
+            .iter()
+            .enumerate()
+            .fold(0, |best, (i, &v)| if v > volumes[best] { i } else { best })
+    }
+
+    /// POC của từng phiên (không overlap) mà chưa nến nào ở các phiên sau chạm
+    /// tới. Phiên cuối không tính vì chưa có dữ liệu sau nó.
+    fn find_naked_pocs(
+        candles: &[CandleStick],
+        sessions: &[Vec<f64>],
+        starts: &[usize],
+        levels: &[f64],
+        price_step: f64,
+    ) -> Vec<NakedPoc> {
+        sessions
+            .par_iter()
+            .zip(starts.par_iter())
+            .enumerate()
+            .filter_map(|(session, (volumes, &start))| {
+                let end = *starts.get(session + 1)?;
+                let level = Self::poc_of(volumes);
+                let price = levels[level] + price_step / 2.0;
+
+                let touched = candles[end..]
+                    .iter()
+                    .any(|candle| candle.l <= price && price <= candle.h);
+                (!touched && volumes[level] > 0.0).then(|| NakedPoc {
+                    t: candles[start].t,
+                    level,
+                    price,
+                    volume: volumes[level],
+                })
+            })
+            .collect()
+    }
+
     /// Tính timeline cho mỗi range: tìm các khoảng thời gian (row index) liên tục
     /// mà **tổng volume** của tất cả cột (price level) trong range vượt ngưỡng.
     ///
@@ -206,52 +962,61 @@ impl VolumeProfile {
 
         Ok(ranges
             .par_iter()
-            .map(|(_, l_beg, l_end)| {
-                let cols: Vec<usize> = (*l_beg..=*l_end).collect();
-                let row_count = heatmap.len();
-
-                // Tính tổng volume mỗi row trong range
-                let row_totals: Vec<f64> = (0..row_count)
-                    .into_par_iter()
-                    .map(|row| cols.iter().map(|&col| heatmap[row][col]).sum())
-                    .collect();
-
-                // Ngưỡng: 1% của max row total, nhưng tối thiểu > 0 để tránh nhiễu
-                let max_total = row_totals.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-                let threshold = if max_total > 0.0 {
-                    (max_total * 0.01).max(f64::EPSILON)
-                } else {
-                    f64::EPSILON
-                };
-
-                // Xác định các row có volume tích luỹ > ngưỡng
-                let active_rows: Vec<bool> = (0..row_count)
-                    .into_par_iter()
-                    .map(|row| row_totals[row] > threshold)
-                    .collect();
-
-                // Gom các row liên tục thành segments, lọc bỏ segment đơn (cần >= 2 row để gọi là tích luỹ)
-                let mut segments = Vec::new();
-                let mut i = 0;
-                while i < row_count {
-                    if active_rows[i] {
-                        let seg_start = i;
-                        while i < row_count && active_rows[i] {
-                            i += 1;
-                        }
-                        // Chỉ giữ segment có ít nhất 2 row (tích luỹ thực sự)
-                        if i - seg_start >= 2 {
-                            segments.push((seg_start, i));
-                        }
-                    } else {
-                        i += 1;
-                    }
-                }
-                segments
-            })
+            .map(|range| Self::timeline_segments(&Self::range_row_totals(heatmap, range)))
             .collect())
     }
 
+    /// Tổng volume mỗi row của heatmap trong các cột thuộc `range`
+    fn range_row_totals(
+        heatmap: &[Vec<f64>],
+        &(_, l_beg, l_end): &(usize, usize, usize),
+    ) -> Vec<f64> {
+        heatmap
+            .par_iter()
+            .map(|row| row[l_beg..=l_end].iter().sum())
+            .collect()
+    }
+
+    #[inline]
+    fn peak_of(row_totals: &[f64]) -> f64 {
+        row_totals.iter().cloned().fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// Ngưỡng: 1% của max row total, nhưng tối thiểu > 0 để tránh nhiễu
+    #[inline]
+    fn timeline_threshold(max_total: f64) -> f64 {
+        if max_total > 0.0 {
+            (max_total * 0.01).max(f64::EPSILON)
+        } else {
+            f64::EPSILON
+        }
+    }
+
+    /// Gom các row có tổng volume vượt ngưỡng thành segments (start, end)
+    fn timeline_segments(row_totals: &[f64]) -> VolumeTimeline {
+        let row_count = row_totals.len();
+        let threshold = Self::timeline_threshold(Self::peak_of(row_totals));
+
+        // Gom các row liên tục thành segments, lọc bỏ segment đơn (cần >= 2 row để gọi là tích luỹ)
+        let mut segments = Vec::new();
+        let mut i = 0;
+        while i < row_count {
+            if row_totals[i] > threshold {
+                let seg_start = i;
+                while i < row_count && row_totals[i] > threshold {
+                    i += 1;
+                }
+                // Chỉ giữ segment có ít nhất 2 row (tích luỹ thực sự)
+                if i - seg_start >= 2 {
+                    segments.push((seg_start, i));
+                }
+            } else {
+                i += 1;
+            }
+        }
+        segments
+    }
+
     #[inline]
     fn smooth_column_totals(totals: &[f64], window: usize) -> Vec<f64> {
         let half = window / 2;
@@ -273,30 +1038,54 @@ impl VolumeProfile {
             return Ok(Vec::new());
         }
 
-        // Song song hóa + SIMD: tính tổng volume của từng cột
-        let column_totals: Vec<f64> = (0..heatmap[0].len())
+        Ok(Self::ranges_from_totals(
+            &Self::column_totals(heatmap),
+            window,
+        ))
+    }
+
+    /// Song song hóa + SIMD: tính tổng volume của từng cột
+    fn column_totals(heatmap: &[Vec<f64>]) -> Vec<f64> {
+        if heatmap.is_empty() {
+            return Vec::new();
+        }
+
+        (0..heatmap[0].len())
             .into_par_iter()
             .map(|col| {
                 // dùng SIMD sum thay vì .sum::<f64>()
                 let col_slice: Vec<f64> = heatmap.iter().map(|row| row[col]).collect();
                 unsafe { simd::sum_f64(&col_slice) }
             })
-            .collect();
+            .collect()
+    }
+
+    fn ranges_from_totals(column_totals: &[f64], window: usize) -> Vec<(usize, usize, usize)> {
+        if column_totals.is_empty() {
+            return Vec::new();
+        }
 
         // Làm mượt bằng SIMD
-        let smoothed = Self::smooth_column_totals(&column_totals, window);
+        let smoothed = Self::smooth_column_totals(column_totals, window);
+        Self::group_ranges(&Self::rank_levels(&smoothed))
+    }
 
-        // Sắp xếp theo volume giảm dần
-        let sorted = smoothed
+    /// Index các level theo volume đã làm mượt giảm dần, bằng nhau thì index nhỏ
+    /// trước (sort ổn định)
+    fn rank_levels(smoothed: &[f64]) -> Vec<usize> {
+        smoothed
             .iter()
             .enumerate()
             .sorted_by(|a, b| b.1.partial_cmp(a.1).unwrap_or(Ordering::Greater))
             .map(|(index, _)| index)
-            .collect::<Vec<_>>();
+            .collect()
+    }
 
-        // Gom nhóm các cột thành range
+    /// Gom các cột thành range theo thứ tự `order`: mỗi cột nối vào range kề nó
+    /// nếu có, không thì mở range mới với cột đó làm tâm
+    fn group_ranges(order: &[usize]) -> Vec<(usize, usize, usize)> {
         let mut centers = BTreeMap::new();
-        for t in sorted {
+        for &t in order {
             let mut found = false;
             let mut update_data = None;
 
@@ -320,11 +1109,36 @@ impl VolumeProfile {
             }
         }
 
-        Ok(centers
+        centers
             .into_iter()
             .sorted_by_key(|k| k.1.2)
             .map(|(center, (begin, end, _))| (begin, center, end))
-            .collect())
+            .collect()
+    }
+
+    /// Gộp profile từng phiên thành các window `overlap` phiên liên tiếp,
+    /// `overlap = 0` là một window cho toàn bộ dữ liệu
+    fn windows_from_sessions(
+        sessions: &[Vec<f64>],
+        overlap: usize,
+    ) -> Result<Vec<Vec<f64>>, Error> {
+        let sum = |sessions: &[Vec<f64>]| {
+            let mut volumes = vec![0.0; sessions[0].len()];
+            for session in sessions {
+                for (volume, value) in volumes.iter_mut().zip(session) {
+                    *volume += value;
+                }
+            }
+            volumes
+        };
+
+        if overlap == 0 {
+            return Ok(vec![sum(sessions)]);
+        }
+        if sessions.len() < overlap {
+            return Err(Error::new(ErrorKind::InvalidData, "Overlap is too large"));
+        }
+        Ok(sessions.par_windows(overlap).map(sum).collect())
     }
 
     #[inline]
@@ -355,16 +1169,7 @@ impl VolumeProfile {
             .collect();
 
         // Chia pin theo interval
-        let mut pin_start_indices = vec![0];
-        let mut current_pin = Self::timestamp_to_pin(candles[0].t, interval_in_hour);
-
-        for (i, candle) in candles.iter().enumerate().skip(1) {
-            let candle_pin = Self::timestamp_to_pin(candle.t, interval_in_hour);
-            if candle_pin > current_pin {
-                pin_start_indices.push(i);
-                current_pin = candle_pin;
-            }
-        }
+        let pin_start_indices = Self::session_starts(candles, interval_in_hour);
 
         // Trường hợp overlap = 0 (tính toàn bộ dataset)
         if overlap == 0 {
@@ -413,21 +1218,32 @@ impl VolumeProfile {
         Ok((heatmap, price_levels))
     }
 
+    /// Các level `[low_bin, high_bin]` mà nến trải qua và volume chia cho mỗi level
     #[inline]
-    fn fill_volumes(
-        volumes: &mut [f64],
+    fn candle_bins(
         candle: &CandleStick,
         min_p: f64,
         bin_size: f64,
         levels: usize,
-    ) {
+    ) -> (usize, usize, f64) {
         let low_bin = ((candle.l - min_p) / bin_size).floor() as usize;
         let high_bin = ((candle.h - min_p) / bin_size).floor() as usize;
         let low_bin = low_bin.min(levels - 1);
         let high_bin = high_bin.min(levels - 1);
 
         let num_bins = (high_bin - low_bin + 1) as f64;
-        let vol_per_bin = candle.v / num_bins;
+        (low_bin, high_bin, candle.v / num_bins)
+    }
+
+    #[inline]
+    fn fill_volumes(
+        volumes: &mut [f64],
+        candle: &CandleStick,
+        min_p: f64,
+        bin_size: f64,
+        levels: usize,
+    ) {
+        let (low_bin, high_bin, vol_per_bin) = Self::candle_bins(candle, min_p, bin_size, levels);
 
         unsafe {
             simd::add_scalar(volumes, low_bin, high_bin, vol_per_bin);
@@ -531,4 +1347,188 @@ mod tests {
         assert_eq!(volumes.len(), vp1_2.heatmap().len());
         assert_eq!(volumes.len(), vp2_2.heatmap().len());
     }
+
+    #[test]
+    fn test_value_area_and_volume_nodes() {
+        let config = ProfileConfig::default();
+
+        // POC = 3, bước đầu nhận cặp phía trên (3 + 1 > 1 + 2): 8 + 4 = 12 >= 70% * 15
+        let summary =
+            VolumeProfile::summarize(&[0.0, 1.0, 2.0, 8.0, 3.0, 1.0, 0.0], &config).unwrap();
+        assert_eq!(summary.poc, 3);
+        assert_eq!((summary.value_area_low, summary.value_area_high), (3, 5));
+
+        // Profile hai đỉnh, không làm mượt
+        let config = ProfileConfig {
+            node_smoothing: 1,
+            ..config
+        };
+        let summary =
+            VolumeProfile::summarize(&[1.0, 5.0, 9.0, 5.0, 1.0, 0.5, 1.0, 6.0, 7.0, 2.0], &config)
+                .unwrap();
+        assert_eq!(summary.poc, 2);
+        assert_eq!(summary.high_volume_nodes, vec![2, 8]);
+        assert_eq!(summary.low_volume_nodes, vec![5]);
+
+        assert_eq!(VolumeProfile::summarize(&[0.0; 5], &config), None);
+    }
+
+    #[test]
+    fn test_naked_pocs() {
+        let candle = |t: i32, l: f64, h: f64| CandleStick {
+            t,
+            o: l,
+            h,
+            c: h,
+            l,
+            v: 1000.0,
+        };
+        let candles = vec![
+            // Ngày 1 giao dịch quanh 100, các ngày sau không quay lại
+            candle(1000000000, 99.0, 101.0),
+            candle(1000010000, 99.0, 101.0),
+            // Ngày 2 quanh 110-112, ngày 3 quay lại chạm POC này
+            candle(1000086400, 110.0, 112.0),
+            candle(1000172800, 109.5, 120.0),
+        ];
+
+        let vp = VolumeProfile::new_from_candles(&candles, 20, 1, 24).unwrap();
+        assert_eq!(vp.summaries().len(), 3);
+        assert!(vp.summaries().iter().all(Option::is_some));
+
+        let naked = vp.naked_pocs();
+        assert_eq!(naked.len(), 1);
+        assert_eq!(naked[0].t, 1000000000);
+        assert_eq!(naked[0].level, 0);
+        assert!((naked[0].price - vp.price_of(0)).abs() < 1e-9);
+        assert!(naked[0].price > 99.0 && naked[0].price < 101.0);
+    }
+
+    #[test]
+    fn test_incremental_update_matches_full_pass() {
+        // 6 ngày, mỗi ngày 4 nến; đáy và đỉnh toàn cục nằm ở ngày đầu để grid không đổi
+        let mut candles = vec![
+            CandleStick {
+                t: 1000000000,
+                o: 100.0,
+                h: 130.0,
+                c: 101.0,
+                l: 99.0,
+                v: 500.0,
+            },
+            CandleStick {
+                t: 1000003600,
+                o: 101.0,
+                h: 102.0,
+                c: 100.0,
+                l: 80.0,
+                v: 700.0,
+            },
+        ];
+        for i in 0..22 {
+            let base = 90.0 + ((i * 7) % 13) as f64 * 2.3;
+            candles.push(CandleStick {
+                t: 1000007200 + (i / 4) * 86400 + (i % 4) * 3600,
+                o: base,
+                h: base + 1.0 + (i % 3) as f64,
+                c: base + 0.5,
+                l: base - 0.7,
+                v: 300.0 + (i * 37 % 11) as f64 * 40.0,
+            });
+        }
+
+        let full = VolumeProfile::new_from_candles(&candles, 30, 2, 24).unwrap();
+        let mut incremental = VolumeProfile::new_from_candles(&candles[..10], 30, 2, 24).unwrap();
+        for candle in &candles[10..] {
+            incremental.update(candle).unwrap();
+
+            // Ranges và timelines luôn khớp với tính lại từ heatmap hiện tại
+            let heatmap = incremental.heatmap();
+            let ranges = VolumeProfile::cumulate_volume_range(heatmap, 3).unwrap();
+            assert_eq!(incremental.ranges(), &ranges);
+            assert_eq!(
+                incremental.timelines(),
+                &VolumeProfile::calculate_cumulate_volume_timeline(heatmap, &ranges).unwrap()
+            );
+        }
+        incremental.refresh();
+
+        assert_eq!(incremental.levels().len(), full.levels().len());
+        assert_eq!(incremental.heatmap().len(), full.heatmap().len());
+        for (actual, expected) in incremental.heatmap().iter().zip(full.heatmap()) {
+            for (a, e) in actual.iter().zip(expected) {
+                assert!((a - e).abs() < 1e-6, "{a} != {e}");
+            }
+        }
+        assert_eq!(incremental.summaries(), full.summaries());
+        assert_eq!(incremental.ranges(), full.ranges());
+        assert_eq!(incremental.timelines(), full.timelines());
+
+        let levels = |vp: &VolumeProfile| {
+            vp.naked_pocs()
+                .iter()
+                .map(|poc| (poc.t, poc.level))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(levels(&incremental), levels(&full));
+
+        // Nến thuộc phiên cũ hơn phiên đang chạy bị từ chối
+        let error = incremental.update(&candles[0]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_streaming_ticks_extend_grid() {
+        let tick = |timestamp: i64, price: f64, quantity: f64| Tick {
+            broker: "binance".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            price,
+            quantity,
+            timestamp,
+            ..Default::default()
+        };
+        let day = 86_400_000;
+
+        // Grid 10 level bước 1.0 quanh 100: [95, 105)
+        let mut vp = VolumeProfile::new_streaming(100.0, 1.0, 10, 1, 24).unwrap();
+        assert!(vp.heatmap().is_empty());
+
+        vp.update_tick(&tick(day, 100.2, 2.0)).unwrap();
+        vp.update_tick(&tick(day + 1000, 107.5, 1.0)).unwrap();
+        vp.refresh();
+        assert_eq!(vp.levels().len(), 13);
+        assert_eq!(vp.heatmap().len(), 1);
+        assert_eq!(vp.summaries()[0].as_ref().unwrap().poc, 5);
+
+        // Giá thủng đáy grid: nối thêm 3 level phía dưới, mọi index dịch theo
+        vp.update_tick(&tick(day + 2000, 92.5, 0.5)).unwrap();
+        assert_eq!(vp.summaries()[0].as_ref().unwrap().poc, 8);
+        vp.refresh();
+        assert_eq!(vp.levels().len(), 16);
+        assert!((vp.levels()[0] - 92.0).abs() < 1e-9);
+        assert_eq!(vp.summaries()[0].as_ref().unwrap().poc, 8);
+        assert!((vp.heatmap()[0][8] - 2.0).abs() < 1e-9);
+        assert!((vp.heatmap()[0][15] - 1.0).abs() < 1e-9);
+
+        // Ngày mới mở window mới, POC ngày trước thành naked POC cho tới khi bị chạm
+        vp.update_tick(&tick(2 * day, 95.0, 1.0)).unwrap();
+        assert_eq!(vp.heatmap().len(), 2);
+        assert_eq!(vp.naked_pocs().len(), 1);
+        assert_eq!(vp.naked_pocs()[0].level, 8);
+        assert!((vp.naked_pocs()[0].price - 100.5).abs() < 1e-9);
+
+        vp.update_tick(&tick(2 * day + 1000, 100.5, 1.0)).unwrap();
+        assert!(vp.naked_pocs().is_empty());
+
+        vp.retain_windows(1);
+        assert_eq!(vp.heatmap().len(), 1);
+        assert_eq!(vp.summaries().len(), 1);
+
+        // Giá lỗi quá xa grid bị từ chối thay vì nối hàng nghìn level
+        let error = vp
+            .update_tick(&tick(2 * day + 2000, 1000.0, 1.0))
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(vp.levels().len(), 16);
+    }
 }
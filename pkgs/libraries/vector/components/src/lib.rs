@@ -4,6 +4,7 @@ mod input;
 mod null;
 mod output;
 mod print;
+mod volume_profile;
 
 pub use converters::{WebSocketClient, WebSocketPolling};
 pub fn used() {}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::io::{Error, ErrorKind};
+
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+
+use analysis::VolumeProfile;
+use schemas::Tick;
+use vector_config_macro::transform;
+use vector_runtime::{Component, Identify, Message, Outbound};
+
+/// Giữ một volume profile cuộn cho mỗi cặp (broker, symbol) từ luồng `Tick`,
+/// mỗi tick đầu vào phát ra snapshot của window cuối: POC, value area, HVN/LVN
+/// và các naked POC còn lại.
+#[transform]
+pub struct RollingVolumeProfile {
+    pub id: String,
+    pub inputs: Vec<String>,
+
+    #[serde(default = "default_number_of_levels")]
+    pub number_of_levels: usize,
+
+    /// Bước giá tính theo % giá của tick đầu tiên
+    #[serde(default = "default_price_step_percent")]
+    pub price_step_percent: f64,
+
+    #[serde(default = "default_overlap")]
+    pub overlap: usize,
+
+    #[serde(default = "default_interval_in_hour")]
+    pub interval_in_hour: i32,
+
+    /// Số window tối đa giữ lại cho mỗi symbol, bỏ trống là giữ tất cả
+    pub max_windows: Option<usize>,
+}
+
+fn default_number_of_levels() -> usize {
+    100
+}
+
+fn default_price_step_percent() -> f64 {
+    0.1
+}
+
+fn default_overlap() -> usize {
+    1
+}
+
+fn default_interval_in_hour() -> i32 {
+    24
+}
+
+fn snapshot(tick: &Tick, profile: &VolumeProfile) -> Value {
+    let summary = profile.summaries().last().cloned().flatten();
+    let prices = |levels: &[usize]| -> Vec<f64> {
+        levels
+            .iter()
+            .map(|&level| profile.price_of(level))
+            .collect()
+    };
+
+    json!({
+        "broker": tick.broker,
+        "symbol": tick.symbol,
+        "timestamp": tick.timestamp,
+        "levels": profile.levels(),
+        "volumes": profile.heatmap().last(),
+        "poc": summary.as_ref().map(|summary| profile.price_of(summary.poc)),
+        "value_area": summary
+            .as_ref()
+            .map(|summary| profile.value_area_prices(summary)),
+        "high_volume_nodes": summary
+            .as_ref()
+            .map(|summary| prices(&summary.high_volume_nodes))
+            .unwrap_or_default(),
+        "low_volume_nodes": summary
+            .as_ref()
+            .map(|summary| prices(&summary.low_volume_nodes))
+            .unwrap_or_default(),
+        "naked_pocs": profile
+            .naked_pocs()
+            .iter()
+            .map(|poc| json!({ "t": poc.t, "price": poc.price, "volume": poc.volume }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+impl RollingVolumeProfile {
+    /// Cộng tick vào profile của symbol rồi trả snapshot, `None` khi tick bị bỏ qua
+    fn roll(
+        &self,
+        profiles: &mut HashMap<(String, String), VolumeProfile>,
+        tick: &Tick,
+    ) -> Option<Value> {
+        let profile = match profiles.entry((tick.broker.clone(), tick.symbol.clone())) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                // Grid khởi tạo quanh giá tick đầu tiên, sau đó tự mở rộng theo giá
+                let profile = VolumeProfile::new_streaming(
+                    tick.price,
+                    tick.price * self.price_step_percent / 100.0,
+                    self.number_of_levels,
+                    self.overlap,
+                    self.interval_in_hour,
+                )
+                .ok()?;
+                entry.insert(profile)
+            }
+        };
+
+        // Tick lỗi hoặc đến trễ so với phiên đang chạy thì bỏ qua
+        profile.update_tick(tick).ok()?;
+        if let Some(keep) = self.max_windows {
+            profile.retain_windows(keep);
+        }
+
+        // `update_tick` không tự tính lại summary của window đang chạy
+        profile.refresh();
+        Some(snapshot(tick, profile))
+    }
+}
+
+impl_rolling_volume_profile!(
+    async fn run(
+        &self,
+        _: usize,
+        rx: &mut mpsc::Receiver<Message>,
+        tx: Outbound,
+    ) -> Result<(), Error> {
+        let mut profiles = HashMap::<(String, String), VolumeProfile>::new();
+
+        while let Some(message) = rx.recv().await {
+            let tick = match serde_json::from_value::<Tick>(message.payload) {
+                Ok(tick) => tick,
+                Err(_) => {
+                    continue;
+                }
+            };
+
+            let Some(payload) = self.roll(&mut profiles, &tick) else {
+                continue;
+            };
+            for stream in &tx.streams {
+                if let Err(error) = stream
+                    .send(Message {
+                        payload: payload.clone(),
+                    })
+                    .await
+                {
+                    return Err(Error::new(
+                        ErrorKind::BrokenPipe,
+                        format!("Failed to forward volume profile downstream: {error}"),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roll_ticks_and_evict_windows() {
+        let component = RollingVolumeProfile {
+            id: "volume_profile".to_string(),
+            inputs: vec!["ticks".to_string()],
+            number_of_levels: 100,
+            price_step_percent: 1.0,
+            overlap: 1,
+            interval_in_hour: 1,
+            max_windows: Some(2),
+        };
+        // Đầu giờ, timestamp tính bằng mili-giây
+        const HOUR: i64 = 1_699_999_200_000;
+        let tick = |symbol: &str, hour: i64, second: i64, price: f64, quantity: f64| Tick {
+            broker: "binance".to_string(),
+            symbol: symbol.to_string(),
+            price,
+            quantity,
+            timestamp: HOUR + hour * 3_600_000 + second * 1000,
+            candlestick: None,
+        };
+        let volume = |snapshot: &Value| {
+            snapshot["volumes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|volume| volume.as_f64().unwrap())
+                .sum::<f64>()
+        };
+        let mut profiles = HashMap::new();
+
+        // Phiên đầu: POC ở level của giá 101 (bước giá 1, level 101 = [101, 102))
+        let mut last = None;
+        for (second, price, quantity) in [(0, 100.0, 1.0), (60, 101.0, 5.0), (120, 100.0, 1.0)] {
+            last = component.roll(&mut profiles, &tick("BTCUSDT", 0, second, price, quantity));
+        }
+        let snapshot = last.unwrap();
+        assert_eq!(snapshot["poc"], json!(101.5));
+        assert_eq!(volume(&snapshot), 7.0);
+
+        // Phiên mới cuộn sang window mới, POC phiên cũ thành naked POC
+        let snapshot = component
+            .roll(&mut profiles, &tick("BTCUSDT", 1, 0, 102.0, 2.0))
+            .unwrap();
+        assert_eq!(snapshot["poc"], json!(102.5));
+        assert_eq!(volume(&snapshot), 2.0);
+        assert_eq!(snapshot["naked_pocs"][0]["price"], json!(101.5));
+
+        // Phiên thứ ba: chỉ giữ 2 window gần nhất, naked POC vẫn còn
+        let snapshot = component
+            .roll(&mut profiles, &tick("BTCUSDT", 2, 0, 103.0, 1.0))
+            .unwrap();
+        let key = ("binance".to_string(), "BTCUSDT".to_string());
+        let profile = &profiles[&key];
+        assert_eq!(profile.heatmap().len(), 2);
+        assert_eq!(profile.summaries().len(), 2);
+        assert_eq!(volume(&snapshot), 1.0);
+        let naked = snapshot["naked_pocs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|poc| poc["price"].as_f64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(naked, vec![101.5, 102.5]);
+
+        // Tick trễ về phiên cũ bị bỏ qua, symbol khác có profile riêng
+        assert!(
+            component
+                .roll(&mut profiles, &tick("BTCUSDT", 0, 0, 100.0, 1.0))
+                .is_none()
+        );
+        let other = component
+            .roll(&mut profiles, &tick("ETHUSDT", 2, 0, 50.0, 3.0))
+            .unwrap();
+        assert_eq!(other["poc"], json!(50.25));
+        assert_eq!(profiles.len(), 2);
+    }
+}